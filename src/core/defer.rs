@@ -1,3 +1,5 @@
+use std::{cell::RefCell, rc::Rc};
+
 /// Ensure the given closure is executed once the surrounding scope closes despite panics.
 /// Inspired by Golang's `defer`, Java's finally and Ruby's `ensure`.
 ///
@@ -18,15 +20,206 @@
 /// }
 /// assert_eq!(tmpdir.exists(), false);
 /// ```
-pub fn defer<T: FnMut()>(f: T) -> impl Drop {
-    Defer(f)
+pub fn defer<T: FnMut()>(f: T) -> Defer<T> {
+    Defer(Some(f))
 }
 
-pub struct Defer<T: FnMut()>(T);
+pub struct Defer<T: FnMut()>(Option<T>);
+
+impl<T: FnMut()> Defer<T> {
+    /// Consume the guard preventing the closure from ever being executed. Useful when cleanup
+    /// should only happen on the error path.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("core_defer_doc_cancel");
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// assert!(sys::mkdir(&tmpdir).is_ok());
+    /// {
+    ///     let guard = defer(|| sys::remove_all(&tmpdir).unwrap());
+    ///     guard.cancel();
+    /// }
+    /// assert_eq!(tmpdir.exists(), true);
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// ```
+    pub fn cancel(mut self) {
+        self.disarm();
+    }
+
+    /// Prevent the closure from being executed when the guard is dropped without consuming it.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("core_defer_doc_disarm");
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// assert!(sys::mkdir(&tmpdir).is_ok());
+    /// {
+    ///     let mut guard = defer(|| sys::remove_all(&tmpdir).unwrap());
+    ///     guard.disarm();
+    /// }
+    /// assert_eq!(tmpdir.exists(), true);
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// ```
+    pub fn disarm(&mut self) {
+        self.0 = None;
+    }
+}
 
 impl<T: FnMut()> Drop for Defer<T> {
     fn drop(&mut self) {
-        (self.0)();
+        if let Some(f) = self.0.as_mut() {
+            f();
+        }
+    }
+}
+
+/// Ensure the given closure is executed with mutable access to the given `state` once the
+/// surrounding scope closes despite panics. Avoids the need for `Cell` or `RefCell` when the
+/// cleanup needs to accumulate results.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let mut count = 0;
+/// {
+///     let mut guard = finally_mut(&mut count, |x| **x += 1);
+///     **guard.state_mut() += 1;
+/// }
+/// assert_eq!(count, 2);
+/// ```
+pub fn finally_mut<S, T: FnOnce(&mut S)>(state: S, func: T) -> FinallyMut<S, T> {
+    FinallyMut { state: Some(state), func: Some(func) }
+}
+
+pub struct FinallyMut<S, T: FnOnce(&mut S)> {
+    state: Option<S>,
+    func: Option<T>,
+}
+
+impl<S, T: FnOnce(&mut S)> FinallyMut<S, T> {
+    /// Returns a mutable reference to the state to allow accumulating results before the
+    /// closure is executed.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let mut guard = finally_mut(vec![1], |x| x.push(3));
+    /// guard.state_mut().push(2);
+    /// assert_eq!(guard.take(), vec![1, 2, 3]);
+    /// ```
+    pub fn state_mut(&mut self) -> &mut S {
+        self.state.as_mut().unwrap()
+    }
+
+    /// Execute the closure now rather than at drop and return the final state.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let guard = finally_mut(1, |x| *x += 1);
+    /// assert_eq!(guard.take(), 2);
+    /// ```
+    pub fn take(mut self) -> S {
+        let mut state = self.state.take().unwrap();
+        if let Some(func) = self.func.take() {
+            func(&mut state);
+        }
+        state
+    }
+}
+
+impl<S, T: FnOnce(&mut S)> Drop for FinallyMut<S, T> {
+    fn drop(&mut self) {
+        if let (Some(state), Some(func)) = (self.state.as_mut(), self.func.take()) {
+            func(state);
+        }
+    }
+}
+
+/// Ensure the given closure is executed once the surrounding scope closes despite panics storing
+/// its return value in a shared slot. Use `handle` to get a cloneable `FinallyHandle` for reading
+/// the cleanup outcome after the guard has dropped.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let handle = {
+///     let guard = finally_result(|| 42);
+///     guard.handle()
+/// };
+/// assert_eq!(handle.take(), Some(42));
+/// ```
+pub fn finally_result<R, T: FnOnce() -> R>(func: T) -> FinallyResult<R, T> {
+    FinallyResult { func: Some(func), handle: FinallyHandle(Rc::new(RefCell::new(None))) }
+}
+
+pub struct FinallyResult<R, T: FnOnce() -> R> {
+    func: Option<T>,
+    handle: FinallyHandle<R>,
+}
+
+impl<R, T: FnOnce() -> R> FinallyResult<R, T> {
+    /// Returns a handle to the slot the closure's return value will be stored in once executed.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let guard = finally_result(|| "done");
+    /// let handle = guard.handle();
+    /// assert_eq!(handle.is_set(), false);
+    /// drop(guard);
+    /// assert_eq!(handle.get(), Some("done"));
+    /// ```
+    pub fn handle(&self) -> FinallyHandle<R> {
+        self.handle.clone()
+    }
+}
+
+impl<R, T: FnOnce() -> R> Drop for FinallyResult<R, T> {
+    fn drop(&mut self) {
+        if let Some(func) = self.func.take() {
+            let result = func();
+            *self.handle.0.borrow_mut() = Some(result);
+        }
+    }
+}
+
+/// FinallyHandle provides shared access to the return value of a `finally_result` closure
+#[derive(Debug)]
+pub struct FinallyHandle<R>(Rc<RefCell<Option<R>>>);
+
+impl<R> Clone for FinallyHandle<R> {
+    fn clone(&self) -> Self {
+        FinallyHandle(self.0.clone())
+    }
+}
+
+impl<R> FinallyHandle<R> {
+    /// Returns a copy of the closure's return value if it has been executed.
+    pub fn get(&self) -> Option<R>
+    where
+        R: Clone,
+    {
+        self.0.borrow().clone()
+    }
+
+    /// Returns true if the closure has been executed and its return value not yet taken.
+    pub fn is_set(&self) -> bool {
+        self.0.borrow().is_some()
+    }
+
+    /// Takes the closure's return value out of the slot if it has been executed.
+    pub fn take(&self) -> Option<R> {
+        self.0.borrow_mut().take()
     }
 }
 
@@ -46,6 +239,33 @@ mod tests {
         panic::set_hook(Box::new(|_| {}));
     }
 
+    #[test]
+    fn test_defer_cancel() {
+        // runs normally on drop
+        let obj = Cell::new(1);
+        {
+            let _defer = defer(|| obj.set(2));
+        }
+        assert_eq!(obj.get(), 2);
+
+        // doesn't run after cancel
+        let obj = Cell::new(1);
+        {
+            let guard = defer(|| obj.set(2));
+            guard.cancel();
+            assert_eq!(obj.get(), 1);
+        }
+        assert_eq!(obj.get(), 1);
+
+        // doesn't run after disarm
+        let obj = Cell::new(1);
+        {
+            let mut guard = defer(|| obj.set(2));
+            guard.disarm();
+        }
+        assert_eq!(obj.get(), 1);
+    }
+
     #[test]
     fn test_defer_fires_even_with_panic() {
         supress_panic_err();
@@ -64,4 +284,63 @@ mod tests {
         let _defer = defer(|| obj.set(2));
         assert_eq!(1, obj.get());
     }
+
+    #[test]
+    fn test_finally_mut() {
+        // accumulate through the state and the closure
+        let mut count = 0;
+        {
+            let mut guard = finally_mut(&mut count, |x| **x += 10);
+            for _ in 0..3 {
+                **guard.state_mut() += 1;
+            }
+            assert_eq!(**guard.state_mut(), 3);
+        }
+        assert_eq!(count, 13);
+
+        // take executes the closure once and returns the state
+        let guard = finally_mut(vec![], |x: &mut Vec<&str>| x.push("cleanup"));
+        assert_eq!(guard.take(), vec!["cleanup"]);
+    }
+
+    #[test]
+    fn test_finally_mut_fires_even_with_panic() {
+        supress_panic_err();
+
+        let mut count = 0;
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            let _guard = finally_mut(&mut count, |x| **x += 1);
+            panic!();
+        }));
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_finally_result() {
+        // cleanup status is readable after the guard drops
+        let handle = {
+            let guard = finally_result(|| sys::remove_all(PathBuf::from("tests/temp/core_finally_result")).map_or(1, |_| 0));
+            let handle = guard.handle();
+            assert_eq!(handle.is_set(), false);
+            handle
+        };
+        assert_eq!(handle.is_set(), true);
+        assert_eq!(handle.get(), Some(0));
+        assert_eq!(handle.clone().take(), Some(0));
+        assert_eq!(handle.is_set(), false);
+        assert_eq!(handle.take(), None);
+    }
+
+    #[test]
+    fn test_finally_result_fires_even_with_panic() {
+        supress_panic_err();
+
+        let mut handle = None;
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            let guard = finally_result(|| 2);
+            handle = Some(guard.handle());
+            panic!();
+        }));
+        assert_eq!(handle.unwrap().take(), Some(2));
+    }
 }
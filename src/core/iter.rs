@@ -43,6 +43,21 @@ where
 
 /// Iterator adaptors to simplify some operations
 pub trait IteratorExt: Iterator {
+    /// If the iterator yields no elements `None` will be returned, if it yields a single element
+    /// that element will be returned, otherwise an error will be returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use fungus::core::*;
+    ///
+    /// assert_eq!((0..10).filter(|&x| x == 11).at_most_one().unwrap(), None);
+    /// assert_eq!((0..10).filter(|&x| x == 2).at_most_one().unwrap(), Some(2));
+    /// assert!((0..10).filter(|&x| x > 2).at_most_one().is_err());
+    /// ```
+    fn at_most_one(self) -> FuResult<Option<Self::Item>>
+    where
+        Self: Sized;
+
     /// Consume the entire iterator eagerly up until but not including the last call to
     /// get None. Allows caller to then call next and get None.
     ///
@@ -87,6 +102,20 @@ pub trait IteratorExt: Iterator {
     where
         Self: Sized;
 
+    /// Returns the first element of the iterator or the given `default` if the iterator yields
+    /// no elements.
+    ///
+    /// # Examples
+    /// ```
+    /// use fungus::core::*;
+    ///
+    /// assert_eq!((0..10).filter(|&x| x > 2).first_or(0), 3);
+    /// assert_eq!((0..10).filter(|&x| x > 10).first_or(0), 0);
+    /// ```
+    fn first_or(self, default: Self::Item) -> Self::Item
+    where
+        Self: Sized;
+
     /// If the iterator yields at least one element, the first element will be returned,
     /// otherwise an error will be returned.
     ///
@@ -165,12 +194,40 @@ pub trait IteratorExt: Iterator {
     fn some(self) -> bool
     where
         Self: Sized;
+
+    /// Collect an iterator of results into a vector of the successful values, short-circuiting
+    /// on and returning the first error encountered.
+    ///
+    /// # Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(vec!["1", "2"].into_iter().map(|x| x.parse::<u32>()).try_collect_vec().unwrap(), vec![1, 2]);
+    /// assert!(vec!["1", "x"].into_iter().map(|x| x.parse::<u32>()).try_collect_vec().is_err());
+    /// ```
+    fn try_collect_vec<U, E>(self) -> FuResult<Vec<U>>
+    where
+        Self: Sized+Iterator<Item = Result<U, E>>,
+        E: Into<FuError>;
 }
 
 impl<T: ?Sized> IteratorExt for T
 where
     T: Iterator,
 {
+    fn at_most_one(mut self) -> FuResult<Option<Self::Item>>
+    where
+        Self: Sized,
+    {
+        match self.next() {
+            Some(item) => match self.next() {
+                Some(_) => Err(IterError::multiple_items_found().into()),
+                None => Ok(Some(item)),
+            },
+            None => Ok(None),
+        }
+    }
+
     #[allow(clippy::all)]
     fn consume(mut self) -> Self
     where
@@ -205,6 +262,13 @@ where
         self.next()
     }
 
+    fn first_or(mut self, default: Self::Item) -> Self::Item
+    where
+        Self: Sized,
+    {
+        self.next().unwrap_or(default)
+    }
+
     fn first_result(mut self) -> FuResult<Self::Item>
     where
         Self: Sized,
@@ -284,6 +348,14 @@ where
     {
         self.next().is_some()
     }
+
+    fn try_collect_vec<U, E>(self) -> FuResult<Vec<U>>
+    where
+        Self: Sized+Iterator<Item = Result<U, E>>,
+        E: Into<FuError>,
+    {
+        self.map(|x| x.map_err(|e| e.into())).collect()
+    }
 }
 
 #[cfg(test)]
@@ -335,6 +407,14 @@ mod tests {
         assert_eq!(vec![0, 1, 2, 3].into_iter().slice(4, -1).next(), None); // left out of bounds consumes everything
     }
 
+    #[test]
+    fn test_at_most_one() {
+        assert_eq!((0..10).filter(|&x| x == 11).at_most_one().unwrap(), None);
+        assert_eq!((0..10).filter(|&x| x == 2).at_most_one().unwrap(), Some(2));
+        assert_eq!((0..10).filter(|&x| x > 2).at_most_one().unwrap_err().downcast_ref::<IterError>(), Some(&IterError::multiple_items_found()));
+        assert_eq!(PathBuf::from("foo").components().at_most_one().unwrap(), Some(Component::Normal(OsStr::new("foo"))));
+    }
+
     #[test]
     fn test_consume() {
         assert_eq!(vec![0].into_iter().nth(0), Some(0));
@@ -369,6 +449,13 @@ mod tests {
         assert_ne!(Component::Normal(OsStr::new("bar")), PathBuf::from("foo/bar").components().first().unwrap());
     }
 
+    #[test]
+    fn test_first_or() {
+        assert_eq!((0..10).filter(|&x| x == 11).first_or(42), 42);
+        assert_eq!((0..10).filter(|&x| x == 2).first_or(42), 2);
+        assert_eq!((0..10).filter(|&x| x > 2).first_or(42), 3);
+    }
+
     #[test]
     fn test_first_result() {
         assert_eq!(Component::Normal(OsStr::new("foo")), PathBuf::from("foo/bar").components().first_result().unwrap());
@@ -384,6 +471,7 @@ mod tests {
     #[test]
     fn test_single() {
         assert_eq!((0..10).filter(|&x| x == 2).single().unwrap(), 2);
+        assert!(PathBuf::new().components().single().contains_err(IterError::item_not_found()));
         assert_eq!((0..10).filter(|&x| x > 2).single().unwrap_err().downcast_ref::<IterError>(), Some(&IterError::multiple_items_found()));
         assert_eq!((0..10).filter(|&x| x > 2 && x < 5).single().unwrap_err().downcast_ref::<IterError>(), Some(&IterError::multiple_items_found()));
     }
@@ -393,4 +481,23 @@ mod tests {
         assert_eq!((0..10).filter(|&x| x == 2).some(), true);
         assert_eq!((0..10).filter(|&x| x == 11).some(), false);
     }
+
+    #[test]
+    fn test_try_collect_vec() {
+        // all ok
+        let items: Vec<FuResult<u32>> = vec![Ok(1), Ok(2), Ok(3)];
+        assert_eq!(items.into_iter().try_collect_vec().unwrap(), vec![1, 2, 3]);
+        assert_eq!(Vec::<FuResult<u32>>::new().into_iter().try_collect_vec().unwrap(), Vec::<u32>::new());
+
+        // error midway propagates and short-circuits
+        let mut visited = 0;
+        let items: Vec<FuResult<u32>> = vec![Ok(1), Err(IterError::item_not_found().into()), Ok(3)];
+        let err = items.into_iter().inspect(|_| visited += 1).try_collect_vec().unwrap_err();
+        assert_eq!(err.downcast_ref::<IterError>(), Some(&IterError::item_not_found()));
+        assert_eq!(visited, 2);
+
+        // other error types are converted
+        let err = vec!["1", "x", "3"].into_iter().map(|x| x.parse::<u32>()).try_collect_vec().unwrap_err();
+        assert!(err.downcast_ref::<std::num::ParseIntError>().is_some());
+    }
 }
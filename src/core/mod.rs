@@ -6,9 +6,13 @@ pub mod macros;
 mod defer;
 mod iter;
 mod option;
+mod result;
+mod retry;
 mod string;
 
 pub use defer::*;
 pub use iter::*;
 pub use option::*;
+pub use result::*;
+pub use retry::*;
 pub use string::*;
@@ -0,0 +1,49 @@
+use crate::errors::*;
+use std::error::Error as StdError;
+
+pub trait ResultExt<T> {
+    fn contains_err<E>(&self, err: E) -> bool
+    where
+        E: StdError+PartialEq+'static;
+}
+
+impl<T> ResultExt<T> for FuResult<T> {
+    /// Returns `true` if the result is an [`Err`] value that downcasts to an error equal to the
+    /// given error.
+    ///
+    /// # Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let x: FuResult<u32> = Err(IterError::item_not_found().into());
+    /// assert!(x.contains_err(IterError::item_not_found()));
+    ///
+    /// let x: FuResult<u32> = Err(IterError::multiple_items_found().into());
+    /// assert!(!x.contains_err(IterError::item_not_found()));
+    ///
+    /// let x: FuResult<u32> = Ok(2);
+    /// assert!(!x.contains_err(IterError::item_not_found()));
+    /// ```
+    fn contains_err<E>(&self, err: E) -> bool
+    where
+        E: StdError+PartialEq+'static,
+    {
+        match self {
+            Ok(_) => false,
+            Err(e) => e.downcast_ref::<E>() == Some(&err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_contains_err() {
+        assert_eq!(PathBuf::new().components().single().contains_err(IterError::item_not_found()), true);
+        assert_eq!(PathBuf::new().components().single().contains_err(IterError::multiple_items_found()), false);
+        assert_eq!(PathBuf::new().components().single().contains_err(PathError::Empty), false);
+        assert_eq!(PathBuf::from("foo").components().single().contains_err(IterError::item_not_found()), false);
+    }
+}
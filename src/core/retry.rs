@@ -0,0 +1,136 @@
+use crate::errors::*;
+use std::{thread, time::Duration};
+
+/// Call the given closure `f` up to `attempts` times until it succeeds, sleeping between attempts
+/// starting with the given `backoff` and doubling it after each failure. Returns the last error
+/// once all attempts are exhausted. An `attempts` of 0 is treated as 1.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+/// use std::time::Duration;
+///
+/// let mut calls = 0;
+/// let result = retry(3, Duration::from_millis(1), || {
+///     calls += 1;
+///     match calls {
+///         1 => Err(NetError::timeout("foo").into()),
+///         _ => Ok(calls),
+///     }
+/// });
+/// assert_eq!(result.unwrap(), 2);
+/// ```
+pub fn retry<T, F: FnMut() -> FuResult<T>>(attempts: u32, backoff: Duration, f: F) -> FuResult<T> {
+    retry_if(attempts, backoff, |_| true, f)
+}
+
+/// Variant of `retry` that only retries when the given predicate `pred` returns true for the
+/// error, allowing callers to retry transient errors while failing fast on the rest. The error is
+/// returned as is when `pred` returns false. `retry` wraps this with a predicate that always
+/// retries.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+/// use std::time::Duration;
+///
+/// let mut calls = 0;
+/// let result: FuResult<()> = retry_if(3, Duration::from_millis(1), |x| x.is::<NetError>(), || {
+///     calls += 1;
+///     Err(PathError::does_not_exist("foo").into())
+/// });
+/// assert!(result.is_err());
+/// assert_eq!(calls, 1);
+/// ```
+pub fn retry_if<T, F, P>(attempts: u32, backoff: Duration, mut pred: P, mut f: F) -> FuResult<T>
+where
+    F: FnMut() -> FuResult<T>,
+    P: FnMut(&FuError) -> bool,
+{
+    let mut delay = backoff;
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= attempts || !pred(&err) => return Err(err),
+            Err(_) => {
+                thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            },
+        }
+    }
+}
+
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_retry() {
+        // fails twice then succeeds
+        let mut calls = 0;
+        let start = Instant::now();
+        let result = retry(5, Duration::from_millis(10), || {
+            calls += 1;
+            match calls {
+                1 | 2 => Err(NetError::timeout("foo").into()),
+                _ => Ok("bar"),
+            }
+        });
+        assert_eq!(result.unwrap(), "bar");
+        assert_eq!(calls, 3);
+
+        // backoff doubles i.e. 10ms then 20ms
+        assert!(start.elapsed() >= Duration::from_millis(30));
+
+        // always fails returning the last error
+        let mut calls = 0;
+        let result: FuResult<()> = retry(3, Duration::from_millis(1), || {
+            calls += 1;
+            Err(NetError::http("foo", 500 + calls).into())
+        });
+        assert_eq!(result.unwrap_err().downcast_ref::<NetError>(), Some(&NetError::http("foo", 503)));
+        assert_eq!(calls, 3);
+
+        // zero attempts still calls once
+        let mut calls = 0;
+        let result: FuResult<()> = retry(0, Duration::from_millis(1), || {
+            calls += 1;
+            Err(NetError::timeout("foo").into())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_retry_if() {
+        let transient = |x: &FuError| x.downcast_ref::<NetError>() == Some(&NetError::timeout("foo"));
+
+        // retries transient errors
+        let mut calls = 0;
+        let result = retry_if(3, Duration::from_millis(1), transient, || {
+            calls += 1;
+            match calls {
+                1 | 2 => Err(NetError::timeout("foo").into()),
+                _ => Ok(calls),
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        // fails fast on other errors
+        let mut calls = 0;
+        let result: FuResult<()> = retry_if(3, Duration::from_millis(1), transient, || {
+            calls += 1;
+            match calls {
+                1 => Err(NetError::timeout("foo").into()),
+                _ => Err(NetError::dns("foo").into()),
+            }
+        });
+        assert_eq!(result.unwrap_err().downcast_ref::<NetError>(), Some(&NetError::dns("foo")));
+        assert_eq!(calls, 2);
+    }
+}
@@ -1,7 +1,159 @@
 use crate::errors::*;
 use std::{ffi::OsStr, path::Path, str};
 
+pub trait CaseExt {
+    /// Returns a new [`String`] in lower camel case i.e. `fooBarBaz`. Words are split at
+    /// underscores, hyphens, whitespace and camel case humps.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("foo_bar-baz".to_camel_case(), "fooBarBaz".to_string());
+    /// ```
+    fn to_camel_case(&self) -> String;
+
+    /// Returns a new [`String`] in kebab case i.e. `foo-bar-baz`. Words are split at
+    /// underscores, hyphens, whitespace and camel case humps.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("FooBarBaz".to_kebab_case(), "foo-bar-baz".to_string());
+    /// ```
+    fn to_kebab_case(&self) -> String;
+
+    /// Returns a new [`String`] in snake case i.e. `foo_bar_baz`. Words are split at
+    /// underscores, hyphens, whitespace and camel case humps.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("FooBarBaz".to_snake_case(), "foo_bar_baz".to_string());
+    /// ```
+    fn to_snake_case(&self) -> String;
+
+    /// Returns a new [`String`] in title case i.e. `Foo Bar Baz`. Words are split at
+    /// underscores, hyphens, whitespace and camel case humps.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("foo_bar".to_title_case(), "Foo Bar".to_string());
+    /// ```
+    fn to_title_case(&self) -> String;
+}
+
+impl CaseExt for str {
+    fn to_camel_case(&self) -> String {
+        let mut result = String::new();
+        for (i, word) in words(self).iter().enumerate() {
+            match i {
+                0 => result.push_str(&word.to_lowercase()),
+                _ => result.push_str(&capitalize(word)),
+            }
+        }
+        result
+    }
+
+    fn to_kebab_case(&self) -> String {
+        words(self).iter().map(|x| x.to_lowercase()).collect::<Vec<_>>().join("-")
+    }
+
+    fn to_snake_case(&self) -> String {
+        words(self).iter().map(|x| x.to_lowercase()).collect::<Vec<_>>().join("_")
+    }
+
+    fn to_title_case(&self) -> String {
+        words(self).iter().map(|x| capitalize(x)).collect::<Vec<_>>().join(" ")
+    }
+}
+
+impl CaseExt for String {
+    fn to_camel_case(&self) -> String {
+        self.as_str().to_camel_case()
+    }
+
+    fn to_kebab_case(&self) -> String {
+        self.as_str().to_kebab_case()
+    }
+
+    fn to_snake_case(&self) -> String {
+        self.as_str().to_snake_case()
+    }
+
+    fn to_title_case(&self) -> String {
+        self.as_str().to_title_case()
+    }
+}
+
+/// Private implementation to uppercase the first character of the word and lowercase the rest
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(|x| x.to_lowercase())).collect(),
+        None => String::new(),
+    }
+}
+
+/// Private implementation to split the given value into words at underscores, hyphens, whitespace
+/// and camel case humps. Runs of uppercase characters are kept together as acronyms i.e.
+/// `HTTPServer` becomes `HTTP` and `Server`. Works on characters rather than bytes.
+fn words(value: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let chars: Vec<char> = value.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        if *c == '_' || *c == '-' || c.is_whitespace() {
+            if !word.is_empty() {
+                words.push(word);
+                word = String::new();
+            }
+            continue;
+        }
+        if c.is_uppercase() && !word.is_empty() {
+            let prev = chars[i - 1];
+            let next_lower = matches!(chars.get(i + 1), Some(x) if x.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                words.push(word);
+                word = String::new();
+            }
+        }
+        word.push(*c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
 pub trait StringExt {
+    /// Returns a new [`String`] with the longest leading whitespace common to all non-blank lines
+    /// removed, mirroring Python's `textwrap.dedent`. Tabs and spaces are not considered equal and
+    /// lines consisting solely of whitespace are normalized to just their line ending.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("    foo:\n      bar\n".dedent(), "foo:\n  bar\n".to_string());
+    /// ```
+    fn dedent(&self) -> String;
+
+    /// Returns a new [`String`] with the given `prefix` added to the start of every non-blank line,
+    /// acting as the inverse of `dedent`.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("foo:\n  bar\n".indent("    "), "    foo:\n      bar\n".to_string());
+    /// ```
+    fn indent(&self, prefix: &str) -> String;
+
     /// Returns the length in characters rather than bytes i.e. this is a human understandable
     /// value. However it is more costly to perform.
     ///
@@ -24,9 +176,59 @@ pub trait StringExt {
     /// assert_eq!("/foo/bar".to_string().trim_suffix("/bar"), "/foo".to_string());
     /// ```
     fn trim_suffix<T: Into<String>>(&self, suffix: T) -> String;
+
+    /// Returns a new [`String`] truncated to at most `n` characters rather than bytes so that
+    /// multi-byte characters are never split.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("ƒoo".truncate_chars(2), "ƒo".to_string()); // fancy f!
+    /// ```
+    fn truncate_chars(&self, n: usize) -> String;
+
+    /// Returns a new [`String`] truncated to at most `n` characters including a trailing `…` when
+    /// truncation occurs, which is useful for rendering fixed width columns.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("ƒoobar".truncate_ellipsis(4), "ƒoo…".to_string()); // fancy f!
+    /// assert_eq!("ƒoo".truncate_ellipsis(4), "ƒoo".to_string());
+    /// ```
+    fn truncate_ellipsis(&self, n: usize) -> String;
 }
 
 impl StringExt for str {
+    fn dedent(&self) -> String {
+        let mut common: Option<&str> = None;
+        for line in self.lines().filter(|x| !x.trim().is_empty()) {
+            let ws = &line[..line.len() - line.trim_start().len()];
+            common = Some(match common {
+                Some(prefix) => &prefix[..prefix.chars().zip(ws.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a.len_utf8()).sum()],
+                None => ws,
+            });
+        }
+        let common = common.unwrap_or("");
+        self.split_inclusive('\n')
+            .map(|line| match line.trim().is_empty() {
+                true => &line[line.trim_end_matches(['\r', '\n']).len()..],
+                false => &line[common.len()..],
+            })
+            .collect()
+    }
+
+    fn indent(&self, prefix: &str) -> String {
+        self.split_inclusive('\n')
+            .map(|line| match line.trim().is_empty() {
+                true => line.to_owned(),
+                false => format!("{}{}", prefix, line),
+            })
+            .collect()
+    }
+
     fn size(&self) -> usize {
         self.chars().count()
     }
@@ -38,9 +240,29 @@ impl StringExt for str {
             _ => self.to_owned(),
         }
     }
+
+    fn truncate_chars(&self, n: usize) -> String {
+        self.chars().take(n).collect()
+    }
+
+    fn truncate_ellipsis(&self, n: usize) -> String {
+        match self.chars().nth(n) {
+            Some(_) if n > 0 => self.chars().take(n - 1).chain(Some('…')).collect(),
+            Some(_) => String::new(),
+            None => self.to_owned(),
+        }
+    }
 }
 
 impl StringExt for String {
+    fn dedent(&self) -> String {
+        self.as_str().dedent()
+    }
+
+    fn indent(&self, prefix: &str) -> String {
+        self.as_str().indent(prefix)
+    }
+
     fn size(&self) -> usize {
         self.chars().count()
     }
@@ -52,6 +274,14 @@ impl StringExt for String {
             _ => self.to_owned(),
         }
     }
+
+    fn truncate_chars(&self, n: usize) -> String {
+        self.as_str().truncate_chars(n)
+    }
+
+    fn truncate_ellipsis(&self, n: usize) -> String {
+        self.as_str().truncate_ellipsis(n)
+    }
 }
 
 pub trait ToStringExt {
@@ -90,6 +320,90 @@ mod tests {
         path::{Path, PathBuf},
     };
 
+    #[test]
+    fn test_str_case() {
+        // camel case humps
+        assert_eq!("FooBarBaz".to_snake_case(), "foo_bar_baz");
+        assert_eq!("FooBarBaz".to_kebab_case(), "foo-bar-baz");
+        assert_eq!("FooBarBaz".to_camel_case(), "fooBarBaz");
+        assert_eq!("FooBarBaz".to_title_case(), "Foo Bar Baz");
+        assert_eq!("fooBar2Baz".to_snake_case(), "foo_bar2_baz");
+        assert_eq!("HTTPServer".to_snake_case(), "http_server");
+
+        // underscores
+        assert_eq!("foo_bar".to_snake_case(), "foo_bar");
+        assert_eq!("foo_bar".to_kebab_case(), "foo-bar");
+        assert_eq!("foo_bar".to_camel_case(), "fooBar");
+        assert_eq!("foo_bar".to_title_case(), "Foo Bar");
+        assert_eq!("__foo__bar__".to_snake_case(), "foo_bar");
+
+        // hyphens and spaces
+        assert_eq!("foo-bar baz".to_snake_case(), "foo_bar_baz");
+        assert_eq!("foo-bar baz".to_kebab_case(), "foo-bar-baz");
+        assert_eq!("foo-bar baz".to_camel_case(), "fooBarBaz");
+        assert_eq!("foo-bar baz".to_title_case(), "Foo Bar Baz");
+
+        // unicode
+        assert_eq!("ƒooBarÉté".to_snake_case(), "ƒoo_bar_été"); // fancy f!
+        assert_eq!("ƒoo_bar_été".to_camel_case(), "ƒooBarÉté");
+        assert_eq!("ƒoo_bar_été".to_title_case(), "Ƒoo Bar Été");
+        assert_eq!("ÀbcÉfg".to_kebab_case().size(), 7);
+
+        // empty
+        assert_eq!("".to_snake_case(), "");
+        assert_eq!(" - _ ".to_title_case(), "");
+    }
+
+    #[test]
+    fn test_string_case() {
+        assert_eq!("FooBarBaz".to_string().to_snake_case(), "foo_bar_baz");
+        assert_eq!("FooBarBaz".to_string().to_kebab_case(), "foo-bar-baz");
+        assert_eq!("foo_bar".to_string().to_camel_case(), "fooBar");
+        assert_eq!("foo-bar baz".to_string().to_title_case(), "Foo Bar Baz");
+    }
+
+    #[test]
+    fn test_str_dedent() {
+        // nothing to remove
+        assert_eq!("".dedent(), "".to_string());
+        assert_eq!("foo\nbar".dedent(), "foo\nbar".to_string());
+        assert_eq!("foo\n  bar\n".dedent(), "foo\n  bar\n".to_string());
+
+        // spaces
+        assert_eq!("  foo\n    bar\n  baz".dedent(), "foo\n  bar\nbaz".to_string());
+
+        // tabs and spaces only share their common prefix
+        assert_eq!("\t  foo\n\t\tbar\n".dedent(), "  foo\n\tbar\n".to_string());
+        assert_eq!("\tfoo\n  bar\n".dedent(), "\tfoo\n  bar\n".to_string());
+
+        // blank lines don't count and are normalized
+        assert_eq!("    foo\n\n    bar\n".dedent(), "foo\n\nbar\n".to_string());
+        assert_eq!("    foo\n  \n      bar\n".dedent(), "foo\n\n  bar\n".to_string());
+        assert_eq!("  foo\r\n \r\n  bar\r\n".dedent(), "foo\r\n\r\nbar\r\n".to_string());
+        assert_eq!("   \n  ".dedent(), "\n".to_string());
+    }
+
+    #[test]
+    fn test_string_dedent() {
+        assert_eq!("  foo\n\n    bar".to_string().dedent(), "foo\n\n  bar".to_string());
+    }
+
+    #[test]
+    fn test_str_indent() {
+        assert_eq!("".indent("  "), "".to_string());
+        assert_eq!("foo\n\n  bar\n".indent("\t"), "\tfoo\n\n\t  bar\n".to_string());
+        assert_eq!("foo\r\n  \r\nbar".indent("  "), "  foo\r\n  \r\n  bar".to_string());
+
+        // round trip
+        let block = "foo:\n  bar: 1\n\n  baz: 2\n";
+        assert_eq!(block.indent("    ").dedent(), block.to_string());
+    }
+
+    #[test]
+    fn test_string_indent() {
+        assert_eq!("foo\nbar".to_string().indent("  "), "  foo\n  bar".to_string());
+    }
+
     #[test]
     fn test_str_size() {
         assert_eq!("foo".size(), 3);
@@ -116,6 +430,31 @@ mod tests {
         assert_eq!("ƒoo".to_string().trim_suffix("o"), "ƒo".to_string()); // fancy f!
     }
 
+    #[test]
+    fn test_str_truncate() {
+        assert_eq!("foo".truncate_chars(2), "fo".to_string());
+        assert_eq!("ƒoo".truncate_chars(0), "".to_string()); // fancy f!
+        assert_eq!("ƒoo".truncate_chars(1), "ƒ".to_string());
+        assert_eq!("ƒoo".truncate_chars(2), "ƒo".to_string());
+        assert_eq!("ƒoo".truncate_chars(3), "ƒoo".to_string());
+        assert_eq!("ƒoo".truncate_chars(10), "ƒoo".to_string());
+
+        assert_eq!("ƒoo".truncate_ellipsis(0), "".to_string());
+        assert_eq!("ƒoo".truncate_ellipsis(1), "…".to_string());
+        assert_eq!("ƒoo".truncate_ellipsis(2), "ƒ…".to_string());
+        assert_eq!("ƒoo".truncate_ellipsis(3), "ƒoo".to_string());
+        assert_eq!("ƒoo".truncate_ellipsis(10), "ƒoo".to_string());
+        assert_eq!("/foo/été/bar".truncate_ellipsis(8).size(), 8);
+        assert_eq!("".truncate_ellipsis(0), "".to_string());
+    }
+
+    #[test]
+    fn test_string_truncate() {
+        assert_eq!("ƒoo".to_string().truncate_chars(2), "ƒo".to_string()); // fancy f!
+        assert_eq!("ƒoobar".to_string().truncate_ellipsis(4), "ƒoo…".to_string());
+        assert_eq!("ƒoo".to_string().truncate_ellipsis(3), "ƒoo".to_string());
+    }
+
     #[test]
     fn test_osstr_to_string() {
         assert_eq!(OsStr::new("foo").to_string().unwrap(), "foo".to_string());
@@ -0,0 +1,178 @@
+use crate::errors::*;
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// The base64 alphabet to encode with or decode from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Alphabet {
+    /// Standard alphabet from RFC 4648 using `+` and `/`
+    Standard,
+
+    /// URL and filename safe alphabet from RFC 4648 using `-` and `_`
+    UrlSafe,
+}
+impl Alphabet {
+    /// Returns the characters of the alphabet
+    fn chars(self) -> &'static [u8; 64] {
+        match self {
+            Alphabet::Standard => STANDARD,
+            Alphabet::UrlSafe => URL_SAFE,
+        }
+    }
+
+    /// Returns the value of the given character in the alphabet
+    fn value(self, c: u8) -> Option<u8> {
+        match (c, self) {
+            (b'A'..=b'Z', _) => Some(c - b'A'),
+            (b'a'..=b'z', _) => Some(c - b'a' + 26),
+            (b'0'..=b'9', _) => Some(c - b'0' + 52),
+            (b'+', Alphabet::Standard) | (b'-', Alphabet::UrlSafe) => Some(62),
+            (b'/', Alphabet::Standard) | (b'_', Alphabet::UrlSafe) => Some(63),
+            _ => None,
+        }
+    }
+}
+
+/// Decode the given base64 string into bytes using the standard alphabet. Padding is optional but
+/// when given must complete the final block. Returns an `EncError::InvalidBase64` with the
+/// offending position in the given string for malformed input.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(base64::decode("Zm9vYg==").unwrap(), b"foob".to_vec());
+/// ```
+pub fn decode<T: AsRef<str>>(s: T) -> FuResult<Vec<u8>> {
+    decode_with(s, Alphabet::Standard)
+}
+
+/// Decode the given base64 string into bytes using the given `alphabet`. Padding is optional but
+/// when given must complete the final block. Returns an `EncError::InvalidBase64` with the
+/// offending position in the given string for malformed input.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(base64::decode_with("-_8", base64::Alphabet::UrlSafe).unwrap(), vec![0xfb, 0xff]);
+/// ```
+pub fn decode_with<T: AsRef<str>>(s: T, alphabet: Alphabet) -> FuResult<Vec<u8>> {
+    let s = s.as_ref().as_bytes();
+
+    // Padding may only trail the data to complete the final block
+    let len = s.iter().rposition(|&c| c != b'=').map_or(0, |i| i + 1);
+    let padding = s.len() - len;
+    if padding > 2 || (padding > 0 && s.len() % 4 != 0) {
+        return Err(EncError::invalid_base64(len).into());
+    }
+    if len % 4 == 1 {
+        return Err(EncError::invalid_base64(len - 1).into());
+    }
+
+    let mut bytes = Vec::with_capacity(len / 4 * 3 + 2);
+    let (mut acc, mut bits) = (0u32, 0);
+    for (pos, c) in s[..len].iter().enumerate() {
+        let value = alphabet.value(*c).ok_or_else(|| EncError::invalid_base64(pos))?;
+        acc = acc << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Encode the given bytes as a padded base64 string using the standard alphabet.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(base64::encode(b"foob"), "Zm9vYg==");
+/// ```
+pub fn encode<T: AsRef<[u8]>>(bytes: T) -> String {
+    encode_with(bytes, Alphabet::Standard, true)
+}
+
+/// Encode the given bytes as a base64 string using the given `alphabet`, optionally padding the
+/// final block with `=`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(base64::encode_with(&[0xfb, 0xff], base64::Alphabet::UrlSafe, false), "-_8");
+/// ```
+pub fn encode_with<T: AsRef<[u8]>>(bytes: T, alphabet: Alphabet, pad: bool) -> String {
+    let bytes = bytes.as_ref();
+    let chars = alphabet.chars();
+    let mut s = String::with_capacity(bytes.len() / 3 * 4 + 4);
+    for chunk in bytes.chunks(3) {
+        let block = chunk.iter().fold(0u32, |acc, b| acc << 8 | *b as u32) << (8 * (3 - chunk.len()));
+        for i in 0..=chunk.len() {
+            s.push(chars[(block >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+        if pad {
+            for _ in chunk.len()..3 {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(base64::decode("").unwrap(), Vec::<u8>::new());
+        assert_eq!(base64::decode("Zg==").unwrap(), b"f".to_vec());
+        assert_eq!(base64::decode("Zg").unwrap(), b"f".to_vec());
+        assert_eq!(base64::decode("Zm8=").unwrap(), b"fo".to_vec());
+        assert_eq!(base64::decode("Zm9v").unwrap(), b"foo".to_vec());
+        assert_eq!(base64::decode("Zm9vYmFy").unwrap(), b"foobar".to_vec());
+        assert_eq!(base64::decode("+/8=").unwrap(), vec![0xfb, 0xff]);
+        assert_eq!(base64::decode_with("-_8=", base64::Alphabet::UrlSafe).unwrap(), vec![0xfb, 0xff]);
+
+        // round trip
+        let bytes: Vec<u8> = (0..=255).collect();
+        for alphabet in vec![base64::Alphabet::Standard, base64::Alphabet::UrlSafe] {
+            for len in 0..bytes.len() {
+                assert_eq!(base64::decode_with(base64::encode_with(&bytes[..len], alphabet, true), alphabet).unwrap(), bytes[..len].to_vec());
+                assert_eq!(base64::decode_with(base64::encode_with(&bytes[..len], alphabet, false), alphabet).unwrap(), bytes[..len].to_vec());
+            }
+        }
+
+        // invalid characters
+        assert_eq!(base64::decode("Zm9v!mFy").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_base64(4)));
+        assert_eq!(base64::decode("-_8=").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_base64(0)));
+        assert_eq!(base64::decode_with("+/8=", base64::Alphabet::UrlSafe).unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_base64(0)));
+        assert_eq!(base64::decode("Zm=v").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_base64(2)));
+
+        // invalid length or padding
+        assert_eq!(base64::decode("Zm9vY").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_base64(4)));
+        assert_eq!(base64::decode("Zg=").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_base64(2)));
+        assert_eq!(base64::decode("Z===").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_base64(1)));
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(base64::encode(&[]), "");
+        assert_eq!(base64::encode(b"f"), "Zg==");
+        assert_eq!(base64::encode(b"fo"), "Zm8=");
+        assert_eq!(base64::encode(b"foo"), "Zm9v");
+        assert_eq!(base64::encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64::encode(vec![0xfb, 0xff]), "+/8=");
+        assert_eq!(base64::encode_with(&[0xfb, 0xff], base64::Alphabet::Standard, false), "+/8");
+        assert_eq!(base64::encode_with(&[0xfb, 0xff], base64::Alphabet::UrlSafe, true), "-_8=");
+        assert_eq!(base64::encode_with(b"f", base64::Alphabet::UrlSafe, false), "Zg");
+    }
+}
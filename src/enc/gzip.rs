@@ -1,5 +1,105 @@
 use crate::{errors::*, sys::PathExt};
-use std::{fs::File, io::prelude::*, path::Path};
+use flate2::{read::MultiGzDecoder, write::GzEncoder, Compression};
+use std::{
+    fs::File,
+    io::{self, prelude::*},
+    path::Path,
+};
+
+/// Compress the data read from `reader` as a gzip stream written to `writer` using the default
+/// compression level. Returns the number of uncompressed bytes read.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let mut gzipped = vec![];
+/// assert_eq!(gzip::compress(&b"this is a test"[..], &mut gzipped).unwrap(), 14);
+/// assert_eq!(gzip::decompress_bytes(&gzipped).unwrap(), b"this is a test");
+/// ```
+pub fn compress<R: Read, W: Write>(reader: R, writer: W) -> FuResult<u64> {
+    compress_with(reader, writer, Compression::default().level())
+}
+
+/// Compress the data read from `reader` as a gzip stream written to `writer` using the given
+/// compression `level` from `0` to `9` where `1` is the fastest, `9` is the smallest and `0`
+/// simply stores the data without compression. Returns the number of uncompressed bytes read or
+/// an `EncError::InvalidLevel` for levels above `9`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let mut gzipped = vec![];
+/// assert_eq!(gzip::compress_with(&b"this is a test"[..], &mut gzipped, 9).unwrap(), 14);
+/// assert_eq!(gzip::decompress_bytes(&gzipped).unwrap(), b"this is a test");
+/// ```
+pub fn compress_with<R: Read, W: Write>(mut reader: R, writer: W, level: u32) -> FuResult<u64> {
+    if level > 9 {
+        return Err(EncError::invalid_level(level).into());
+    }
+    let mut encoder = GzEncoder::new(writer, Compression::new(level));
+    let n = io::copy(&mut reader, &mut encoder)?;
+    encoder.finish()?;
+    Ok(n)
+}
+
+/// Decompress the gzip stream read from `reader` writing the decompressed data to `writer`.
+/// Concatenated multi-member streams, as produced by some tools, are decompressed in full.
+/// Returns the number of decompressed bytes written. Truncated or corrupt streams surface as an
+/// error rather than partial success.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let gzipped = PathBuf::from("tests/alpine-base.tgz").abs().unwrap();
+/// let mut data = vec![];
+/// assert!(gzip::decompress(File::open(&gzipped).unwrap(), &mut data).unwrap() > 0);
+/// ```
+pub fn decompress<R: Read, W: Write>(reader: R, mut writer: W) -> FuResult<u64> {
+    let mut decoder = MultiGzDecoder::new(reader);
+    Ok(io::copy(&mut decoder, &mut writer)?)
+}
+
+/// Wraps `decompress` to decompress the given gzipped `bytes` into a new `Vec<u8>`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("gzip_decompress_bytes_doc");
+/// let gzipped = tmpdir.mash("../../alpine-base.tgz");
+/// let bytes = sys::readbytes(&gzipped).unwrap();
+/// assert!(gzip::decompress_bytes(&bytes).unwrap().len() > bytes.len());
+/// ```
+pub fn decompress_bytes(bytes: &[u8]) -> FuResult<Vec<u8>> {
+    let mut data = vec![];
+    decompress(bytes, &mut data)?;
+    Ok(data)
+}
+
+/// Wraps `decompress` to decompress the gzipped `src` file into the `dst` file. Handles path
+/// expansion and returns the number of decompressed bytes written.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("gzip_decompress_file_doc");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let gzipped = tmpdir.mash("../../alpine-base.tgz");
+/// let tarball = tmpdir.mash("alpine-base.tar");
+/// assert!(gzip::decompress_file(&gzipped, &tarball).unwrap() > 0);
+/// assert_eq!(gzip::is_gzipped(&tarball).unwrap(), false);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn decompress_file<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<u64> {
+    let src = src.as_ref().abs()?;
+    let dst = dst.as_ref().abs()?;
+    decompress(File::open(src)?, File::create(dst)?)
+}
 
 /// Returns true if the given `path` is a gzipped file
 ///
@@ -31,6 +131,7 @@ pub fn is_gzipped<T: AsRef<Path>>(path: T) -> FuResult<bool> {
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
+    use flate2::{write::GzEncoder, Compression};
 
     // Test setup
     fn setup<T: AsRef<Path>>(path: T) -> PathBuf {
@@ -39,6 +140,94 @@ mod tests {
         temp.mash(path.as_ref())
     }
 
+    #[test]
+    fn test_compress_with() {
+        let data = "this is a test of the gzip compression levels".repeat(1000).into_bytes();
+
+        // levels 1 and 9 round trip with level 9 being no larger
+        let (mut fast, mut best) = (vec![], vec![]);
+        assert_eq!(gzip::compress_with(&data[..], &mut fast, 1).unwrap(), data.len() as u64);
+        assert_eq!(gzip::compress_with(&data[..], &mut best, 9).unwrap(), data.len() as u64);
+        assert!(best.len() <= fast.len());
+        assert!(fast.len() < data.len());
+        assert_eq!(gzip::decompress_bytes(&fast).unwrap(), data);
+        assert_eq!(gzip::decompress_bytes(&best).unwrap(), data);
+
+        // level 0 stores
+        let mut stored = vec![];
+        assert_eq!(gzip::compress_with(&data[..], &mut stored, 0).unwrap(), data.len() as u64);
+        assert!(stored.len() > data.len());
+        assert_eq!(gzip::decompress_bytes(&stored).unwrap(), data);
+
+        // default level
+        let mut gzipped = vec![];
+        assert_eq!(gzip::compress(&data[..], &mut gzipped).unwrap(), data.len() as u64);
+        assert_eq!(gzip::decompress_bytes(&gzipped).unwrap(), data);
+
+        // invalid level
+        assert_eq!(gzip::compress_with(&data[..], vec![], 10).unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_level(10)));
+    }
+
+    #[test]
+    fn test_decompress() {
+        let data = "this is a test of the gzip decompression".repeat(100).into_bytes();
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&data).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        // round trip via reader and writer
+        let mut result = vec![];
+        assert_eq!(gzip::decompress(&gzipped[..], &mut result).unwrap(), data.len() as u64);
+        assert_eq!(result, data);
+
+        // round trip via bytes
+        assert_eq!(gzip::decompress_bytes(&gzipped).unwrap(), data);
+
+        // corrupt header
+        assert!(gzip::decompress_bytes(b"this is not gzipped").unwrap_err().is::<io::Error>());
+
+        // corrupt body
+        let mut corrupt = gzipped.clone();
+        corrupt.iter_mut().skip(10).take(10).for_each(|x| *x = 0xff);
+        assert!(gzip::decompress_bytes(&corrupt).is_err());
+
+        // truncated stream
+        assert!(gzip::decompress_bytes(&gzipped[..gzipped.len() / 2]).is_err());
+
+        // concatenated multi-member stream
+        let mut multi = gzipped.clone();
+        assert!(gzip::compress(&b"second member"[..], &mut multi).is_ok());
+        let mut expected = data.clone();
+        expected.extend_from_slice(b"second member");
+        let mut result = vec![];
+        assert_eq!(gzip::decompress(&multi[..], &mut result).unwrap(), expected.len() as u64);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_decompress_file() {
+        let tmpdir = setup("gzip_decompress_file");
+        let file1 = tmpdir.mash("file1.gz");
+        let file2 = tmpdir.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        let mut encoder = GzEncoder::new(File::create(&file1).unwrap(), Compression::default());
+        encoder.write_all(b"this is a test").unwrap();
+        encoder.finish().unwrap();
+
+        // round trip
+        assert_eq!(gzip::decompress_file(&file1, &file2).unwrap(), 14);
+        assert_eq!(sys::readstring(&file2).unwrap(), "this is a test");
+
+        // source doesn't exist
+        assert!(gzip::decompress_file(tmpdir.mash("foo"), &file2).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_is_gzipped() {
         let tmpdir = setup("is_gzipped");
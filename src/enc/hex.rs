@@ -0,0 +1,206 @@
+use crate::errors::*;
+
+const HEX_LOWER: &[u8; 16] = b"0123456789abcdef";
+const HEX_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Options for encoding bytes as a hex string
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HexOpts {
+    /// Use uppercase hex characters
+    pub upper: bool,
+
+    /// Separator to insert between groups of bytes e.g. `:` for `de:ad:be:ef`
+    pub sep: Option<char>,
+
+    /// Number of bytes per separated group, a value of `0` is treated as `1`
+    pub group: usize,
+}
+
+/// Decode the given hex string into bytes. Accepts both upper and lowercase characters and an
+/// optional `0x` prefix. Returns an `EncError::InvalidHex` with the offending position in the
+/// given string for non-hex characters or an odd number of hex characters.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(hex::decode("0xDEADbeef").unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+/// ```
+pub fn decode<T: AsRef<str>>(s: T) -> FuResult<Vec<u8>> {
+    decode_with(s, None)
+}
+
+/// Decode the given hex string into bytes skipping any occurrences of the given separator
+/// `sep`. Accepts both upper and lowercase characters and an optional `0x` prefix. Returns an
+/// `EncError::InvalidHex` with the offending position in the given string for non-hex characters
+/// or an odd number of hex characters.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(hex::decode_with("de:ad:BE:EF", Some(':')).unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+/// ```
+pub fn decode_with<T: AsRef<str>>(s: T, sep: Option<char>) -> FuResult<Vec<u8>> {
+    let s = s.as_ref();
+    let offset = if s.starts_with("0x") || s.starts_with("0X") { 2 } else { 0 };
+
+    let mut bytes = Vec::with_capacity((s.len() - offset) / 2);
+    let (mut hi, mut end) = (None, offset);
+    for (i, c) in s[offset..].char_indices() {
+        let pos = offset + i;
+        if Some(c) == sep {
+            continue;
+        }
+        let value = Some(c).filter(char::is_ascii).and_then(|c| nibble(c as u8)).ok_or_else(|| EncError::invalid_hex(pos))?;
+        match hi.take() {
+            Some(hi) => bytes.push(hi << 4 | value),
+            None => hi = Some(value),
+        }
+        end = pos + 1;
+    }
+    if hi.is_some() {
+        return Err(EncError::invalid_hex(end).into());
+    }
+    Ok(bytes)
+}
+
+/// Encode the given bytes as a lowercase hex string without separators.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(hex::encode(&[0xde, 0xad, 0xbe, 0xef]), "deadbeef");
+/// ```
+pub fn encode<T: AsRef<[u8]>>(bytes: T) -> String {
+    encode_with(bytes, HexOpts::default())
+}
+
+/// Encode the given bytes as an uppercase hex string without separators.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(hex::encode_upper(&[0xde, 0xad, 0xbe, 0xef]), "DEADBEEF");
+/// ```
+pub fn encode_upper<T: AsRef<[u8]>>(bytes: T) -> String {
+    encode_with(bytes, HexOpts { upper: true, ..Default::default() })
+}
+
+/// Encode the given bytes as a hex string using the given `opts` to control the case and the
+/// separator inserted between each group of bytes.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let opts = hex::HexOpts { sep: Some(':'), ..Default::default() };
+/// assert_eq!(hex::encode_with(&[0xde, 0xad, 0xbe, 0xef], opts), "de:ad:be:ef");
+/// ```
+pub fn encode_with<T: AsRef<[u8]>>(bytes: T, opts: HexOpts) -> String {
+    let bytes = bytes.as_ref();
+    let chars = if opts.upper { HEX_UPPER } else { HEX_LOWER };
+    let group = opts.group.max(1);
+    let mut s = String::with_capacity(bytes.len() * 3);
+    for (i, byte) in bytes.iter().enumerate() {
+        if let Some(sep) = opts.sep {
+            if i > 0 && i % group == 0 {
+                s.push(sep);
+            }
+        }
+        s.push(chars[(byte >> 4) as usize] as char);
+        s.push(chars[(byte & 0xf) as usize] as char);
+    }
+    s
+}
+
+/// Private implementation to convert a hex character into its value
+fn nibble(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(hex::decode("").unwrap(), Vec::<u8>::new());
+        assert_eq!(hex::decode("0x").unwrap(), Vec::<u8>::new());
+        assert_eq!(hex::decode("00ff7f").unwrap(), vec![0x00, 0xff, 0x7f]);
+        assert_eq!(hex::decode("DEADBEEF").unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(hex::decode("0xdeadBEEF").unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(hex::decode("0XdeadBEEF").unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+
+        // round trip
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(hex::decode(hex::encode(&bytes)).unwrap(), bytes);
+        assert_eq!(hex::decode(hex::encode(&bytes).to_uppercase()).unwrap(), bytes);
+
+        // odd length
+        assert_eq!(hex::decode("abc").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_hex(3)));
+        assert_eq!(hex::decode("0xabc").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_hex(5)));
+
+        // invalid characters
+        assert_eq!(hex::decode("zz").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_hex(0)));
+        assert_eq!(hex::decode("abcg").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_hex(3)));
+        assert_eq!(hex::decode("0xab-c").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_hex(4)));
+        assert_eq!(hex::decode("x0ab").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_hex(0)));
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(hex::encode(&[]), "");
+        assert_eq!(hex::encode(&[0x00, 0x0f, 0xf0, 0xff]), "000ff0ff");
+        assert_eq!(hex::encode(b"foo"), "666f6f");
+        assert_eq!(hex::encode(vec![0xde, 0xad, 0xbe, 0xef]), "deadbeef");
+    }
+
+    #[test]
+    fn test_encode_upper() {
+        assert_eq!(hex::encode_upper(&[]), "");
+        assert_eq!(hex::encode_upper(&[0x00, 0x0f, 0xf0, 0xff]), "000FF0FF");
+        assert_eq!(hex::encode_upper(vec![0xde, 0xad, 0xbe, 0xef]), "DEADBEEF");
+        assert_eq!(hex::decode(hex::encode_upper(&[0xde, 0xad, 0xbe, 0xef])).unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[test]
+    fn test_encode_with() {
+        let bytes = vec![0xde, 0xad, 0xbe, 0xef, 0x01];
+
+        // default options match encode
+        assert_eq!(hex::encode_with(&bytes, hex::HexOpts::default()), hex::encode(&bytes));
+
+        // separated
+        let opts = hex::HexOpts { sep: Some(':'), ..Default::default() };
+        assert_eq!(hex::encode_with(&[], opts), "");
+        assert_eq!(hex::encode_with(&[0xde], opts), "de");
+        assert_eq!(hex::encode_with(&bytes, opts), "de:ad:be:ef:01");
+        assert_eq!(hex::encode_with(&bytes, hex::HexOpts { upper: true, sep: Some(':'), group: 1 }), "DE:AD:BE:EF:01");
+
+        // grouped
+        assert_eq!(hex::encode_with(&bytes, hex::HexOpts { upper: false, sep: Some(' '), group: 2 }), "dead beef 01");
+        assert_eq!(hex::encode_with(&bytes, hex::HexOpts { upper: true, sep: Some('-'), group: 4 }), "DEADBEEF-01");
+        assert_eq!(hex::encode_with(&bytes, hex::HexOpts { upper: false, sep: None, group: 2 }), "deadbeef01");
+
+        // separator round trip
+        let bytes: Vec<u8> = (0..=255).collect();
+        for group in 0..4 {
+            let opts = hex::HexOpts { upper: group % 2 == 0, sep: Some(':'), group };
+            assert_eq!(hex::decode_with(hex::encode_with(&bytes, opts), Some(':')).unwrap(), bytes);
+        }
+        assert_eq!(hex::decode_with("0xde:ad", Some(':')).unwrap(), vec![0xde, 0xad]);
+        assert_eq!(hex::decode_with("de:a", Some(':')).unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_hex(4)));
+        assert_eq!(hex::decode_with("de-ad", Some(':')).unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_hex(2)));
+        assert_eq!(hex::decode("de:ad").unwrap_err().downcast_ref::<EncError>(), Some(&EncError::invalid_hex(2)));
+    }
+}
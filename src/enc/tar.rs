@@ -4,7 +4,166 @@ use crate::{
     sys::{self, PathExt},
 };
 use flate2::{self, read::GzDecoder, write::GzEncoder, Compression};
-use std::{fs::File, path::Path};
+use std::{
+    fs::{self, File},
+    io::{self, prelude::*},
+    path::{Path, PathBuf},
+};
+use tar::EntryType;
+use walkdir::WalkDir;
+
+/// The kind of an entry in a tarball as reported by `list`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A directory
+    Dir,
+
+    /// A regular file
+    File,
+
+    /// A hard link to another entry in the tarball
+    HardLink,
+
+    /// A symbolic link
+    Symlink,
+
+    /// Any other kind of entry e.g. a device or fifo
+    Other,
+}
+
+/// TarEntry describes an entry of a tarball as reported by `list`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TarEntry {
+    pub path: PathBuf,   // path of the entry within the tarball
+    pub size: u64,       // size of the entry's data in bytes
+    pub mode: u32,       // permission bits of the entry
+    pub kind: EntryKind, // kind of the entry
+}
+
+/// Options for creating a tarball with `create_with` or adding to one with `append`. Sorting
+/// entries and zeroing out the mtimes and ids allows two runs over identical trees to produce byte
+/// identical archives.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TarOpts {
+    /// Compress the tarball using gzip
+    pub gzip: bool,
+
+    /// Add entries in lexical order
+    pub sort: bool,
+
+    /// Normalize the uid and gid of all entries to 0
+    pub zero_ids: bool,
+
+    /// Set the mtime of all entries to 0
+    pub zero_mtime: bool,
+}
+impl TarOpts {
+    /// Returns options for a reproducible tarball i.e. sorted entries with zeroed mtimes and ids
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let opts = tar::TarOpts::reproducible();
+    /// assert_eq!(opts.sort && opts.zero_ids && opts.zero_mtime, true);
+    /// ```
+    pub fn reproducible() -> Self {
+        TarOpts { sort: true, zero_ids: true, zero_mtime: true, ..Default::default() }
+    }
+}
+
+/// Append the files implicated by the `glob` to the existing uncompressed `tarfile` using the
+/// given `opts`. New entries are written over the archive's trailing zero blocks which are then
+/// rewritten after them. Handles file globbing and errors with a `TarError::Compressed` if the
+/// archive is gzip compressed or `opts.gzip` is set as compressed archives can't be appended to.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("tar_append_doc");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("file2");
+/// let tarball = tmpdir.mash("tarball.tar");
+/// assert!(sys::write(&file1, "file1\n").is_ok());
+/// assert!(sys::write(&file2, "file2\n").is_ok());
+/// assert!(tar::create_with(&tarball, &file1, Default::default()).is_ok());
+/// assert!(tar::append(&tarball, &file2, Default::default()).is_ok());
+/// assert_eq!(tar::list(&tarball).unwrap().len(), 2);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn append<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, glob: U, opts: TarOpts) -> FuResult<()> {
+    let tarfile = tarfile.as_ref().abs()?;
+    if !tarfile.is_file() {
+        return Err(PathError::does_not_exist(&tarfile).into());
+    }
+    if opts.gzip || gzip::is_gzipped(&tarfile)? {
+        return Err(TarError::compressed(&tarfile).into());
+    }
+
+    // Handle globbing
+    let mut sources = sys::glob(glob.as_ref())?;
+    if sources.is_empty() {
+        return Err(PathError::does_not_exist(glob.as_ref()).into());
+    }
+    if opts.sort {
+        sources.sort();
+    }
+
+    // Locate the end of the last entry's data, padded to the block size, as the trailing zero
+    // blocks may span any number of blocks depending on the tool that wrote the archive.
+    let mut end = 0;
+    for entry in tar::Archive::new(File::open(&tarfile)?).entries()? {
+        let entry = entry?;
+        end = entry.raw_file_position() + entry.size().div_ceil(512) * 512;
+    }
+
+    // Append over the trailing zero blocks which `finish` will write again
+    let mut f = fs::OpenOptions::new().write(true).open(&tarfile)?;
+    f.seek(io::SeekFrom::Start(end))?;
+    append_all(tar::Builder::new(f), &sources, &opts)?.sync_all()?;
+    Ok(())
+}
+
+/// Private implementation of appending the `sources` recursively with the given `opts`, returning
+/// the underlying writer once the archive has been finished. Entry paths are relative to the
+/// directory of their source and symlinks are added as symlinks rather than followed.
+fn append_all<W: Write>(mut tarball: tar::Builder<W>, sources: &[PathBuf], opts: &TarOpts) -> FuResult<W> {
+    for source in sources {
+        let root = source.dir()?;
+        let mut walker = WalkDir::new(source).follow_links(false);
+        if opts.sort {
+            walker = walker.sort_by(|x, y| x.file_name().cmp(y.file_name()));
+        }
+        for entry in walker {
+            let path = entry?.into_path();
+            let name = path.rel(&root)?;
+            let meta = fs::symlink_metadata(&path)?;
+
+            let mut header = tar::Header::new_gnu();
+            header.set_metadata(&meta);
+            if opts.zero_mtime {
+                header.set_mtime(0);
+            }
+            if opts.zero_ids {
+                header.set_uid(0);
+                header.set_gid(0);
+            }
+
+            if meta.file_type().is_symlink() {
+                header.set_size(0);
+                tarball.append_link(&mut header, &name, path.readlink()?)?;
+            } else if meta.is_dir() {
+                header.set_size(0);
+                tarball.append_data(&mut header, &name, io::empty())?;
+            } else {
+                tarball.append_data(&mut header, &name, File::open(&path)?)?;
+            }
+        }
+    }
+    Ok(tarball.into_inner()?)
+}
 
 /// Create a tarball `tarfile` uing gzip compression from the files implicated by the `glob`.
 /// Handles file globbing and recursively adds source files based on glob.
@@ -53,7 +212,74 @@ pub fn create<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, glob: U) -> FuResult<(
     Ok(())
 }
 
-/// Extract all tarball files into the given `dst` directory.
+/// Create a gzip compressed tarball `tarfile` from the files implicated by the `glob`. This is
+/// a shortcut for `create_with` with the `gzip` option set.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("tar_create_gz_doc");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let tarball = tmpdir.mash("tarball.tar.gz");
+/// assert!(sys::write(&file1, "single file\n").is_ok());
+/// assert!(tar::create_gz(&tarball, &file1).is_ok());
+/// assert_eq!(gzip::is_gzipped(&tarball).unwrap(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn create_gz<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, glob: U) -> FuResult<()> {
+    create_with(tarfile, glob, TarOpts { gzip: true, ..Default::default() })
+}
+
+/// Create a tarball `tarfile` from the files implicated by the `glob` using the given `opts`.
+/// Handles file globbing and recursively adds source files based on glob with entry paths relative
+/// to the directory of each source. Symlinks are stored as symlinks rather than followed.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("tar_create_with_doc");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let tarball = tmpdir.mash("tarball.tar");
+/// let dst = tmpdir.mash("dst");
+/// let dstfile = dst.mash("file1");
+/// assert!(sys::write(&file1, "single file\n").is_ok());
+/// assert!(tar::create_with(&tarball, &file1, tar::TarOpts::reproducible()).is_ok());
+/// assert!(tar::extract_all(&tarball, &dst).is_ok());
+/// assert_eq!(sys::readstring(&dstfile).unwrap(), "single file\n".to_string());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn create_with<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, glob: U, opts: TarOpts) -> FuResult<()> {
+    let tarfile = tarfile.as_ref().abs()?;
+
+    // Handle globbing
+    let mut sources = sys::glob(glob.as_ref())?;
+    if sources.is_empty() {
+        return Err(PathError::does_not_exist(glob.as_ref()).into());
+    }
+    if opts.sort {
+        sources.sort();
+    }
+
+    // Create the tarfile including all source files
+    let f = File::create(&tarfile)?;
+    if opts.gzip {
+        let encoder = GzEncoder::new(f, Compression::default());
+        append_all(tar::Builder::new(encoder), &sources, &opts)?.finish()?;
+    } else {
+        append_all(tar::Builder::new(f), &sources, &opts)?;
+    }
+
+    Ok(())
+}
+
+/// Extract all tarball files into the given `dst` directory. Aborts with a `TarError::PathTraversal`
+/// if any entry, or the target of any link entry, resolves outside of `dst`.
 ///
 /// ### Examples
 /// ```
@@ -79,14 +305,129 @@ pub fn extract_all<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, dst: U) -> FuResu
     if gzip::is_gzipped(&tarfile)? {
         let f = File::open(&tarfile)?;
         let tar = GzDecoder::new(f);
-        let mut archive = tar::Archive::new(tar);
-        archive.unpack(&dst)?;
+        unpack(tar::Archive::new(tar), &dst)?;
     } else {
         let f = File::open(&tarfile)?;
-        let mut archive = tar::Archive::new(f);
-        archive.unpack(&dst)?;
+        unpack(tar::Archive::new(f), &dst)?;
+    }
+
+    Ok(())
+}
+
+/// Extract all files of the gzip compressed tarball into the given `dst` directory. Detects the
+/// compression by the gzip magic bytes and returns a `TarError::NotGzipped` if it isn't.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("tar_extract_gz_doc");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let tarball = tmpdir.mash("tarball.tar.gz");
+/// let dst = tmpdir.mash("dst");
+/// assert!(sys::write(&file1, "single file\n").is_ok());
+/// assert!(tar::create_gz(&tarball, &file1).is_ok());
+/// assert!(tar::extract_gz(&tarball, &dst).is_ok());
+/// assert_eq!(sys::readstring(dst.mash("file1")).unwrap(), "single file\n".to_string());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn extract_gz<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, dst: U) -> FuResult<()> {
+    let dst = dst.as_ref().abs()?;
+    let tarfile = tarfile.as_ref().abs()?;
+
+    if !gzip::is_gzipped(&tarfile)? {
+        return Err(TarError::not_gzipped(&tarfile).into());
     }
+    let f = File::open(&tarfile)?;
+    unpack(tar::Archive::new(GzDecoder::new(f)), &dst)
+}
 
+/// List the entries of the given tarball without extracting them. Gzip compressed tarballs are
+/// detected by their magic bytes and decompressed on the fly.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("tar_list_doc");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let tarball = tmpdir.mash("tarball.tgz");
+/// assert!(sys::write(&file1, "single file\n").is_ok());
+/// assert!(tar::create(&tarball, &file1).is_ok());
+/// let entries = tar::list(&tarball).unwrap();
+/// assert_eq!(entries[0].path, PathBuf::from("file1"));
+/// assert_eq!(entries[0].size, 12);
+/// assert_eq!(entries[0].kind, tar::EntryKind::File);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn list<T: AsRef<Path>>(tarfile: T) -> FuResult<Vec<TarEntry>> {
+    let tarfile = tarfile.as_ref().abs()?;
+    let f = File::open(&tarfile)?;
+    match gzip::is_gzipped(&tarfile)? {
+        true => list_p(tar::Archive::new(GzDecoder::new(f))),
+        false => list_p(tar::Archive::new(f)),
+    }
+}
+
+/// Private implementation of listing the entries of the given `archive`
+fn list_p<R: Read>(mut archive: tar::Archive<R>) -> FuResult<Vec<TarEntry>> {
+    let mut entries = vec![];
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let header = entry.header();
+        let kind = match header.entry_type() {
+            EntryType::Directory => EntryKind::Dir,
+            EntryType::Regular | EntryType::Continuous => EntryKind::File,
+            EntryType::Link => EntryKind::HardLink,
+            EntryType::Symlink => EntryKind::Symlink,
+            _ => EntryKind::Other,
+        };
+        entries.push(TarEntry { path: entry.path()?.to_path_buf(), size: header.size()?, mode: header.mode()? & 0o7777, kind });
+    }
+    Ok(entries)
+}
+
+/// Private implementation of the unpack guarding against entries escaping the `dst` directory
+fn unpack<R: Read>(mut archive: tar::Archive<R>, dst: &Path) -> FuResult<()> {
+    let dst = sys::mkdir(dst)?.clean()?;
+
+    // Directories are deferred until the end, deepest first, so that restrictive directory
+    // permissions don't prevent their children from being unpacked.
+    let mut dirs = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        let target = dst.mash(&path).clean()?;
+        if !target.starts_with(&dst) {
+            return Err(TarError::path_traversal(path).into());
+        }
+
+        // Symlinks resolve relative to their own directory while hard links resolve relative to
+        // the root of the archive.
+        if let Some(link) = entry.link_name()? {
+            let resolved: PathBuf = match entry.header().entry_type() {
+                EntryType::Symlink if link.is_absolute() => link.clean()?,
+                EntryType::Symlink => target.dir()?.mash(&link).clean()?,
+                _ => dst.mash(&link).clean()?,
+            };
+            if !resolved.starts_with(&dst) {
+                return Err(TarError::path_traversal(path).into());
+            }
+        }
+        match entry.header().entry_type() {
+            EntryType::Directory => dirs.push(entry),
+            _ => {
+                entry.unpack_in(&dst)?;
+            },
+        }
+    }
+    dirs.sort_by(|x, y| y.path_bytes().cmp(&x.path_bytes()));
+    for mut dir in dirs {
+        dir.unpack_in(&dst)?;
+    }
     Ok(())
 }
 
@@ -95,6 +436,8 @@ pub fn extract_all<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, dst: U) -> FuResu
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
+    use flate2::read::GzDecoder;
+    use std::time::{Duration, SystemTime};
 
     // Test setup
     fn setup<T: AsRef<Path>>(path: T) -> PathBuf {
@@ -103,6 +446,62 @@ mod tests {
         temp.mash(path.as_ref())
     }
 
+    #[test]
+    fn test_append() {
+        let tmpdir = setup("tar_append");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = dir1.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let file3 = tmpdir.mash("file3");
+        let tarball = tmpdir.mash("tarball.tar");
+        let fixture = tmpdir.mash("alpine-base.tar");
+        let dst = tmpdir.mash("dst");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "file1\n").is_ok());
+        assert!(sys::write(&file2, "file2\n").is_ok());
+        assert!(sys::write(&file3, "x".repeat(1000)).is_ok());
+        assert!(tar::create_with(&tarball, &dir1, tar::TarOpts { sort: true, ..Default::default() }).is_ok());
+
+        // Old and new entries are listed
+        assert!(tar::append(&tarball, &file2, Default::default()).is_ok());
+        assert!(tar::append(&tarball, &file3, Default::default()).is_ok());
+        let entries = tar::list(&tarball).unwrap();
+        let paths = vec![PathBuf::from("dir1"), PathBuf::from("dir1/file1"), PathBuf::from("file2"), PathBuf::from("file3")];
+        assert_eq!(entries.iter().map(|x| x.path.clone()).collect::<Vec<PathBuf>>(), paths);
+        assert_eq!(entries.iter().map(|x| x.size).collect::<Vec<u64>>(), vec![0, 6, 6, 1000]);
+
+        // Archive still ends with two zero blocks and extracts cleanly
+        let data = sys::readbytes(&tarball).unwrap();
+        assert_eq!(data.len() % 512, 0);
+        assert!(data[data.len() - 1024..].iter().all(|x| *x == 0));
+        assert!(tar::extract_all(&tarball, &dst).is_ok());
+        assert_eq!(sys::readstring(dst.mash("dir1/file1")).unwrap(), "file1\n");
+        assert_eq!(sys::readstring(dst.mash("file2")).unwrap(), "file2\n");
+        assert_eq!(sys::readstring(dst.mash("file3")).unwrap(), "x".repeat(1000));
+
+        // Archives padded out to a full record by other tools
+        assert!(sys::copyfile("tests/alpine-base.tar", &fixture).is_ok());
+        assert!(tar::append(&fixture, &file2, Default::default()).is_ok());
+        let entries = tar::list(&fixture).unwrap();
+        assert_eq!(entries.len(), 42);
+        assert_eq!(entries.last().unwrap().path, PathBuf::from("file2"));
+
+        // Compressed archives can't be appended to
+        let tgz = tmpdir.mash("tarball.tgz");
+        assert!(tar::create(&tgz, &file1).is_ok());
+        assert_eq!(tar::append(&tgz, &file2, Default::default()).unwrap_err().downcast_ref::<TarError>(), Some(&TarError::compressed(&tgz)));
+        let opts = tar::TarOpts { gzip: true, ..Default::default() };
+        assert_eq!(tar::append(&tarball, &file2, opts).unwrap_err().downcast_ref::<TarError>(), Some(&TarError::compressed(&tarball)));
+        assert_eq!(tar::list(&tarball).unwrap().len(), 4);
+
+        // Missing archive or sources
+        assert_eq!(tar::append(tmpdir.mash("foo.tar"), &file2, Default::default()).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(tmpdir.mash("foo.tar"))));
+        assert!(tar::append(&tarball, tmpdir.mash("foo"), Default::default()).is_err());
+        assert_eq!(tar::list(&tarball).unwrap().len(), 4);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_create_and_extract_multiple() {
         let tmpdir = setup("tar_create_and_extract_multile");
@@ -180,6 +579,195 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_create_gz_and_extract_gz() {
+        let tmpdir = setup("tar_create_gz_and_extract_gz");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = dir1.mash("file1");
+        let dir2 = dir1.mash("dir2");
+        let file2 = dir2.mash("file2");
+        let tarball = tmpdir.mash("tarball.tar.gz");
+        let plain = tmpdir.mash("tarball.tar");
+        let dst = tmpdir.mash("dst");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir2).is_ok());
+        assert!(sys::write(&file1, "file1\n").is_ok());
+        assert!(sys::write(&file2, "file2\n").is_ok());
+
+        // Round trip the directory into a fresh destination
+        assert!(tar::create_gz(&tarball, &dir1).is_ok());
+        assert_eq!(gzip::is_gzipped(&tarball).unwrap(), true);
+        assert!(tar::extract_gz(&tarball, &dst).is_ok());
+        assert_eq!(sys::readstring(dst.mash("dir1/file1")).unwrap(), "file1\n".to_string());
+        assert_eq!(sys::readstring(dst.mash("dir1/dir2/file2")).unwrap(), "file2\n".to_string());
+
+        // Uncompressed tarballs are rejected
+        assert!(tar::create_with(&plain, &dir1, tar::TarOpts::default()).is_ok());
+        assert_eq!(tar::extract_gz(&plain, &dst).unwrap_err().downcast_ref::<TarError>(), Some(&TarError::not_gzipped(&plain)));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_create_with_reproducible() {
+        let tmpdir = setup("tar_create_with_reproducible");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = dir1.mash("file1");
+        let dir2 = dir1.mash("dir2");
+        let file2 = dir2.mash("file2");
+        let link1 = dir1.mash("link1");
+        let tarball1 = tmpdir.mash("tarball1.tgz");
+        let tarball2 = tmpdir.mash("tarball2.tgz");
+        let dst = tmpdir.mash("dst");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir2).is_ok());
+        assert!(sys::write(&file1, "file1\n").is_ok());
+        assert!(sys::write(&file2, "file2\n").is_ok());
+        assert!(sys::symlink(&link1, "dir2/file2").is_ok());
+        let opts = tar::TarOpts { gzip: true, ..tar::TarOpts::reproducible() };
+
+        // Build the tarball twice with different mtimes in between
+        assert!(sys::touch_at(&file1, SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000)).is_ok());
+        assert!(tar::create_with(&tarball1, &dir1, opts.clone()).is_ok());
+        assert!(sys::touch_at(&file1, SystemTime::UNIX_EPOCH + Duration::from_secs(2_000_000)).is_ok());
+        assert!(tar::create_with(&tarball2, &dir1, opts).is_ok());
+        assert_eq!(sys::readbytes(&tarball1).unwrap(), sys::readbytes(&tarball2).unwrap());
+
+        // Entries are sorted with zeroed mtimes and ids
+        let mut archive = ::tar::Archive::new(GzDecoder::new(File::open(&tarball1).unwrap()));
+        let mut names = vec![];
+        for entry in archive.entries().unwrap() {
+            let entry = entry.unwrap();
+            assert_eq!(entry.header().mtime().unwrap(), 0);
+            assert_eq!(entry.header().uid().unwrap(), 0);
+            assert_eq!(entry.header().gid().unwrap(), 0);
+            names.push(entry.path().unwrap().to_string().unwrap());
+        }
+        assert_eq!(names, vec!["dir1", "dir1/dir2", "dir1/dir2/file2", "dir1/file1", "dir1/link1"]);
+
+        // Extract tarball
+        assert!(tar::extract_all(&tarball1, &dst).is_ok());
+        assert_eq!(sys::readstring(dst.mash("dir1/file1")).unwrap(), "file1\n".to_string());
+        assert_eq!(sys::readstring(dst.mash("dir1/dir2/file2")).unwrap(), "file2\n".to_string());
+        assert_eq!(dst.mash("dir1/link1").readlink().unwrap(), PathBuf::from("dir2/file2"));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_list() {
+        let tmpdir = setup("tar_list");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = dir1.mash("file1");
+        let link1 = dir1.mash("link1");
+        let tarball = tmpdir.mash("tarball.tar");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "file1\n").is_ok());
+        assert!(sys::chmod(&file1, 0o640).is_ok());
+        assert!(sys::symlink(&link1, "file1").is_ok());
+        assert!(tar::create_with(&tarball, &dir1, tar::TarOpts { sort: true, ..Default::default() }).is_ok());
+
+        // Listing doesn't extract anything
+        let entries = tar::list(&tarball).unwrap();
+        assert_eq!(entries.iter().map(|x| x.path.clone()).collect::<Vec<PathBuf>>(), vec![PathBuf::from("dir1"), PathBuf::from("dir1/file1"), PathBuf::from("dir1/link1")]);
+        assert_eq!(entries.iter().map(|x| x.kind).collect::<Vec<tar::EntryKind>>(), vec![tar::EntryKind::Dir, tar::EntryKind::File, tar::EntryKind::Symlink]);
+        assert_eq!(entries.iter().map(|x| x.size).collect::<Vec<u64>>(), vec![0, 6, 0]);
+        assert_eq!(entries[1].mode, 0o640);
+        assert_eq!(sys::all_paths(&tmpdir).unwrap().len(), 4);
+
+        // Fixture archives list the same entries whether compressed or not
+        let tgz = tar::list(tmpdir.mash("../../alpine-base.tgz")).unwrap();
+        let tar = tar::list(tmpdir.mash("../../alpine-base.tar")).unwrap();
+        assert_eq!(tgz.len(), 41);
+        assert_eq!(tgz.iter().map(|x| &x.path).collect::<Vec<&PathBuf>>(), tar.iter().map(|x| &x.path).collect::<Vec<&PathBuf>>());
+        let readme = tgz.iter().find(|x| x.path == PathBuf::from("README.md")).unwrap();
+        assert_eq!((readme.size, readme.mode, readme.kind), (3688, 0o644, tar::EntryKind::File));
+        let config = tgz.iter().find(|x| x.path == PathBuf::from("config")).unwrap();
+        assert_eq!((config.size, config.mode, config.kind), (0, 0o755, tar::EntryKind::Dir));
+
+        // Not a tarball
+        assert!(tar::list(&file1).is_err());
+        assert!(tar::list(tmpdir.mash("foo")).is_err());
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_extract_path_traversal() {
+        let tmpdir = setup("tar_extract_path_traversal");
+        let tarball = tmpdir.mash("tarball.tar");
+        let dst = tmpdir.mash("dst");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // Crafted archive with a parent directory entry, written directly into the header as the
+        // tar builder refuses to create such entries.
+        let mut builder = ::tar::Builder::new(File::create(&tarball).unwrap());
+        let mut header = ::tar::Header::new_old();
+        header.as_old_mut().name[..11].copy_from_slice(b"../../file1");
+        header.set_size(4);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, &b"foo\n"[..]).unwrap();
+        builder.finish().unwrap();
+        assert_eq!(tar::extract_all(&tarball, &dst).unwrap_err().downcast_ref::<TarError>(), Some(&TarError::path_traversal("../../file1")));
+        assert_eq!(tmpdir.mash("../file1").exists(), false);
+
+        // Crafted archive with a symlink pointing outside the destination
+        let mut builder = ::tar::Builder::new(File::create(&tarball).unwrap());
+        let mut header = ::tar::Header::new_gnu();
+        header.set_entry_type(::tar::EntryType::Symlink);
+        header.set_size(0);
+        header.set_mode(0o777);
+        builder.append_link(&mut header, "link1", "../../etc/passwd").unwrap();
+        builder.finish().unwrap();
+        assert_eq!(tar::extract_all(&tarball, &dst).unwrap_err().downcast_ref::<TarError>(), Some(&TarError::path_traversal("link1")));
+        assert_eq!(dst.mash("link1").is_symlink(), false);
+
+        // Symlink pointing inside the destination is allowed
+        let mut builder = ::tar::Builder::new(File::create(&tarball).unwrap());
+        let mut header = ::tar::Header::new_gnu();
+        header.set_entry_type(::tar::EntryType::Symlink);
+        header.set_size(0);
+        header.set_mode(0o777);
+        builder.append_link(&mut header, "dir1/link1", "../file1").unwrap();
+        builder.finish().unwrap();
+        assert!(tar::extract_all(&tarball, &dst).is_ok());
+        assert_eq!(dst.mash("dir1/link1").readlink().unwrap(), PathBuf::from("../file1"));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_extract_readonly_dir() {
+        let tmpdir = setup("tar_extract_readonly_dir");
+        let tarball = tmpdir.mash("tarball.tar");
+        let dst = tmpdir.mash("dst");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // Directory entries come first in the tarball so unpacking them in order would block the
+        // file from being written.
+        let mut builder = ::tar::Builder::new(File::create(&tarball).unwrap());
+        let mut header = ::tar::Header::new_gnu();
+        header.set_entry_type(::tar::EntryType::Directory);
+        header.set_size(0);
+        header.set_mode(0o555);
+        builder.append_data(&mut header, "dir1", io::empty()).unwrap();
+        let mut header = ::tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, "dir1/file1", &b"foo\n"[..]).unwrap();
+        builder.finish().unwrap();
+
+        assert!(tar::extract_all(&tarball, &dst).is_ok());
+        assert_eq!(dst.mash("dir1").mode().unwrap(), 0o40555);
+        assert_eq!(sys::readstring(dst.mash("dir1/file1")).unwrap(), "foo\n");
+
+        assert!(sys::chmod(dst.mash("dir1"), 0o755).is_ok());
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_extract_sys_tgz() {
         let tmpdir = setup("tar_extract_sys_tgz");
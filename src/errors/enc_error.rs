@@ -0,0 +1,57 @@
+use std::{error::Error as StdError, fmt};
+
+// An error indicating that something went wrong with an encoding operation
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum EncError {
+    /// An error indicating that the base64 string is invalid at the given position.
+    InvalidBase64 { pos: usize },
+
+    /// An error indicating that the hex string is invalid at the given position.
+    InvalidHex { pos: usize },
+
+    /// An error indicating that the compression level is invalid.
+    InvalidLevel(u32),
+}
+impl EncError {
+    /// Return an error indicating that the base64 string is invalid at the given position
+    pub fn invalid_base64(pos: usize) -> EncError {
+        EncError::InvalidBase64 { pos }
+    }
+
+    /// Return an error indicating that the hex string is invalid at the given position
+    pub fn invalid_hex(pos: usize) -> EncError {
+        EncError::InvalidHex { pos }
+    }
+
+    /// Return an error indicating that the compression level is invalid
+    pub fn invalid_level(level: u32) -> EncError {
+        EncError::InvalidLevel(level)
+    }
+}
+
+impl StdError for EncError {}
+
+impl fmt::Display for EncError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EncError::InvalidBase64 { pos } => write!(f, "invalid base64 string at position: {}", pos),
+            EncError::InvalidHex { pos } => write!(f, "invalid hex string at position: {}", pos),
+            EncError::InvalidLevel(level) => write!(f, "invalid compression level: {}", level),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(EncError::invalid_base64(1), EncError::InvalidBase64 { pos: 1 });
+        assert_eq!(format!("{}", EncError::InvalidBase64 { pos: 1 }), "invalid base64 string at position: 1");
+        assert_eq!(EncError::invalid_hex(1), EncError::InvalidHex { pos: 1 });
+        assert_eq!(format!("{}", EncError::InvalidHex { pos: 1 }), "invalid hex string at position: 1");
+        assert_eq!(EncError::invalid_level(10), EncError::InvalidLevel(10));
+        assert_eq!(format!("{}", EncError::InvalidLevel(10)), "invalid compression level: 10");
+    }
+}
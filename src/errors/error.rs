@@ -1,5 +1,5 @@
 use crate::errors::*;
-use std::{env, error::Error as StdError, ffi, fmt, io};
+use std::{env, error::Error as StdError, ffi, fmt, io, num, str};
 
 /// `Result<T>` provides a simplified result type with a common error type
 pub type FuResult<T> = std::result::Result<T, FuError>;
@@ -7,20 +7,34 @@ pub type FuResult<T> = std::result::Result<T, FuError>;
 /// Define common error wrapper type
 #[derive(Debug)]
 pub enum FuError {
+    Enc(EncError),
+    Exec(ExecError),
     File(FileError),
+    Git(GitError),
     GlobPattern(glob::PatternError),
+    Group(GroupError),
     Io(io::Error),
     Iter(IterError),
+    Net(NetError),
     Nul(ffi::NulError),
     Os(OsError),
+    ParseFloat(num::ParseFloatError),
+    ParseInt(num::ParseIntError),
     Path(PathError),
     Regex(regex::Error),
     String(StringError),
+    Tar(TarError),
     User(UserError),
+    Utf8(str::Utf8Error),
     Var(env::VarError),
     WalkDir(walkdir::Error),
 }
 impl FuError {
+    /// Returns a reference to the wrapped error if it is of type `T` e.g. `err.as_err::<PathError>()`
+    pub fn as_err<T: StdError+'static>(&self) -> Option<&T> {
+        self.downcast_ref::<T>()
+    }
+
     /// Implemented directly on the `Error` type to reduce casting required
     pub fn is<T: StdError+'static>(&self) -> bool {
         self.as_ref().is::<T>()
@@ -45,16 +59,25 @@ impl FuError {
 impl std::fmt::Display for FuError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            FuError::Enc(ref err) => write!(f, "{}", err),
+            FuError::Exec(ref err) => write!(f, "{}", err),
             FuError::File(ref err) => write!(f, "{}", err),
+            FuError::Git(ref err) => write!(f, "{}", err),
             FuError::GlobPattern(ref err) => write!(f, "{}", err),
+            FuError::Group(ref err) => write!(f, "{}", err),
             FuError::Io(ref err) => write!(f, "{}", err),
             FuError::Iter(ref err) => write!(f, "{}", err),
+            FuError::Net(ref err) => write!(f, "{}", err),
             FuError::Nul(ref err) => write!(f, "{}", err),
             FuError::Os(ref err) => write!(f, "{}", err),
+            FuError::ParseFloat(ref err) => write!(f, "{}", err),
+            FuError::ParseInt(ref err) => write!(f, "{}", err),
             FuError::Path(ref err) => write!(f, "{}", err),
             FuError::Regex(ref err) => write!(f, "{}", err),
             FuError::String(ref err) => write!(f, "{}", err),
+            FuError::Tar(ref err) => write!(f, "{}", err),
             FuError::User(ref err) => write!(f, "{}", err),
+            FuError::Utf8(ref err) => write!(f, "{}", err),
             FuError::Var(ref err) => write!(f, "{}", err),
             FuError::WalkDir(ref err) => write!(f, "{}", err),
         }
@@ -64,16 +87,25 @@ impl std::fmt::Display for FuError {
 impl AsRef<dyn StdError> for FuError {
     fn as_ref(&self) -> &(dyn StdError+'static) {
         match *self {
+            FuError::Enc(ref err) => err,
+            FuError::Exec(ref err) => err,
             FuError::File(ref err) => err,
+            FuError::Git(ref err) => err,
             FuError::GlobPattern(ref err) => err,
+            FuError::Group(ref err) => err,
             FuError::Io(ref err) => err,
             FuError::Iter(ref err) => err,
+            FuError::Net(ref err) => err,
             FuError::Nul(ref err) => err,
             FuError::Os(ref err) => err,
+            FuError::ParseFloat(ref err) => err,
+            FuError::ParseInt(ref err) => err,
             FuError::Path(ref err) => err,
             FuError::Regex(ref err) => err,
             FuError::String(ref err) => err,
+            FuError::Tar(ref err) => err,
             FuError::User(ref err) => err,
+            FuError::Utf8(ref err) => err,
             FuError::Var(ref err) => err,
             FuError::WalkDir(ref err) => err,
         }
@@ -83,34 +115,67 @@ impl AsRef<dyn StdError> for FuError {
 impl AsMut<dyn StdError> for FuError {
     fn as_mut(&mut self) -> &mut (dyn StdError+'static) {
         match *self {
+            FuError::Enc(ref mut err) => err,
+            FuError::Exec(ref mut err) => err,
             FuError::File(ref mut err) => err,
+            FuError::Git(ref mut err) => err,
             FuError::GlobPattern(ref mut err) => err,
+            FuError::Group(ref mut err) => err,
             FuError::Io(ref mut err) => err,
             FuError::Iter(ref mut err) => err,
+            FuError::Net(ref mut err) => err,
             FuError::Nul(ref mut err) => err,
             FuError::Os(ref mut err) => err,
+            FuError::ParseFloat(ref mut err) => err,
+            FuError::ParseInt(ref mut err) => err,
             FuError::Path(ref mut err) => err,
             FuError::Regex(ref mut err) => err,
             FuError::String(ref mut err) => err,
+            FuError::Tar(ref mut err) => err,
             FuError::User(ref mut err) => err,
+            FuError::Utf8(ref mut err) => err,
             FuError::Var(ref mut err) => err,
             FuError::WalkDir(ref mut err) => err,
         }
     }
 }
 
+impl From<EncError> for FuError {
+    fn from(err: EncError) -> FuError {
+        FuError::Enc(err)
+    }
+}
+
+impl From<ExecError> for FuError {
+    fn from(err: ExecError) -> FuError {
+        FuError::Exec(err)
+    }
+}
+
 impl From<FileError> for FuError {
     fn from(err: FileError) -> FuError {
         FuError::File(err)
     }
 }
 
+impl From<GitError> for FuError {
+    fn from(err: GitError) -> FuError {
+        FuError::Git(err)
+    }
+}
+
 impl From<glob::PatternError> for FuError {
     fn from(err: glob::PatternError) -> FuError {
         FuError::GlobPattern(err)
     }
 }
 
+impl From<GroupError> for FuError {
+    fn from(err: GroupError) -> FuError {
+        FuError::Group(err)
+    }
+}
+
 impl From<io::Error> for FuError {
     fn from(err: io::Error) -> FuError {
         FuError::Io(err)
@@ -123,6 +188,12 @@ impl From<IterError> for FuError {
     }
 }
 
+impl From<NetError> for FuError {
+    fn from(err: NetError) -> FuError {
+        FuError::Net(err)
+    }
+}
+
 impl From<ffi::NulError> for FuError {
     fn from(err: ffi::NulError) -> FuError {
         FuError::Nul(err)
@@ -135,6 +206,18 @@ impl From<OsError> for FuError {
     }
 }
 
+impl From<num::ParseFloatError> for FuError {
+    fn from(err: num::ParseFloatError) -> FuError {
+        FuError::ParseFloat(err)
+    }
+}
+
+impl From<num::ParseIntError> for FuError {
+    fn from(err: num::ParseIntError) -> FuError {
+        FuError::ParseInt(err)
+    }
+}
+
 impl From<PathError> for FuError {
     fn from(err: PathError) -> FuError {
         FuError::Path(err)
@@ -153,12 +236,24 @@ impl From<StringError> for FuError {
     }
 }
 
+impl From<TarError> for FuError {
+    fn from(err: TarError) -> FuError {
+        FuError::Tar(err)
+    }
+}
+
 impl From<UserError> for FuError {
     fn from(err: UserError) -> FuError {
         FuError::User(err)
     }
 }
 
+impl From<str::Utf8Error> for FuError {
+    fn from(err: str::Utf8Error) -> FuError {
+        FuError::Utf8(err)
+    }
+}
+
 impl From<env::VarError> for FuError {
     fn from(err: env::VarError) -> FuError {
         FuError::Var(err)
@@ -182,6 +277,22 @@ mod tests {
 
     #[test]
     fn test_error() {
+        let mut err = FuError::from(EncError::InvalidHex { pos: 1 });
+        assert_eq!("invalid hex string at position: 1", err.to_string());
+        assert_eq!("invalid hex string at position: 1", err.as_ref().to_string());
+        assert_eq!("invalid hex string at position: 1", err.as_mut().to_string());
+        assert!(err.downcast_ref::<EncError>().is_some());
+        assert!(err.downcast_mut::<EncError>().is_some());
+        assert!(err.source().is_none());
+
+        let mut err = FuError::from(ExecError::non_zero_exit(1, "foo"));
+        assert_eq!("command exited with non-zero code 1: foo", err.to_string());
+        assert_eq!("command exited with non-zero code 1: foo", err.as_ref().to_string());
+        assert_eq!("command exited with non-zero code 1: foo", err.as_mut().to_string());
+        assert!(err.downcast_ref::<ExecError>().is_some());
+        assert!(err.downcast_mut::<ExecError>().is_some());
+        assert!(err.source().is_none());
+
         let mut err = FuError::from(FileError::FailedToExtractString);
         assert_eq!("failed to extract string from file", err.to_string());
         assert_eq!("failed to extract string from file", err.as_ref().to_string());
@@ -190,6 +301,14 @@ mod tests {
         assert!(err.downcast_mut::<FileError>().is_some());
         assert!(err.source().is_none());
 
+        let mut err = FuError::from(GitError::BranchNotFound("foo".to_string()));
+        assert_eq!("git branch not found: foo", err.to_string());
+        assert_eq!("git branch not found: foo", err.as_ref().to_string());
+        assert_eq!("git branch not found: foo", err.as_mut().to_string());
+        assert!(err.downcast_ref::<GitError>().is_some());
+        assert!(err.downcast_mut::<GitError>().is_some());
+        assert!(err.source().is_none());
+
         let mut err = FuError::from(glob::PatternError { pos: 1, msg: "1" });
         assert_eq!("Pattern syntax error near position 1: 1", err.to_string());
         assert_eq!("Pattern syntax error near position 1: 1", err.as_ref().to_string());
@@ -198,6 +317,14 @@ mod tests {
         assert!(err.downcast_mut::<glob::PatternError>().is_some());
         assert!(err.source().is_none());
 
+        let mut err = FuError::from(GroupError::DoesNotExistById(1));
+        assert_eq!("group does not exist: 1", err.to_string());
+        assert_eq!("group does not exist: 1", err.as_ref().to_string());
+        assert_eq!("group does not exist: 1", err.as_mut().to_string());
+        assert!(err.downcast_ref::<GroupError>().is_some());
+        assert!(err.downcast_mut::<GroupError>().is_some());
+        assert!(err.source().is_none());
+
         let mut err = FuError::from(io::Error::new(io::ErrorKind::AlreadyExists, "foo"));
         assert_eq!("foo", err.to_string());
         assert_eq!("foo", err.as_ref().to_string());
@@ -214,6 +341,14 @@ mod tests {
         assert!(err.downcast_mut::<IterError>().is_some());
         assert!(err.source().is_none());
 
+        let mut err = FuError::from(NetError::http("foo", 404));
+        assert_eq!("http status 404 for url: foo", err.to_string());
+        assert_eq!("http status 404 for url: foo", err.as_ref().to_string());
+        assert_eq!("http status 404 for url: foo", err.as_mut().to_string());
+        assert!(err.downcast_ref::<NetError>().is_some());
+        assert!(err.downcast_mut::<NetError>().is_some());
+        assert!(err.source().is_none());
+
         let mut err = FuError::from(std::ffi::CString::new(b"f\0oo".to_vec()).unwrap_err());
         assert_eq!("nul byte found in provided data at position: 1", err.to_string());
         assert_eq!("nul byte found in provided data at position: 1", err.as_ref().to_string());
@@ -230,6 +365,22 @@ mod tests {
         assert!(err.downcast_mut::<OsError>().is_some());
         assert!(err.source().is_none());
 
+        let mut err = FuError::from("foo".parse::<f64>().unwrap_err());
+        assert_eq!("invalid float literal", err.to_string());
+        assert_eq!("invalid float literal", err.as_ref().to_string());
+        assert_eq!("invalid float literal", err.as_mut().to_string());
+        assert!(err.downcast_ref::<std::num::ParseFloatError>().is_some());
+        assert!(err.downcast_mut::<std::num::ParseFloatError>().is_some());
+        assert!(err.source().is_none());
+
+        let mut err = FuError::from("foo".parse::<u32>().unwrap_err());
+        assert_eq!("invalid digit found in string", err.to_string());
+        assert_eq!("invalid digit found in string", err.as_ref().to_string());
+        assert_eq!("invalid digit found in string", err.as_mut().to_string());
+        assert!(err.downcast_ref::<std::num::ParseIntError>().is_some());
+        assert!(err.downcast_mut::<std::num::ParseIntError>().is_some());
+        assert!(err.source().is_none());
+
         let mut err = FuError::from(PathError::Empty);
         assert_eq!("path empty", err.to_string());
         assert_eq!("path empty", err.as_ref().to_string());
@@ -254,6 +405,14 @@ mod tests {
         assert!(err.downcast_mut::<StringError>().is_some());
         assert!(err.source().is_none());
 
+        let mut err = FuError::from(TarError::PathTraversal(PathBuf::from("../foo")));
+        assert_eq!("archive entry escapes extraction root: ../foo", err.to_string());
+        assert_eq!("archive entry escapes extraction root: ../foo", err.as_ref().to_string());
+        assert_eq!("archive entry escapes extraction root: ../foo", err.as_mut().to_string());
+        assert!(err.downcast_ref::<TarError>().is_some());
+        assert!(err.downcast_mut::<TarError>().is_some());
+        assert!(err.source().is_none());
+
         let mut err = FuError::from(UserError::DoesNotExistById(1));
         assert_eq!("user does not exist: 1", err.to_string());
         assert_eq!("user does not exist: 1", err.as_ref().to_string());
@@ -262,6 +421,14 @@ mod tests {
         assert!(err.downcast_mut::<UserError>().is_some());
         assert!(err.source().is_none());
 
+        let mut err = FuError::from(std::str::from_utf8(&[0x66, 0xff]).unwrap_err());
+        assert_eq!("invalid utf-8 sequence of 1 bytes from index 1", err.to_string());
+        assert_eq!("invalid utf-8 sequence of 1 bytes from index 1", err.as_ref().to_string());
+        assert_eq!("invalid utf-8 sequence of 1 bytes from index 1", err.as_mut().to_string());
+        assert!(err.downcast_ref::<std::str::Utf8Error>().is_some());
+        assert!(err.downcast_mut::<std::str::Utf8Error>().is_some());
+        assert!(err.source().is_none());
+
         let mut err = FuError::from(std::env::VarError::NotPresent);
         assert_eq!("environment variable not found", err.to_string());
         assert_eq!("environment variable not found", err.as_ref().to_string());
@@ -271,10 +438,36 @@ mod tests {
         assert!(err.source().is_none());
     }
 
+    #[test]
+    fn test_parse_propagation() {
+        fn parse_u32(s: &str) -> FuResult<u32> {
+            Ok(s.parse::<u32>()?)
+        }
+        fn parse_f64(s: &str) -> FuResult<f64> {
+            Ok(s.parse::<f64>()?)
+        }
+        fn parse_utf8(bytes: &[u8]) -> FuResult<&str> {
+            Ok(str::from_utf8(bytes)?)
+        }
+        assert_eq!(parse_u32("42").unwrap(), 42);
+        assert_eq!(parse_u32("-1").unwrap_err().downcast_ref::<std::num::ParseIntError>(), Some(&"-1".parse::<u32>().unwrap_err()));
+        assert_eq!(parse_f64("1.5").unwrap(), 1.5);
+        assert_eq!(parse_f64("foo").unwrap_err().downcast_ref::<std::num::ParseFloatError>(), Some(&"foo".parse::<f64>().unwrap_err()));
+        assert_eq!(parse_utf8(b"foo").unwrap(), "foo");
+        assert_eq!(parse_utf8(&[0x66, 0xff]).unwrap_err().downcast_ref::<std::str::Utf8Error>().unwrap().valid_up_to(), 1);
+    }
+
     #[test]
     fn test_is() {
         assert!(path_empty().is_err());
         assert!(path_empty().unwrap_err().is::<PathError>());
+        assert!(!path_empty().unwrap_err().is::<FileError>());
+    }
+
+    #[test]
+    fn test_as_err() {
+        assert_eq!(path_empty().unwrap_err().as_err::<PathError>(), Some(&PathError::Empty));
+        assert_eq!(path_empty().unwrap_err().as_err::<FileError>(), None);
     }
 
     #[test]
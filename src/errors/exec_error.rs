@@ -0,0 +1,35 @@
+use std::{error::Error as StdError, fmt};
+
+// An error indicating that something went wrong with an exec operation
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ExecError {
+    /// An error indicating that the command exited with a non-zero exit code.
+    NonZeroExit { code: i32, stderr: String },
+}
+impl ExecError {
+    /// Return an error indicating that the command exited with a non-zero exit code
+    pub fn non_zero_exit<T: AsRef<str>>(code: i32, stderr: T) -> ExecError {
+        ExecError::NonZeroExit { code, stderr: stderr.as_ref().to_string() }
+    }
+}
+
+impl StdError for ExecError {}
+
+impl fmt::Display for ExecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExecError::NonZeroExit { code, ref stderr } => write!(f, "command exited with non-zero code {}: {}", code, stderr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(ExecError::non_zero_exit(1, "foo"), ExecError::NonZeroExit { code: 1, stderr: "foo".to_string() });
+        assert_eq!(format!("{}", ExecError::non_zero_exit(1, "foo")), "command exited with non-zero code 1: foo");
+    }
+}
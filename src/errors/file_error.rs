@@ -1,10 +1,47 @@
-use std::{error::Error as StdError, fmt};
+use std::{
+    error::Error as StdError,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 // An error indicating that something went wrong with a file operation
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum FileError {
+    /// An error indicating that a link can't be created across devices.
+    CrossDevice { src: PathBuf, dst: PathBuf },
+
     /// An error indicating that a regex string extraction failed.
     FailedToExtractString,
+
+    /// An error indicating that the symbolic mode is invalid.
+    InvalidMode(String),
+
+    /// An error indicating that a recursive delete of a dangerous path was refused.
+    RefusedDangerousDelete(PathBuf),
+
+    /// An error indicating that the file size exceeds the given limit.
+    TooLarge { size: u64, max: usize },
+}
+impl FileError {
+    /// Return an error indicating that a link can't be created across devices
+    pub fn cross_device<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FileError {
+        FileError::CrossDevice { src: src.as_ref().to_path_buf(), dst: dst.as_ref().to_path_buf() }
+    }
+
+    /// Return an error indicating that the symbolic mode is invalid
+    pub fn invalid_mode<T: AsRef<str>>(mode: T) -> FileError {
+        FileError::InvalidMode(mode.as_ref().to_string())
+    }
+
+    /// Return an error indicating that a recursive delete of a dangerous path was refused
+    pub fn refused_dangerous_delete<T: AsRef<Path>>(path: T) -> FileError {
+        FileError::RefusedDangerousDelete(path.as_ref().to_path_buf())
+    }
+
+    /// Return an error indicating that the file size exceeds the given limit
+    pub fn too_large(size: u64, max: usize) -> FileError {
+        FileError::TooLarge { size, max }
+    }
 }
 
 impl StdError for FileError {}
@@ -12,15 +49,29 @@ impl StdError for FileError {}
 impl fmt::Display for FileError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            FileError::CrossDevice { ref src, ref dst } => write!(f, "cannot link across devices: {} to {}", src.display(), dst.display()),
             FileError::FailedToExtractString => write!(f, "failed to extract string from file"),
+            FileError::InvalidMode(ref mode) => write!(f, "invalid symbolic mode: {}", mode),
+            FileError::RefusedDangerousDelete(ref path) => write!(f, "refusing to recursively delete dangerous path: {}", path.display()),
+            FileError::TooLarge { size, max } => write!(f, "file size {} exceeds limit of {} bytes", size, max),
         }
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use crate::prelude::*;
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
 
-//     #[test]
-//     fn test_errors() {}
-// }
+    #[test]
+    fn test_errors() {
+        assert_eq!(FileError::cross_device("foo", "bar"), FileError::CrossDevice { src: PathBuf::from("foo"), dst: PathBuf::from("bar") });
+        assert_eq!(format!("{}", FileError::cross_device("foo", "bar")), "cannot link across devices: foo to bar");
+        assert_eq!(format!("{}", FileError::FailedToExtractString), "failed to extract string from file");
+        assert_eq!(FileError::invalid_mode("u+q"), FileError::InvalidMode("u+q".to_string()));
+        assert_eq!(format!("{}", FileError::InvalidMode("u+q".to_string())), "invalid symbolic mode: u+q");
+        assert_eq!(FileError::refused_dangerous_delete("/"), FileError::RefusedDangerousDelete(PathBuf::from("/")));
+        assert_eq!(format!("{}", FileError::refused_dangerous_delete("/")), "refusing to recursively delete dangerous path: /");
+        assert_eq!(FileError::too_large(10, 5), FileError::TooLarge { size: 10, max: 5 });
+        assert_eq!(format!("{}", FileError::TooLarge { size: 10, max: 5 }), "file size 10 exceeds limit of 5 bytes");
+    }
+}
@@ -0,0 +1,105 @@
+use std::{
+    error::Error as StdError,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+// An error indicating that something went wrong with a git operation
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum GitError {
+    /// An error indicating that the branch does not exist.
+    BranchNotFound(String),
+
+    /// An error indicating that the git command failed with the given message.
+    CommandFailed(String),
+
+    /// An error indicating that the destination exists already and is not empty.
+    DestinationNotEmpty(PathBuf),
+
+    /// An error indicating that the repo's HEAD is detached rather than on a branch.
+    DetachedHead(PathBuf),
+
+    /// An error indicating that the repo's worktree has local changes that would be overwritten.
+    DirtyWorktree(PathBuf),
+
+    /// An error indicating that the path is not within a git repo.
+    NotARepo(PathBuf),
+
+    /// An error indicating that the ref does not exist.
+    RefNotFound(String),
+}
+impl GitError {
+    /// Return an error indicating that the branch does not exist
+    pub fn branch_not_found<T: AsRef<str>>(branch: T) -> GitError {
+        GitError::BranchNotFound(branch.as_ref().to_string())
+    }
+
+    /// Return an error indicating that the git command failed with the given message
+    pub fn command_failed<T: AsRef<str>>(msg: T) -> GitError {
+        GitError::CommandFailed(msg.as_ref().to_string())
+    }
+
+    /// Return an error indicating that the destination exists already and is not empty
+    pub fn destination_not_empty<T: AsRef<Path>>(path: T) -> GitError {
+        GitError::DestinationNotEmpty(path.as_ref().to_path_buf())
+    }
+
+    /// Return an error indicating that the repo's HEAD is detached rather than on a branch
+    pub fn detached_head<T: AsRef<Path>>(repo: T) -> GitError {
+        GitError::DetachedHead(repo.as_ref().to_path_buf())
+    }
+
+    /// Return an error indicating that the repo's worktree has local changes that would be overwritten
+    pub fn dirty_worktree<T: AsRef<Path>>(repo: T) -> GitError {
+        GitError::DirtyWorktree(repo.as_ref().to_path_buf())
+    }
+
+    /// Return an error indicating that the path is not within a git repo
+    pub fn not_a_repo<T: AsRef<Path>>(path: T) -> GitError {
+        GitError::NotARepo(path.as_ref().to_path_buf())
+    }
+
+    /// Return an error indicating that the ref does not exist
+    pub fn ref_not_found<T: AsRef<str>>(refname: T) -> GitError {
+        GitError::RefNotFound(refname.as_ref().to_string())
+    }
+}
+
+impl StdError for GitError {}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GitError::BranchNotFound(ref branch) => write!(f, "git branch not found: {}", branch),
+            GitError::CommandFailed(ref msg) => write!(f, "git command failed: {}", msg),
+            GitError::DestinationNotEmpty(ref path) => write!(f, "git destination is not empty: {}", path.display()),
+            GitError::DetachedHead(ref path) => write!(f, "git HEAD is detached for repo: {}", path.display()),
+            GitError::DirtyWorktree(ref path) => write!(f, "git worktree has local changes for repo: {}", path.display()),
+            GitError::NotARepo(ref path) => write!(f, "git repo not found for path: {}", path.display()),
+            GitError::RefNotFound(ref refname) => write!(f, "git ref not found: {}", refname),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(GitError::branch_not_found("foo"), GitError::BranchNotFound("foo".to_string()));
+        assert_eq!(format!("{}", GitError::BranchNotFound("foo".to_string())), "git branch not found: foo");
+        assert_eq!(GitError::command_failed("foo"), GitError::CommandFailed("foo".to_string()));
+        assert_eq!(format!("{}", GitError::CommandFailed("foo".to_string())), "git command failed: foo");
+        assert_eq!(GitError::destination_not_empty("foo"), GitError::DestinationNotEmpty(PathBuf::from("foo")));
+        assert_eq!(format!("{}", GitError::DestinationNotEmpty(PathBuf::from("foo"))), "git destination is not empty: foo");
+        assert_eq!(GitError::detached_head("foo"), GitError::DetachedHead(PathBuf::from("foo")));
+        assert_eq!(format!("{}", GitError::DetachedHead(PathBuf::from("foo"))), "git HEAD is detached for repo: foo");
+        assert_eq!(GitError::dirty_worktree("foo"), GitError::DirtyWorktree(PathBuf::from("foo")));
+        assert_eq!(format!("{}", GitError::DirtyWorktree(PathBuf::from("foo"))), "git worktree has local changes for repo: foo");
+        assert_eq!(GitError::not_a_repo("foo"), GitError::NotARepo(PathBuf::from("foo")));
+        assert_eq!(format!("{}", GitError::NotARepo(PathBuf::from("foo"))), "git repo not found for path: foo");
+        assert_eq!(GitError::ref_not_found("foo"), GitError::RefNotFound("foo".to_string()));
+        assert_eq!(format!("{}", GitError::RefNotFound("foo".to_string())), "git ref not found: foo");
+    }
+}
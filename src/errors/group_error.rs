@@ -0,0 +1,46 @@
+use std::{error::Error as StdError, fmt};
+
+// An error indicating that something went wrong with a group operation
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum GroupError {
+    /// An error indicating that the group does not exist.
+    DoesNotExistById(u32),
+
+    /// An error indicating that the group does not exist.
+    DoesNotExistByName(String),
+}
+impl GroupError {
+    /// Return an error indicating that the group does not exist
+    pub fn does_not_exist_by_id(gid: u32) -> GroupError {
+        GroupError::DoesNotExistById(gid)
+    }
+
+    /// Return an error indicating that the group does not exist
+    pub fn does_not_exist_by_name<T: AsRef<str>>(name: T) -> GroupError {
+        GroupError::DoesNotExistByName(name.as_ref().to_string())
+    }
+}
+
+impl StdError for GroupError {}
+
+impl fmt::Display for GroupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GroupError::DoesNotExistById(ref gid) => write!(f, "group does not exist: {}", gid),
+            GroupError::DoesNotExistByName(ref name) => write!(f, "group does not exist: {}", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(GroupError::does_not_exist_by_id(1000), GroupError::DoesNotExistById(1000));
+        assert_eq!(format!("{}", GroupError::DoesNotExistById(1000)), "group does not exist: 1000");
+        assert_eq!(GroupError::does_not_exist_by_name("foo"), GroupError::DoesNotExistByName("foo".to_string()));
+        assert_eq!(format!("{}", GroupError::DoesNotExistByName("foo".to_string())), "group does not exist: foo");
+    }
+}
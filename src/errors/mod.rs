@@ -1,15 +1,27 @@
+mod enc_error;
 mod error;
+mod exec_error;
 mod file_error;
+mod git_error;
+mod group_error;
 mod iter_error;
+mod net_error;
 mod os_error;
 mod path_error;
 mod string_error;
+mod tar_error;
 mod user_error;
 
+pub use enc_error::*;
 pub use error::*;
+pub use exec_error::*;
 pub use file_error::*;
+pub use git_error::*;
+pub use group_error::*;
 pub use iter_error::*;
+pub use net_error::*;
 pub use os_error::*;
 pub use path_error::*;
 pub use string_error::*;
+pub use tar_error::*;
 pub use user_error::*;
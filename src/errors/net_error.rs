@@ -0,0 +1,68 @@
+use std::{error::Error as StdError, fmt};
+
+// An error indicating that something went wrong with a network operation
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum NetError {
+    /// An error indicating that the downloaded content's checksum doesn't match the expected value.
+    ChecksumMismatch { expected: String, actual: String },
+
+    /// An error indicating that the host name could not be resolved.
+    Dns(String),
+
+    /// An error indicating that the server responded with an unexpected HTTP status.
+    Http { url: String, status: u16 },
+
+    /// An error indicating that the network operation timed out.
+    Timeout(String),
+}
+impl NetError {
+    /// Return an error indicating that the downloaded content's checksum doesn't match the expected value
+    pub fn checksum_mismatch<T: AsRef<str>, U: AsRef<str>>(expected: T, actual: U) -> NetError {
+        NetError::ChecksumMismatch { expected: expected.as_ref().to_string(), actual: actual.as_ref().to_string() }
+    }
+
+    /// Return an error indicating that the host name could not be resolved
+    pub fn dns<T: AsRef<str>>(url: T) -> NetError {
+        NetError::Dns(url.as_ref().to_string())
+    }
+
+    /// Return an error indicating that the server responded with an unexpected HTTP status
+    pub fn http<T: AsRef<str>>(url: T, status: u16) -> NetError {
+        NetError::Http { url: url.as_ref().to_string(), status }
+    }
+
+    /// Return an error indicating that the network operation timed out
+    pub fn timeout<T: AsRef<str>>(url: T) -> NetError {
+        NetError::Timeout(url.as_ref().to_string())
+    }
+}
+
+impl StdError for NetError {}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NetError::ChecksumMismatch { ref expected, ref actual } => write!(f, "checksum mismatch: expected {} but got {}", expected, actual),
+            NetError::Dns(ref url) => write!(f, "failed to resolve host for url: {}", url),
+            NetError::Http { ref url, status } => write!(f, "http status {} for url: {}", status, url),
+            NetError::Timeout(ref url) => write!(f, "timed out for url: {}", url),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(NetError::checksum_mismatch("foo", "bar"), NetError::ChecksumMismatch { expected: "foo".to_string(), actual: "bar".to_string() });
+        assert_eq!(format!("{}", NetError::checksum_mismatch("foo", "bar")), "checksum mismatch: expected foo but got bar");
+        assert_eq!(NetError::dns("foo"), NetError::Dns("foo".to_string()));
+        assert_eq!(format!("{}", NetError::dns("foo")), "failed to resolve host for url: foo");
+        assert_eq!(NetError::http("foo", 404), NetError::Http { url: "foo".to_string(), status: 404 });
+        assert_eq!(format!("{}", NetError::http("foo", 404)), "http status 404 for url: foo");
+        assert_eq!(NetError::timeout("foo"), NetError::Timeout("foo".to_string()));
+        assert_eq!(format!("{}", NetError::timeout("foo")), "timed out for url: foo");
+    }
+}
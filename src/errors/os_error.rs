@@ -1,4 +1,8 @@
-use std::{error::Error as StdError, fmt};
+use std::{
+    error::Error as StdError,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 // An error indicating that something went wrong with an os operation
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -8,6 +12,15 @@ pub enum OsError {
 
     /// An error indicating that the kernel version was not found.
     KernelVersionNotFound,
+
+    /// An error indicating that no mount was found for the path.
+    MountNotFound(PathBuf),
+}
+impl OsError {
+    /// Return an error indicating that no mount was found for the path
+    pub fn mount_not_found<T: AsRef<Path>>(path: T) -> OsError {
+        OsError::MountNotFound(path.as_ref().to_path_buf())
+    }
 }
 
 impl StdError for OsError {}
@@ -17,6 +30,7 @@ impl fmt::Display for OsError {
         match *self {
             OsError::KernelReleaseNotFound => write!(f, "kernel release was not found"),
             OsError::KernelVersionNotFound => write!(f, "kernel version was not found"),
+            OsError::MountNotFound(ref path) => write!(f, "mount was not found for path: {}", path.display()),
         }
     }
 }
@@ -29,5 +43,7 @@ mod tests {
     fn test_errors() {
         assert_eq!(format!("{}", OsError::KernelReleaseNotFound), "kernel release was not found");
         assert_eq!(format!("{}", OsError::KernelVersionNotFound), "kernel version was not found");
+        assert_eq!(OsError::mount_not_found("foo"), OsError::MountNotFound(PathBuf::from("foo")));
+        assert_eq!(format!("{}", OsError::MountNotFound(PathBuf::from("foo"))), "mount was not found for path: foo");
     }
 }
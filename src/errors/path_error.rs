@@ -7,6 +7,9 @@ use std::{
 // An error indicating that something went wrong with a path operation
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum PathError {
+    /// An error indicating that the path contains a path list separator i.e. colon.
+    ContainsSeparator(PathBuf),
+
     /// An error indicating that the path does not exist.
     DoesNotExist(PathBuf),
 
@@ -43,10 +46,21 @@ pub enum PathError {
     /// An error indicating that the path contains multiple user home symbols i.e. tilda.
     MultipleHomeSymbols(PathBuf),
 
+    /// An error indicating that the path does not share a common root with its base.
+    NoCommonRoot(PathBuf),
+
     /// An error indicating that the path does not have a valid parent path.
     ParentNotFound(PathBuf),
+
+    /// An error indicating that the path references an undefined environment variable.
+    UndefinedVar(String),
 }
 impl PathError {
+    /// Return an error indicating that the path contains a path list separator i.e. colon
+    pub fn contains_separator<T: AsRef<Path>>(path: T) -> PathError {
+        PathError::ContainsSeparator(path.as_ref().to_path_buf())
+    }
+
     /// Return an error indicating that the path does not exist
     pub fn does_not_exist<T: AsRef<Path>>(path: T) -> PathError {
         PathError::DoesNotExist(path.as_ref().to_path_buf())
@@ -102,10 +116,20 @@ impl PathError {
         PathError::MultipleHomeSymbols(path.as_ref().to_path_buf())
     }
 
+    /// Return an error indicating that the path does not share a common root with its base
+    pub fn no_common_root<T: AsRef<Path>>(path: T) -> PathError {
+        PathError::NoCommonRoot(path.as_ref().to_path_buf())
+    }
+
     /// Return an error indicating that the path does not have a valid parent path
     pub fn parent_not_found<T: AsRef<Path>>(path: T) -> PathError {
         PathError::ParentNotFound(path.as_ref().to_path_buf())
     }
+
+    /// Return an error indicating that the path references an undefined environment variable
+    pub fn undefined_var<T: AsRef<str>>(var: T) -> PathError {
+        PathError::UndefinedVar(var.as_ref().to_string())
+    }
 }
 
 impl StdError for PathError {}
@@ -119,6 +143,7 @@ impl AsRef<dyn StdError> for PathError {
 impl fmt::Display for PathError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            PathError::ContainsSeparator(ref path) => write!(f, "path contains a separator: {}", path.display()),
             PathError::DoesNotExist(ref path) => write!(f, "path does not exist: {}", path.display()),
             PathError::Empty => write!(f, "path empty"),
             PathError::ExistsAlready(ref path) => write!(f, "path exists already: {}", path.display()),
@@ -131,7 +156,9 @@ impl fmt::Display for PathError {
             PathError::IsNotFile(ref path) => write!(f, "is not a file: {}", path.display()),
             PathError::IsNotFileOrSymlinkToFile(ref path) => write!(f, "is not a file or a symlink to a file: {}", path.display()),
             PathError::MultipleHomeSymbols(ref path) => write!(f, "multiple home symbols for path: {}", path.display()),
+            PathError::NoCommonRoot(ref path) => write!(f, "no common root for path: {}", path.display()),
             PathError::ParentNotFound(ref path) => write!(f, "parent not found for path: {}", path.display()),
+            PathError::UndefinedVar(ref var) => write!(f, "undefined variable for path expansion: {}", var),
         }
     }
 }
@@ -164,6 +191,8 @@ mod tests {
 
     #[test]
     fn test_other_errors() {
+        assert_eq!(PathError::contains_separator(Path::new("foo")), PathError::ContainsSeparator(PathBuf::from("foo")));
+        assert_eq!(format!("{}", PathError::ContainsSeparator(PathBuf::from("foo"))), "path contains a separator: foo");
         assert_eq!(PathError::does_not_exist(Path::new("foo")), PathError::DoesNotExist(PathBuf::from("foo")));
         assert_eq!(format!("{}", PathError::DoesNotExist(PathBuf::from("foo"))), "path does not exist: foo");
         assert_eq!(format!("{}", PathError::Empty), "path empty");
@@ -187,6 +216,10 @@ mod tests {
         assert_eq!(format!("{}", PathError::is_not_file_or_symlink_to_file(PathBuf::from("foo"))), "is not a file or a symlink to a file: foo");
         assert_eq!(PathError::multiple_home_symbols(Path::new("foo")), PathError::MultipleHomeSymbols(PathBuf::from("foo")));
         assert_eq!(format!("{}", PathError::multiple_home_symbols(PathBuf::from("foo"))), "multiple home symbols for path: foo");
+        assert_eq!(PathError::no_common_root(Path::new("foo")), PathError::NoCommonRoot(PathBuf::from("foo")));
+        assert_eq!(format!("{}", PathError::no_common_root(PathBuf::from("foo"))), "no common root for path: foo");
+        assert_eq!(PathError::undefined_var("FOO"), PathError::UndefinedVar("FOO".to_string()));
+        assert_eq!(format!("{}", PathError::undefined_var("FOO")), "undefined variable for path expansion: FOO");
     }
 
     #[test]
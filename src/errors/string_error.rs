@@ -1,18 +1,48 @@
 use std::{error::Error as StdError, fmt};
 
-// An error indicating that something went wrong with a file operation
+// An error indicating that something went wrong with a string operation
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum StringError {
+    /// An error indicating that the string is empty.
+    Empty,
+
     /// An error indicating a failure to convert the file value to a string.
     FailedToString,
+
+    /// An error indicating that the string contains invalid UTF-8 at the given position.
+    InvalidUtf8 { pos: usize },
+}
+impl StringError {
+    /// Return an error indicating that the string is empty
+    pub fn empty() -> StringError {
+        StringError::Empty
+    }
+
+    /// Return an error indicating a failure to convert the value to a string
+    pub fn failed_to_string() -> StringError {
+        StringError::FailedToString
+    }
+
+    /// Return an error indicating that the string contains invalid UTF-8 at the given position
+    pub fn invalid_utf8(pos: usize) -> StringError {
+        StringError::InvalidUtf8 { pos }
+    }
 }
 
 impl StdError for StringError {}
 
+impl AsRef<dyn StdError> for StringError {
+    fn as_ref(&self) -> &(dyn StdError+'static) {
+        self
+    }
+}
+
 impl fmt::Display for StringError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            StringError::Empty => write!(f, "string empty"),
             StringError::FailedToString => write!(f, "failed to convert value to string"),
+            StringError::InvalidUtf8 { pos } => write!(f, "invalid utf-8 string at position: {}", pos),
         }
     }
 }
@@ -21,8 +51,24 @@ impl fmt::Display for StringError {
 mod tests {
     use crate::errors::*;
 
+    fn string_empty() -> FuResult<String> {
+        Err(StringError::empty())?
+    }
+
+    #[test]
+    fn test_downcast() {
+        assert!(string_empty().is_err());
+        assert_eq!(string_empty().unwrap_err().downcast_ref::<StringError>(), Some(&StringError::Empty));
+        assert_ne!(string_empty().unwrap_err().downcast_ref::<StringError>(), Some(&StringError::FailedToString));
+    }
+
     #[test]
     fn test_errors() {
+        assert_eq!(StringError::empty(), StringError::Empty);
+        assert_eq!(format!("{}", StringError::Empty), "string empty");
+        assert_eq!(StringError::failed_to_string(), StringError::FailedToString);
         assert_eq!(format!("{}", StringError::FailedToString), "failed to convert value to string");
+        assert_eq!(StringError::invalid_utf8(3), StringError::InvalidUtf8 { pos: 3 });
+        assert_eq!(format!("{}", StringError::InvalidUtf8 { pos: 3 }), "invalid utf-8 string at position: 3");
     }
 }
@@ -0,0 +1,61 @@
+use std::{
+    error::Error as StdError,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+// An error indicating that something went wrong with a tar operation
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TarError {
+    /// An error indicating that the archive is compressed and can't be appended to.
+    Compressed(PathBuf),
+
+    /// An error indicating that the archive is not gzip compressed.
+    NotGzipped(PathBuf),
+
+    /// An error indicating that the archive entry resolves outside the extraction root.
+    PathTraversal(PathBuf),
+}
+impl TarError {
+    /// Return an error indicating that the archive is compressed and can't be appended to
+    pub fn compressed<T: AsRef<Path>>(path: T) -> TarError {
+        TarError::Compressed(path.as_ref().to_path_buf())
+    }
+
+    /// Return an error indicating that the archive is not gzip compressed
+    pub fn not_gzipped<T: AsRef<Path>>(path: T) -> TarError {
+        TarError::NotGzipped(path.as_ref().to_path_buf())
+    }
+
+    /// Return an error indicating that the archive entry resolves outside the extraction root
+    pub fn path_traversal<T: AsRef<Path>>(path: T) -> TarError {
+        TarError::PathTraversal(path.as_ref().to_path_buf())
+    }
+}
+
+impl StdError for TarError {}
+
+impl fmt::Display for TarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TarError::Compressed(ref path) => write!(f, "archive is compressed and can't be appended to: {}", path.display()),
+            TarError::NotGzipped(ref path) => write!(f, "archive is not gzip compressed: {}", path.display()),
+            TarError::PathTraversal(ref path) => write!(f, "archive entry escapes extraction root: {}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(TarError::compressed("foo.tgz"), TarError::Compressed(PathBuf::from("foo.tgz")));
+        assert_eq!(format!("{}", TarError::Compressed(PathBuf::from("foo.tgz"))), "archive is compressed and can't be appended to: foo.tgz");
+        assert_eq!(TarError::not_gzipped("foo.tar"), TarError::NotGzipped(PathBuf::from("foo.tar")));
+        assert_eq!(format!("{}", TarError::NotGzipped(PathBuf::from("foo.tar"))), "archive is not gzip compressed: foo.tar");
+        assert_eq!(TarError::path_traversal("../foo"), TarError::PathTraversal(PathBuf::from("../foo")));
+        assert_eq!(format!("{}", TarError::PathTraversal(PathBuf::from("../foo"))), "archive entry escapes extraction root: ../foo");
+    }
+}
@@ -3,14 +3,38 @@ use std::{error::Error as StdError, fmt};
 // An error indicating that something went wrong with a user operation
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum UserError {
-    /// An error indicating that the use does not exist.
+    /// An error indicating that the user does not exist.
     DoesNotExistById(u32),
+
+    /// An error indicating that the user does not exist.
+    DoesNotExistByName(String),
+
+    /// An error indicating that the user has no home directory.
+    HomeNotFound(String),
+
+    /// An error indicating that switching to the given real and effective user ids failed.
+    SwitchFailed { ruid: u32, euid: u32 },
 }
 impl UserError {
     /// Return an error indicating that the user does not exist
     pub fn does_not_exist_by_id(uid: u32) -> UserError {
         UserError::DoesNotExistById(uid)
     }
+
+    /// Return an error indicating that the user does not exist
+    pub fn does_not_exist_by_name<T: AsRef<str>>(name: T) -> UserError {
+        UserError::DoesNotExistByName(name.as_ref().to_string())
+    }
+
+    /// Return an error indicating that the user has no home directory
+    pub fn home_not_found<T: AsRef<str>>(name: T) -> UserError {
+        UserError::HomeNotFound(name.as_ref().to_string())
+    }
+
+    /// Return an error indicating that switching to the given real and effective user ids failed
+    pub fn switch_failed(ruid: u32, euid: u32) -> UserError {
+        UserError::SwitchFailed { ruid, euid }
+    }
 }
 
 impl StdError for UserError {}
@@ -19,6 +43,9 @@ impl fmt::Display for UserError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             UserError::DoesNotExistById(ref uid) => write!(f, "user does not exist: {}", uid),
+            UserError::DoesNotExistByName(ref name) => write!(f, "user does not exist: {}", name),
+            UserError::HomeNotFound(ref name) => write!(f, "user has no home directory: {}", name),
+            UserError::SwitchFailed { ruid, euid } => write!(f, "failed to switch user to ruid {} and euid {}", ruid, euid),
         }
     }
 }
@@ -31,5 +58,11 @@ mod tests {
     fn test_errors() {
         assert_eq!(UserError::does_not_exist_by_id(1000), UserError::DoesNotExistById(1000));
         assert_eq!(format!("{}", UserError::DoesNotExistById(1000)), "user does not exist: 1000");
+        assert_eq!(UserError::does_not_exist_by_name("foo"), UserError::DoesNotExistByName("foo".to_string()));
+        assert_eq!(format!("{}", UserError::DoesNotExistByName("foo".to_string())), "user does not exist: foo");
+        assert_eq!(UserError::home_not_found("foo"), UserError::HomeNotFound("foo".to_string()));
+        assert_eq!(format!("{}", UserError::HomeNotFound("foo".to_string())), "user has no home directory: foo");
+        assert_eq!(UserError::switch_failed(1000, 0), UserError::SwitchFailed { ruid: 1000, euid: 0 });
+        assert_eq!(format!("{}", UserError::SwitchFailed { ruid: 1000, euid: 0 }), "failed to switch user to ruid 1000 and euid 0");
     }
 }
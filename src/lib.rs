@@ -22,11 +22,11 @@ pub mod prelude {
         cfgblock,
         core::*,
         defer,
-        enc::{gzip, tar},
+        enc::{base64, gzip, hex, tar},
         errors::*,
         function,
-        net::{self, agent},
-        sys::{self, exec, ext::*, user},
+        net::{self, agent, git},
+        sys::{self, exec, ext::*, group, user},
         unit::{self, time},
     };
 
@@ -46,6 +46,7 @@ pub mod prelude {
         path::{Path, PathBuf},
         str,
     };
+    pub use ureq;
 }
 
 /// All essential symbols for testing in a simple consumable way
@@ -62,7 +63,9 @@ pub mod assert {
 /// Inspired by Golang's `defer`, Java's finally and Ruby's `ensure`.
 ///
 /// This provides a mechanism similar to Golang's `defer` that will trigger when the
-/// surrounding function goes out of scope.
+/// surrounding function goes out of scope. Multiple calls within the same scope all fire in
+/// reverse order of registration as each expansion's binding is hygienic and shadowed bindings
+/// are only dropped at the end of the scope.
 ///
 /// ### Examples
 /// ```
@@ -127,7 +130,7 @@ macro_rules! function {
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
-    use std::cell::Cell;
+    use std::cell::{Cell, RefCell};
 
     #[test]
     fn test_defer_macro() {
@@ -136,6 +139,18 @@ mod tests {
         assert_eq!(1, obj.get());
     }
 
+    #[test]
+    fn test_defer_macro_lifo() {
+        let order = RefCell::new(vec![]);
+        {
+            defer!(order.borrow_mut().push(1));
+            defer!(order.borrow_mut().push(2));
+            defer!(order.borrow_mut().push(3));
+            assert!(order.borrow().is_empty());
+        }
+        assert_eq!(*order.borrow(), vec![3, 2, 1]);
+    }
+
     #[test]
     fn test_function_macro() {
         fn indirect_func_name() -> &'static str {
@@ -1,4 +1,5 @@
 #![allow(dead_code)]
+use std::time::Duration;
 
 // Agent identifiers
 pub const ANDROID5_NEXUS7: &str = "Mozilla/5.0 (Linux; Android 5.1.1; Nexus 7 Build/LMY47V) AppleWebKit/537.36 (KHTML like Gecko) Chrome/47.0.2526.76 Safari/537.36";
@@ -33,11 +34,49 @@ pub const WINDOWS_EDGE14: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) Appl
 pub const WINDOWS_MOZILLA: &str = "Mozilla/5.0 (Windows; U; Windows NT 5.0; en-US; rv:1.4b) Gecko/20030516 Mozilla Firebird/0.6";
 pub const WINDOWS_FIREFOX43: &str = "Mozilla/5.0 (Windows NT 6.3; WOW64; rv:43.0) Gecko/20100101 Firefox/43.0";
 
+/// Default user agent identifying this crate by name and version i.e. `fungus/0.1.27`
+pub const DEFAULT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Configuration for the HTTP client returned by `with_config`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// User-Agent header value sent with every request
+    pub user_agent: String,
+
+    /// Timeout for establishing the connection
+    pub connect_timeout: Duration,
+
+    /// Timeout for individual reads from the connection
+    pub read_timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { user_agent: DEFAULT.to_string(), connect_timeout: Duration::from_secs(30), read_timeout: Duration::from_secs(30) }
+    }
+}
+
+/// Returns a reusable HTTP client configured with the given `cfg`. Connections are pooled
+/// between requests so reuse the client rather than constructing one per request.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+/// use std::time::Duration;
+///
+/// let cfg = agent::Config { user_agent: agent::LINUX_FIREFOX43.to_string(), connect_timeout: Duration::from_secs(5), ..Default::default() };
+/// let client = agent::with_config(cfg);
+/// ```
+pub fn with_config(cfg: Config) -> ureq::Agent {
+    ureq::AgentBuilder::new().user_agent(&cfg.user_agent).timeout_connect(cfg.connect_timeout).timeout_read(cfg.read_timeout).build()
+}
+
 // Unit tests
 // -------------------------------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
+    use std::{error::Error as StdError, net::TcpListener, time::Duration};
 
     #[test]
     fn test_agents() {
@@ -73,4 +112,35 @@ mod tests {
         assert_ne!(agent::WINDOWS_MOZILLA, "");
         assert_ne!(agent::WINDOWS_FIREFOX43, "");
     }
+
+    #[test]
+    fn test_config() {
+        let cfg = agent::Config::default();
+        assert_eq!(cfg.user_agent, format!("fungus/{}", env!("CARGO_PKG_VERSION")));
+        assert_eq!(cfg.connect_timeout, Duration::from_secs(30));
+        assert_eq!(cfg.read_timeout, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_with_config_timeout() {
+        // accept connections but never respond
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        let cfg = agent::Config { read_timeout: Duration::from_millis(100), ..Default::default() };
+        match agent::with_config(cfg).get(&url).call() {
+            Err(ureq::Error::Transport(err)) => {
+                let err = err.source().and_then(|x| x.downcast_ref::<io::Error>()).unwrap();
+                assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+            },
+            _ => panic!("expected a timeout"),
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_with_config_timeout_unroutable() {
+        let cfg = agent::Config { connect_timeout: Duration::from_millis(100), ..Default::default() };
+        assert!(agent::with_config(cfg).get("http://10.255.255.1/").call().is_err());
+    }
 }
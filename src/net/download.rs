@@ -0,0 +1,284 @@
+use crate::{
+    enc::hex,
+    errors::*,
+    net::agent,
+    sys::{self, PathExt},
+};
+use std::{
+    error::Error as StdError,
+    fs::{self, OpenOptions},
+    io,
+    path::Path,
+};
+
+/// Options for controlling the behavior of `download`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DownloadOpts {
+    /// Resume a previous partial download by requesting only the missing bytes
+    pub resume: bool,
+
+    /// Verify the downloaded file against the given hex encoded sha256 digest
+    pub expected_sha256: Option<String>,
+}
+
+/// Download the given `url` to the `dst` file returning the final size of the file in bytes.
+/// Handles path expansion and creates the parent directory as needed. When `opts.resume` is
+/// set and a partial file exists a `Range` header is sent and the remaining bytes are appended,
+/// starting over with a full download if the server's `Content-Range` doesn't resume there.
+/// When `opts.expected_sha256` is set the completed file is verified, inline via a
+/// `sys::DigestReader` when the full content is streamed or else via `sys::digest_sha256`.
+/// Uses a client with the default `agent::Config`.
+///
+/// ### Examples
+/// ```no_run
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("download_doc_download");
+/// let dst = tmpdir.mash("README.md");
+/// let opts = net::DownloadOpts { resume: true, ..Default::default() };
+/// assert!(net::download("https://raw.githubusercontent.com/phR0ze/fungus/master/README.md", &dst, opts).is_ok());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn download<T: AsRef<Path>>(url: &str, dst: T, opts: DownloadOpts) -> FuResult<u64> {
+    download_with(&agent::with_config(agent::Config::default()), url, dst, opts)
+}
+
+/// Wraps `download` to use the given `client` from `agent::with_config` allowing for a custom
+/// user agent and timeouts as well as connection reuse between downloads.
+///
+/// ### Examples
+/// ```no_run
+/// use fungus::prelude::*;
+///
+/// let client = agent::with_config(agent::Config { user_agent: agent::LINUX_FIREFOX43.to_string(), ..Default::default() });
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("download_doc_download_with");
+/// let dst = tmpdir.mash("README.md");
+/// assert!(net::download_with(&client, "https://raw.githubusercontent.com/phR0ze/fungus/master/README.md", &dst, Default::default()).is_ok());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn download_with<T: AsRef<Path>>(client: &ureq::Agent, url: &str, dst: T, opts: DownloadOpts) -> FuResult<u64> {
+    let dst = dst.as_ref().abs()?;
+    sys::mkdir(dst.dir()?)?;
+
+    // Determine how much of the file was already downloaded
+    let offset = match opts.resume && dst.exists() {
+        true => fs::metadata(&dst)?.len(),
+        false => 0,
+    };
+
+    // Only a full download can be hashed inline
+    let mut digest = None;
+    let mut req = client.get(url);
+    if offset > 0 {
+        req = req.set("Range", &format!("bytes={}-", offset));
+    }
+    match req.call() {
+        // Server honored the range request so append the remaining bytes
+        Ok(res) if res.status() == 206 && offset > 0 => {
+            // Start over if the server didn't resume at the end of the partial file
+            if !resumes_at(res.header("Content-Range"), offset) {
+                return download_with(client, url, &dst, DownloadOpts { resume: false, ..opts });
+            }
+            let mut file = OpenOptions::new().append(true).open(&dst)?;
+            io::copy(&mut res.into_reader(), &mut file)?;
+        },
+
+        // Server sent the full content so start over
+        Ok(res) => {
+            let mut file = OpenOptions::new().create(true).write(true).truncate(true).open(&dst)?;
+            let mut reader = sys::DigestReader::with_algo(res.into_reader(), sys::DigestAlgo::Sha256);
+            io::copy(&mut reader, &mut file)?;
+            digest = Some(reader.finalize());
+        },
+
+        // Range not satisfiable means the file was already complete
+        Err(ureq::Error::Status(416, _)) if offset > 0 => {},
+        Err(ureq::Error::Status(status, _)) => return Err(NetError::http(url, status).into()),
+        Err(ureq::Error::Transport(err)) => return Err(transport(url, err)),
+    }
+
+    if let Some(expected) = opts.expected_sha256 {
+        verify(&dst, &expected, digest)?;
+    }
+    Ok(fs::metadata(&dst)?.len())
+}
+
+/// Private implementation to check that the given `Content-Range` header value e.g.
+/// `bytes 5-26/27` starts at the given `offset`
+fn resumes_at(range: Option<&str>, offset: u64) -> bool {
+    match range.and_then(|x| x.strip_prefix("bytes ")) {
+        Some(x) => x.split('-').next().and_then(|x| x.trim().parse::<u64>().ok()) == Some(offset),
+        None => false,
+    }
+}
+
+/// Private implementation to map the given transport error into a `NetError` where possible
+fn transport(url: &str, err: ureq::Transport) -> FuError {
+    let timedout = match err.source().and_then(|x| x.downcast_ref::<io::Error>()) {
+        Some(x) => matches!(x.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock),
+        None => false,
+    };
+    match err.kind() {
+        ureq::ErrorKind::Dns => NetError::dns(url).into(),
+        _ if timedout => NetError::timeout(url).into(),
+        _ => io::Error::other(err).into(),
+    }
+}
+
+/// Private implementation to verify the sha256 digest of the given `path` using the given
+/// `digest` when already computed inline
+fn verify(path: &Path, expected: &str, digest: Option<Vec<u8>>) -> FuResult<()> {
+    let actual = hex::encode(match digest {
+        Some(x) => x,
+        None => sys::digest_sha256(path)?,
+    });
+    if actual != expected.to_lowercase() {
+        return Err(NetError::checksum_mismatch(expected, actual).into());
+    }
+    Ok(())
+}
+
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use std::{
+        net::TcpListener,
+        thread::{self, JoinHandle},
+        time::Duration,
+    };
+
+    // Test setup
+    fn setup() -> PathBuf {
+        let temp = PathBuf::from("tests/temp").abs().unwrap();
+        sys::mkdir(&temp).unwrap();
+        temp
+    }
+
+    // Serve the given `content` for `count` requests honoring simple `Range: bytes=N-` headers or
+    // when not `honor_range` answering them with the full content as a range from the start
+    fn serve(content: &'static [u8], count: usize, honor_range: bool) -> (String, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/file", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut offset = None;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(range) = line.trim().strip_prefix("range: bytes=").or_else(|| line.trim().strip_prefix("Range: bytes=")) {
+                        offset = Some(range.trim_end_matches('-').parse::<usize>().unwrap());
+                    }
+                }
+                let res = match offset {
+                    Some(_) if !honor_range => partial(content, 0),
+                    Some(x) if x >= content.len() => "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Length: 0\r\n\r\n".as_bytes().to_vec(),
+                    Some(x) => partial(content, x),
+                    None => [format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", content.len()).as_bytes(), content].concat(),
+                };
+                stream.write_all(&res).unwrap();
+            }
+        });
+        (url, handle)
+    }
+
+    // Build a partial content response for the given `content` starting at `offset`
+    fn partial(content: &[u8], offset: usize) -> Vec<u8> {
+        let len = content.len();
+        let head = format!("HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\n\r\n", len - offset, offset, len - 1, len);
+        [head.as_bytes(), &content[offset..]].concat()
+    }
+
+    #[test]
+    fn test_download() {
+        let tmpdir = setup().mash("download_download");
+        let dst = tmpdir.mash("dir1/file1");
+        let content = b"hello world, this is a test";
+        let sha256 = "46ee2223f6cde3ecfb4ee25a2da22ed853dddd992486e4ac6b374ad1f97bd34b";
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // full download creating the parent directory
+        let (url, handle) = serve(content, 1, true);
+        assert_eq!(net::download(&url, &dst, net::DownloadOpts::default()).unwrap(), content.len() as u64);
+        assert_eq!(sys::readbytes(&dst).unwrap(), content.to_vec());
+        handle.join().unwrap();
+
+        // resume a partial download and verify the checksum
+        assert!(sys::write(&dst, &content[..5]).is_ok());
+        let (url, handle) = serve(content, 1, true);
+        let opts = net::DownloadOpts { resume: true, expected_sha256: Some(sha256.to_uppercase()) };
+        assert_eq!(net::download(&url, &dst, opts).unwrap(), content.len() as u64);
+        assert_eq!(sys::readbytes(&dst).unwrap(), content.to_vec());
+        handle.join().unwrap();
+
+        // resume an already complete download
+        let (url, handle) = serve(content, 1, true);
+        let opts = net::DownloadOpts { resume: true, expected_sha256: Some(sha256.to_string()) };
+        assert_eq!(net::download(&url, &dst, opts).unwrap(), content.len() as u64);
+        handle.join().unwrap();
+
+        // without resume the partial file is replaced
+        assert!(sys::write(&dst, "foo").is_ok());
+        let (url, handle) = serve(content, 1, true);
+        assert_eq!(net::download(&url, &dst, net::DownloadOpts::default()).unwrap(), content.len() as u64);
+        handle.join().unwrap();
+
+        // resume restarts when the server doesn't resume at the requested offset
+        assert!(sys::write(&dst, &content[..5]).is_ok());
+        let (url, handle) = serve(content, 2, false);
+        let opts = net::DownloadOpts { resume: true, expected_sha256: Some(sha256.to_string()) };
+        assert_eq!(net::download(&url, &dst, opts).unwrap(), content.len() as u64);
+        assert_eq!(sys::readbytes(&dst).unwrap(), content.to_vec());
+        handle.join().unwrap();
+
+        // checksum mismatch
+        let (url, handle) = serve(content, 1, true);
+        let opts = net::DownloadOpts { resume: false, expected_sha256: Some("foo".to_string()) };
+        assert_eq!(net::download(&url, &dst, opts).unwrap_err().downcast_ref::<NetError>(), Some(&NetError::checksum_mismatch("foo", sha256)));
+        handle.join().unwrap();
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_download_errors() {
+        let tmpdir = setup().mash("download_download_errors");
+        let dst = tmpdir.mash("file1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // unresolvable host
+        let url = "http://fungus.invalid/file";
+        assert_eq!(net::download(url, &dst, net::DownloadOpts::default()).unwrap_err().downcast_ref::<NetError>(), Some(&NetError::dns(url)));
+
+        // accept connections but never respond
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/file", listener.local_addr().unwrap());
+        let client = agent::with_config(agent::Config { read_timeout: Duration::from_millis(100), ..Default::default() });
+        assert_eq!(net::download_with(&client, &url, &dst, net::DownloadOpts::default()).unwrap_err().downcast_ref::<NetError>(), Some(&NetError::timeout(&url)));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_download_live() {
+        let tmpdir = setup().mash("download_download_live");
+        let dst = tmpdir.mash("LICENSE-MIT");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // download then resume the complete file verifying the checksum
+        let url = "https://raw.githubusercontent.com/phR0ze/fungus/master/LICENSE-MIT";
+        let size = net::download(url, &dst, net::DownloadOpts::default()).unwrap();
+        let sha256 = hex::encode(sys::digest_sha256(&dst).unwrap());
+        let opts = net::DownloadOpts { resume: true, expected_sha256: Some(sha256) };
+        assert_eq!(net::download(url, &dst, opts).unwrap(), size);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+}
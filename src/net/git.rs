@@ -0,0 +1,705 @@
+use crate::{
+    core::ToStringExt,
+    errors::*,
+    net::agent,
+    sys::{self, PathExt},
+    FuResult,
+};
+use std::{
+    cmp::Ordering,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// CloneOpts provides options for `clone`
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CloneOpts {
+    pub branch: Option<String>,   // branch to checkout rather than the remote's HEAD
+    pub depth: Option<u32>,       // truncate the history to the given number of commits
+    pub recurse_submodules: bool, // initialize and clone submodules as well
+}
+
+/// PullOpts provides options for `pull`
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PullOpts {
+    pub force: bool, // discard local changes and reset to the upstream branch
+}
+
+/// PullResult reports the outcome of a `pull`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PullResult {
+    pub updated: bool, // working tree was changed by the pull
+    pub head: String,  // sha of HEAD after the pull
+}
+
+/// RefKind identifies the kind of ref listed by `ls_remote`
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RefKind {
+    Branch, // a branch under refs/heads
+    Head,   // the remote's HEAD
+    Other,  // any other ref e.g. refs/pull or refs/notes
+    Tag,    // a tag under refs/tags
+}
+
+/// RemoteRef describes a ref listed by `ls_remote`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RemoteRef {
+    pub name: String, // short name for branches and tags e.g. `main` else the full ref name
+    pub sha: String,  // commit the ref points to with annotated tags peeled
+    pub kind: RefKind,
+}
+
+/// SemverIdent is a run of digits or non digits within a semantic version pre-release identifier.
+/// Numeric runs order before text and compare as numbers.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum SemverIdent {
+    Num(u64),     // run of digits e.g. `10` in `rc10`
+    Text(String), // run of non digits e.g. `rc` in `rc10`
+}
+
+/// SemverKey sorts semantic versions as major, minor, patch, is release and pre-release identifiers
+type SemverKey = (u64, u64, u64, bool, Vec<Vec<SemverIdent>>);
+
+/// Check out the given `refname` in the given `repo` where `refname` may be a branch, tag or
+/// commit sha. Branches that only exist on a remote are created locally tracking the remote.
+/// Handles path expansion and errors with a `GitError::RefNotFound` if the ref doesn't exist or
+/// a `GitError::DirtyWorktree` if tracked files have local changes that would be lost.
+///
+/// ### Examples
+/// ```ignore
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("git_doc_checkout");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(git::clone("https://github.com/phR0ze/fungus", &tmpdir, git::CloneOpts::default()).is_ok());
+/// let tag = git::tags(&tmpdir).unwrap().pop().unwrap();
+/// assert!(git::checkout(&tmpdir, &tag).is_ok());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn checkout<T: AsRef<Path>>(repo: T, refname: &str) -> FuResult<()> {
+    let repo = repo.as_ref().abs()?;
+    git(Some(&repo), &["rev-parse", "--git-dir"])?;
+
+    // Resolve the ref locally falling back on a remote branch of the same name
+    let exists = !refname.starts_with('-')
+        && (git(Some(&repo), &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", refname)]).is_ok()
+            || !git(Some(&repo), &["for-each-ref", "--format=%(refname)", &format!("refs/remotes/*/{}", refname)])?.is_empty());
+    if !exists {
+        return Err(GitError::ref_not_found(refname).into());
+    }
+
+    if !git(Some(&repo), &["status", "--porcelain", "--untracked-files=no"])?.is_empty() {
+        return Err(GitError::dirty_worktree(&repo).into());
+    }
+    git(Some(&repo), &["checkout", "--quiet", refname, "--"])?;
+    Ok(())
+}
+
+/// Clone the repo at the given `url` into the given `dst` directory using the given `opts`.
+/// Handles path expansion and errors if `dst` exists and is not an empty directory or if the
+/// requested branch doesn't exist on the remote.
+///
+/// ### Examples
+/// ```ignore
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("git_doc_clone");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let opts = git::CloneOpts { depth: Some(1), ..Default::default() };
+/// assert!(git::clone("https://github.com/phR0ze/fungus", &tmpdir, opts).is_ok());
+/// assert_eq!(tmpdir.mash(".git").is_dir(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn clone<T: AsRef<Path>>(url: &str, dst: T, opts: CloneOpts) -> FuResult<()> {
+    let dst = dst.as_ref().abs()?;
+    if dst.exists() && (!dst.is_dir() || fs::read_dir(&dst)?.next().is_some()) {
+        return Err(GitError::destination_not_empty(&dst).into());
+    }
+    if let Some(ref branch) = opts.branch {
+        if !remote_branch_exists(url, branch)? {
+            return Err(GitError::branch_not_found(branch).into());
+        }
+    }
+
+    // Build the clone arguments from the options
+    let mut args = vec!["clone".to_string(), "--quiet".to_string()];
+    if let Some(ref branch) = opts.branch {
+        args.push(format!("--branch={}", branch));
+    }
+    if let Some(depth) = opts.depth {
+        args.push(format!("--depth={}", depth));
+    }
+    if opts.recurse_submodules {
+        args.push("--recurse-submodules".to_string());
+    }
+    args.push(url.to_string());
+    args.push(dst.to_string()?);
+
+    sys::mkdir(dst.dir()?)?;
+    git(None, &args)?;
+    Ok(())
+}
+
+/// Returns the name of the branch currently checked out in the given `repo`. Handles path
+/// expansion and errors with `GitError::DetachedHead` if HEAD isn't on a branch.
+///
+/// ### Examples
+/// ```ignore
+/// use fungus::prelude::*;
+///
+/// assert_eq!(git::current_branch(".").unwrap(), "main");
+/// ```
+pub fn current_branch<T: AsRef<Path>>(repo: T) -> FuResult<String> {
+    let repo = repo.as_ref().abs()?;
+    let branch = git(Some(&repo), &["rev-parse", "--abbrev-ref", "HEAD"])?;
+    if branch == "HEAD" {
+        return Err(GitError::detached_head(&repo).into());
+    }
+    Ok(branch)
+}
+
+/// Returns true if the given `path` is within a git repo. Wraps `root` so the same upward search
+/// applies.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(git::is_repo("/proc"), false);
+/// ```
+pub fn is_repo<T: AsRef<Path>>(path: T) -> bool {
+    root(path).is_ok()
+}
+
+/// Returns all refs of the remote repo at the given `url` i.e. HEAD, branches and tags in the
+/// order reported by the remote. Annotated tags are peeled to the commit they point to.
+///
+/// ### Examples
+/// ```ignore
+/// use fungus::prelude::*;
+///
+/// let refs = git::ls_remote("https://github.com/phR0ze/fungus").unwrap();
+/// assert!(refs.iter().any(|x| x.kind == git::RefKind::Branch && x.name == "master"));
+/// ```
+pub fn ls_remote(url: &str) -> FuResult<Vec<RemoteRef>> {
+    let mut refs: Vec<RemoteRef> = Vec::new();
+    for line in git(None, &["ls-remote", url])?.lines() {
+        let (sha, name) = match line.split_once('\t') {
+            Some((sha, name)) => (sha.to_string(), name),
+            None => continue,
+        };
+
+        // Peeled annotated tags follow their tag so update the tag to point to the commit
+        if let Some(name) = name.strip_prefix("refs/tags/").and_then(|x| x.strip_suffix("^{}")) {
+            if let Some(tag) = refs.iter_mut().rev().find(|x| x.kind == RefKind::Tag && x.name == name) {
+                tag.sha = sha;
+            }
+            continue;
+        }
+
+        let (name, kind) = if name == "HEAD" {
+            (name, RefKind::Head)
+        } else if let Some(branch) = name.strip_prefix("refs/heads/") {
+            (branch, RefKind::Branch)
+        } else if let Some(tag) = name.strip_prefix("refs/tags/") {
+            (tag, RefKind::Tag)
+        } else {
+            (name, RefKind::Other)
+        };
+        refs.push(RemoteRef { name: name.to_string(), sha, kind });
+    }
+    Ok(refs)
+}
+
+/// Update the existing clone at the given `repo` by fetching and fast forwarding its current
+/// branch to its upstream branch. Handles path expansion and errors with a
+/// `GitError::DirtyWorktree` if tracked files have local changes that would be overwritten,
+/// unless `opts.force` is set in which case they are discarded along with any local commits.
+///
+/// ### Examples
+/// ```ignore
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("git_doc_pull");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(git::clone("https://github.com/phR0ze/fungus", &tmpdir, git::CloneOpts::default()).is_ok());
+/// assert_eq!(git::pull(&tmpdir, git::PullOpts::default()).unwrap().updated, false);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn pull<T: AsRef<Path>>(repo: T, opts: PullOpts) -> FuResult<PullResult> {
+    let repo = repo.as_ref().abs()?;
+    current_branch(&repo)?;
+    let dirty = !git(Some(&repo), &["status", "--porcelain", "--untracked-files=no"])?.is_empty();
+    if dirty && !opts.force {
+        return Err(GitError::dirty_worktree(&repo).into());
+    }
+
+    let before = git(Some(&repo), &["rev-parse", "HEAD"])?;
+    git(Some(&repo), &["fetch", "--quiet"])?;
+    match opts.force {
+        true => git(Some(&repo), &["reset", "--hard", "--quiet", "@{upstream}"])?,
+        false => git(Some(&repo), &["merge", "--ff-only", "--quiet", "@{upstream}"])?,
+    };
+    let head = git(Some(&repo), &["rev-parse", "HEAD"])?;
+    Ok(PullResult { updated: dirty || head != before, head })
+}
+
+/// Returns true if the given `branch` exists on the remote repo at the given `url`.
+///
+/// ### Examples
+/// ```ignore
+/// use fungus::prelude::*;
+///
+/// assert_eq!(git::remote_branch_exists("https://github.com/phR0ze/fungus", "main").unwrap(), true);
+/// ```
+pub fn remote_branch_exists(url: &str, branch: &str) -> FuResult<bool> {
+    Ok(ls_remote(url)?.iter().any(|x| x.kind == RefKind::Branch && x.name == branch))
+}
+
+/// Returns the root of the git repo containing the given `start` path by walking upward until a
+/// directory holding a `.git` entry is found. A `.git` file is accepted as well to support linked
+/// worktrees and submodules. Handles path expansion and errors with a `GitError::NotARepo` if the
+/// filesystem root is reached without finding one.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("git_doc_root");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(tmpdir.mash(".git")).is_ok());
+/// assert!(sys::mkdir(tmpdir.mash("foo/bar")).is_ok());
+/// assert_eq!(git::root(tmpdir.mash("foo/bar")).unwrap(), tmpdir);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn root<T: AsRef<Path>>(start: T) -> FuResult<PathBuf> {
+    let start = start.as_ref().abs()?;
+    match start.ancestors().find(|x| x.mash(".git").exists()) {
+        Some(root) => Ok(root.to_path_buf()),
+        None => Err(GitError::not_a_repo(&start).into()),
+    }
+}
+
+/// Returns the tag names of the given `repo`. Tags parseable as semantic versions, optionally
+/// prefixed with a `v`, are sorted by version first followed by the remaining tags sorted
+/// lexically. Handles path expansion.
+///
+/// ### Examples
+/// ```ignore
+/// use fungus::prelude::*;
+///
+/// assert!(git::tags(".").unwrap().len() > 0);
+/// ```
+pub fn tags<T: AsRef<Path>>(repo: T) -> FuResult<Vec<String>> {
+    let repo = repo.as_ref().abs()?;
+    let mut tags: Vec<String> = git(Some(&repo), &["tag", "--list"])?.lines().map(|x| x.to_string()).collect();
+    tags.sort_by(|x, y| match (semver(x), semver(y)) {
+        (Some(a), Some(b)) => a.cmp(&b).then_with(|| x.cmp(y)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => x.cmp(y),
+    });
+    Ok(tags)
+}
+
+/// Private implementation to run the git command line with the given `args`, optionally from
+/// within the given `repo`, returning its trimmed stdout or its stderr as a `GitError`.
+fn git<T: AsRef<str>>(repo: Option<&Path>, args: &[T]) -> FuResult<String> {
+    let mut cmd = Command::new("git");
+    if let Some(repo) = repo {
+        cmd.arg("-C").arg(repo);
+    }
+
+    // Identify ourselves and abort stalled transfers consistent with the default http client
+    let cfg = agent::Config::default();
+    cmd.arg("-c").arg(format!("http.userAgent={}", cfg.user_agent));
+    cmd.arg("-c").arg("http.lowSpeedLimit=1");
+    cmd.arg("-c").arg(format!("http.lowSpeedTime={}", cfg.read_timeout.as_secs()));
+    cmd.args(args.iter().map(|x| x.as_ref()));
+
+    // Never block waiting on credentials
+    cmd.env("GIT_TERMINAL_PROMPT", "0");
+
+    let output = cmd.output()?;
+    if !output.status.success() {
+        return Err(GitError::command_failed(String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
+
+/// Private implementation to parse the given `tag` as a semantic version sort key with
+/// pre-release versions ordered before their release. Pre-release identifiers are split on `.`
+/// and compared in order with numeric identifiers compared as numbers and ordered before
+/// alphanumeric ones, which compare by their runs of digits and text e.g. `rc2` before `rc10`.
+fn semver(tag: &str) -> Option<SemverKey> {
+    let tag = tag.strip_prefix('v').unwrap_or(tag);
+    let (version, pre) = match tag.find('-') {
+        Some(i) => (&tag[..i], Some(&tag[i + 1..])),
+        None => (tag, None),
+    };
+    let mut parts = version.split('.').map(|x| x.parse::<u64>());
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch)), None) => {
+            let pre = match pre {
+                Some(x) => x.split('.').map(semver_ident).collect(),
+                None => vec![],
+            };
+            Some((major, minor, patch, pre.is_empty(), pre))
+        },
+        _ => None,
+    }
+}
+
+/// Private implementation to split the given pre-release identifier into its runs of digits and
+/// text for comparison.
+fn semver_ident(ident: &str) -> Vec<SemverIdent> {
+    let mut runs = vec![];
+    let mut chars = ident.chars().peekable();
+    while let Some(c) = chars.peek().copied() {
+        let mut run = String::new();
+        while let Some(x) = chars.next_if(|x| x.is_ascii_digit() == c.is_ascii_digit()) {
+            run.push(x);
+        }
+        runs.push(match run.parse::<u64>() {
+            Ok(x) if c.is_ascii_digit() => SemverIdent::Num(x),
+            _ => SemverIdent::Text(run),
+        });
+    }
+    runs
+}
+
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    // Test setup
+    fn setup<T: AsRef<Path>>(path: T) -> PathBuf {
+        let temp = PathBuf::from("tests/temp").abs().unwrap();
+        sys::mkdir(&temp).unwrap();
+        temp.mash(path.as_ref())
+    }
+
+    // Create a fixture repo with a single commit on master and a dev branch
+    fn fixture<T: AsRef<Path>>(repo: T) {
+        let repo = repo.as_ref();
+        assert!(sys::mkdir(repo).is_ok());
+        assert!(super::git(Some(repo), &["-c", "init.defaultBranch=master", "init", "--quiet"]).is_ok());
+        assert!(sys::write(repo.mash("README.md"), "fixture\n").is_ok());
+        assert!(super::git(Some(repo), &["add", "README.md"]).is_ok());
+        assert!(super::git(Some(repo), &["-c", "user.name=fungus", "-c", "user.email=fungus@localhost", "commit", "--quiet", "-m", "init"]).is_ok());
+        assert!(super::git(Some(repo), &["branch", "dev"]).is_ok());
+    }
+
+    #[test]
+    fn test_checkout() {
+        let tmpdir = setup("git_checkout");
+        let repo = tmpdir.mash("repo");
+        let dst = tmpdir.mash("dst");
+        let url = format!("file://{}", repo.display());
+        let head = |repo: &Path| super::git(Some(repo), &["rev-parse", "HEAD"]).unwrap();
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        fixture(&repo);
+        let init = head(&repo);
+        assert!(super::git(Some(&repo), &["tag", "v0.1.0"]).is_ok());
+        assert!(sys::write(repo.mash("README.md"), "update\n").is_ok());
+        assert!(super::git(Some(&repo), &["-c", "user.name=fungus", "-c", "user.email=fungus@localhost", "commit", "--quiet", "-am", "update"]).is_ok());
+        let update = head(&repo);
+        assert!(git::clone(&url, &dst, git::CloneOpts::default()).is_ok());
+
+        // tag
+        assert!(git::checkout(&dst, "v0.1.0").is_ok());
+        assert_eq!(head(&dst), init);
+        assert_eq!(sys::readstring(dst.mash("README.md")).unwrap(), "fixture\n");
+
+        // local branch
+        assert!(git::checkout(&dst, "master").is_ok());
+        assert_eq!(git::current_branch(&dst).unwrap(), "master");
+        assert_eq!(head(&dst), update);
+
+        // branch only on the remote
+        assert!(git::checkout(&dst, "dev").is_ok());
+        assert_eq!(git::current_branch(&dst).unwrap(), "dev");
+        assert_eq!(head(&dst), init);
+
+        // commit sha
+        assert!(git::checkout(&dst, &update[..8]).is_ok());
+        assert_eq!(head(&dst), update);
+
+        // missing refs
+        assert_eq!(git::checkout(&dst, "foo").unwrap_err().downcast_ref::<GitError>(), Some(&GitError::ref_not_found("foo")));
+        assert_eq!(git::checkout(&dst, "--force").unwrap_err().downcast_ref::<GitError>(), Some(&GitError::ref_not_found("--force")));
+
+        // local changes would be lost
+        assert!(sys::write(dst.mash("README.md"), "local\n").is_ok());
+        assert_eq!(git::checkout(&dst, "v0.1.0").unwrap_err().downcast_ref::<GitError>(), Some(&GitError::dirty_worktree(&dst)));
+        assert_eq!(sys::readstring(dst.mash("README.md")).unwrap(), "local\n");
+
+        // not a repo
+        assert!(git::checkout(&tmpdir, "master").unwrap_err().is::<GitError>());
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_checkout_live() {
+        let tmpdir = setup("git_checkout_live");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(git::clone("https://github.com/phR0ze/fungus", &tmpdir, git::CloneOpts::default()).is_ok());
+        let tag = git::tags(&tmpdir).unwrap().pop().unwrap();
+        assert!(git::checkout(&tmpdir, &tag).is_ok());
+        let sha = super::git(Some(&tmpdir), &["rev-parse", &format!("{}^{{commit}}", tag)]).unwrap();
+        assert_eq!(super::git(Some(&tmpdir), &["rev-parse", "HEAD"]).unwrap(), sha);
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_clone() {
+        let tmpdir = setup("git_clone");
+        let repo = tmpdir.mash("repo");
+        let dst1 = tmpdir.mash("dst1");
+        let dst2 = tmpdir.mash("dst2");
+        let url = format!("file://{}", repo.display());
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        fixture(&repo);
+
+        // clone the default branch
+        let opts = git::CloneOpts { depth: Some(1), ..Default::default() };
+        assert!(git::clone(&url, &dst1, opts).is_ok());
+        assert_eq!(dst1.mash(".git").is_dir(), true);
+        assert_eq!(sys::readstring(dst1.mash("README.md")).unwrap(), "fixture\n");
+        assert_eq!(super::git(Some(&dst1), &["rev-parse", "--abbrev-ref", "HEAD"]).unwrap(), "master");
+
+        // destination isn't empty
+        assert_eq!(git::clone(&url, &dst1, git::CloneOpts::default()).unwrap_err().downcast_ref::<GitError>(), Some(&GitError::destination_not_empty(&dst1)));
+
+        // branch doesn't exist
+        let opts = git::CloneOpts { branch: Some("foo".to_string()), ..Default::default() };
+        assert_eq!(git::clone(&url, &dst2, opts).unwrap_err().downcast_ref::<GitError>(), Some(&GitError::branch_not_found("foo")));
+        assert_eq!(dst2.exists(), false);
+
+        // clone a specific branch into an existing empty directory
+        assert!(sys::mkdir(&dst2).is_ok());
+        let opts = git::CloneOpts { branch: Some("dev".to_string()), recurse_submodules: true, ..Default::default() };
+        assert!(git::clone(&url, &dst2, opts).is_ok());
+        assert_eq!(super::git(Some(&dst2), &["rev-parse", "--abbrev-ref", "HEAD"]).unwrap(), "dev");
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_clone_live() {
+        let tmpdir = setup("git_clone_live");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        let opts = git::CloneOpts { depth: Some(1), ..Default::default() };
+        assert!(git::clone("https://github.com/phR0ze/fungus", &tmpdir, opts).is_ok());
+        assert_eq!(tmpdir.mash(".git").is_dir(), true);
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_current_branch() {
+        let tmpdir = setup("git_current_branch");
+        let repo = tmpdir.mash("repo");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        fixture(&repo);
+
+        assert_eq!(git::current_branch(&repo).unwrap(), "master");
+        assert!(super::git(Some(&repo), &["checkout", "--quiet", "dev"]).is_ok());
+        assert_eq!(git::current_branch(&repo).unwrap(), "dev");
+
+        // detached HEAD
+        assert!(super::git(Some(&repo), &["checkout", "--quiet", "--detach"]).is_ok());
+        assert_eq!(git::current_branch(&repo).unwrap_err().downcast_ref::<GitError>(), Some(&GitError::detached_head(&repo)));
+
+        // not a repo
+        assert!(git::current_branch(&tmpdir).unwrap_err().is::<GitError>());
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_ls_remote() {
+        let tmpdir = setup("git_ls_remote");
+        let repo = tmpdir.mash("repo");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        fixture(&repo);
+        let url = repo.to_str().unwrap();
+        let sha = super::git(Some(&repo), &["rev-parse", "HEAD"]).unwrap();
+
+        // lightweight and annotated tags
+        assert!(super::git(Some(&repo), &["tag", "v0.1.0"]).is_ok());
+        assert!(super::git(Some(&repo), &["-c", "user.name=fungus", "-c", "user.email=fungus@localhost", "tag", "-a", "-m", "release", "v0.2.0"]).is_ok());
+        assert!(super::git(Some(&repo), &["update-ref", "refs/notes/foo", "HEAD"]).is_ok());
+
+        let refs = git::ls_remote(url).unwrap();
+        let kinds: Vec<(&str, git::RefKind)> = refs.iter().map(|x| (x.name.as_str(), x.kind)).collect();
+        assert_eq!(kinds, vec![
+            ("HEAD", git::RefKind::Head),
+            ("dev", git::RefKind::Branch),
+            ("master", git::RefKind::Branch),
+            ("refs/notes/foo", git::RefKind::Other),
+            ("v0.1.0", git::RefKind::Tag),
+            ("v0.2.0", git::RefKind::Tag),
+        ]);
+        assert!(refs.iter().all(|x| x.sha == sha));
+
+        // not a repo
+        assert!(git::ls_remote(tmpdir.mash("foo").to_str().unwrap()).unwrap_err().is::<GitError>());
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ls_remote_live() {
+        let refs = git::ls_remote("https://github.com/phR0ze/fungus").unwrap();
+        assert!(refs.iter().any(|x| x.kind == git::RefKind::Head));
+        assert!(refs.iter().any(|x| x.kind == git::RefKind::Branch && (x.name == "master" || x.name == "main")));
+    }
+
+    #[test]
+    fn test_pull() {
+        let tmpdir = setup("git_pull");
+        let repo = tmpdir.mash("repo");
+        let dst = tmpdir.mash("dst");
+        let url = format!("file://{}", repo.display());
+        let commit = |msg: &str| {
+            assert!(super::git(Some(&repo), &["-c", "user.name=fungus", "-c", "user.email=fungus@localhost", "commit", "--quiet", "-am", msg]).is_ok());
+            super::git(Some(&repo), &["rev-parse", "HEAD"]).unwrap()
+        };
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        fixture(&repo);
+        assert!(git::clone(&url, &dst, git::CloneOpts::default()).is_ok());
+
+        // up to date
+        let result = git::pull(&dst, git::PullOpts::default()).unwrap();
+        assert_eq!(result, git::PullResult { updated: false, head: super::git(Some(&repo), &["rev-parse", "HEAD"]).unwrap() });
+
+        // fast forward to upstream changes
+        assert!(sys::write(repo.mash("README.md"), "update 1\n").is_ok());
+        let sha = commit("update 1");
+        assert_eq!(git::pull(&dst, git::PullOpts::default()).unwrap(), git::PullResult { updated: true, head: sha });
+        assert_eq!(sys::readstring(dst.mash("README.md")).unwrap(), "update 1\n");
+
+        // local changes would be overwritten
+        assert!(sys::write(repo.mash("README.md"), "update 2\n").is_ok());
+        let sha = commit("update 2");
+        assert!(sys::write(dst.mash("README.md"), "local\n").is_ok());
+        assert_eq!(git::pull(&dst, git::PullOpts::default()).unwrap_err().downcast_ref::<GitError>(), Some(&GitError::dirty_worktree(&dst)));
+        assert_eq!(sys::readstring(dst.mash("README.md")).unwrap(), "local\n");
+
+        // forced discards local changes
+        assert_eq!(git::pull(&dst, git::PullOpts { force: true }).unwrap(), git::PullResult { updated: true, head: sha.clone() });
+        assert_eq!(sys::readstring(dst.mash("README.md")).unwrap(), "update 2\n");
+
+        // untracked files don't block the pull
+        assert!(sys::write(dst.mash("untracked"), "foo").is_ok());
+        assert_eq!(git::pull(&dst, git::PullOpts::default()).unwrap(), git::PullResult { updated: false, head: sha });
+
+        // detached HEAD and not a repo
+        assert!(super::git(Some(&dst), &["checkout", "--quiet", "--detach"]).is_ok());
+        assert_eq!(git::pull(&dst, git::PullOpts::default()).unwrap_err().downcast_ref::<GitError>(), Some(&GitError::detached_head(&dst)));
+        assert!(git::pull(&tmpdir, git::PullOpts::default()).unwrap_err().is::<GitError>());
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_pull_live() {
+        let tmpdir = setup("git_pull_live");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(git::clone("https://github.com/phR0ze/fungus", &tmpdir, git::CloneOpts::default()).is_ok());
+        let result = git::pull(&tmpdir, git::PullOpts::default()).unwrap();
+        assert_eq!(result.updated, false);
+        assert_eq!(result.head, super::git(Some(&tmpdir), &["rev-parse", "HEAD"]).unwrap());
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_remote_branch_exists() {
+        let tmpdir = setup("git_remote_branch_exists");
+        let repo = tmpdir.mash("repo");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        fixture(&repo);
+
+        assert_eq!(git::remote_branch_exists(repo.to_str().unwrap(), "master").unwrap(), true);
+        assert_eq!(git::remote_branch_exists(repo.to_str().unwrap(), "dev").unwrap(), true);
+        assert_eq!(git::remote_branch_exists(repo.to_str().unwrap(), "foo").unwrap(), false);
+        assert!(git::remote_branch_exists(tmpdir.mash("foo").to_str().unwrap(), "master").unwrap_err().is::<GitError>());
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_root() {
+        let tmpdir = setup("git_root");
+        let repo = tmpdir.mash("repo");
+        let sub = repo.mash("foo/bar");
+        let worktree = tmpdir.mash("worktree");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        fixture(&repo);
+        assert!(sys::mkdir(&sub).is_ok());
+        assert!(sys::touch(sub.mash("file1")).is_ok());
+
+        // from the root, a sub directory and a file
+        assert_eq!(git::root(&repo).unwrap(), repo);
+        assert_eq!(git::root(&sub).unwrap(), repo);
+        assert_eq!(git::root(sub.mash("file1")).unwrap(), repo);
+        assert_eq!(git::is_repo(&repo), true);
+        assert_eq!(git::is_repo(&sub), true);
+
+        // linked worktrees use a .git file
+        assert!(super::git(Some(&repo), &["worktree", "add", "--quiet", &worktree.to_string().unwrap(), "dev"]).is_ok());
+        assert_eq!(worktree.mash(".git").is_file(), true);
+        assert_eq!(git::root(worktree.mash("README.md")).unwrap(), worktree);
+
+        // not a repo
+        assert_eq!(git::root("/proc/self").unwrap_err().downcast_ref::<GitError>(), Some(&GitError::not_a_repo("/proc/self")));
+        assert_eq!(git::is_repo("/proc/self"), false);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_semver() {
+        use super::SemverIdent::{Num, Text};
+        assert_eq!(super::semver("1.2.3"), Some((1, 2, 3, true, vec![])));
+        assert_eq!(super::semver("v10.0.1"), Some((10, 0, 1, true, vec![])));
+        assert_eq!(super::semver("v1.0.0-rc1"), Some((1, 0, 0, false, vec![vec![Text("rc".to_string()), Num(1)]])));
+        assert_eq!(super::semver("v1.0.0-rc.1"), Some((1, 0, 0, false, vec![vec![Text("rc".to_string())], vec![Num(1)]])));
+
+        // pre-release ordering
+        let ordered = vec![
+            "1.0.0-1", "1.0.0-2", "1.0.0-10", "1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta", "1.0.0-beta.2", "1.0.0-beta.11", "1.0.0-rc2",
+            "1.0.0-rc10", "1.0.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(super::semver(pair[0]) < super::semver(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(super::semver("1.2"), None);
+        assert_eq!(super::semver("1.2.3.4"), None);
+        assert_eq!(super::semver("release"), None);
+    }
+
+    #[test]
+    fn test_tags() {
+        let tmpdir = setup("git_tags");
+        let repo = tmpdir.mash("repo");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        fixture(&repo);
+
+        assert_eq!(git::tags(&repo).unwrap(), Vec::<String>::new());
+        for tag in &["v0.10.0", "v0.9.1", "beta", "v0.10.0-rc10", "v0.10.0-rc2", "0.2.0", "alpha", "v1.0"] {
+            assert!(super::git(Some(&repo), &["tag", tag]).is_ok());
+        }
+        assert_eq!(git::tags(&repo).unwrap(), vec!["0.2.0", "v0.9.1", "v0.10.0-rc2", "v0.10.0-rc10", "v0.10.0", "alpha", "beta", "v1.0"]);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+}
@@ -1 +1,10 @@
+mod download;
+mod probe;
+
+// Export contents of modules into net
+pub use download::*;
+pub use probe::*;
+
+// Export modules directly
 pub mod agent;
+pub mod git;
@@ -0,0 +1,93 @@
+use crate::errors::*;
+use std::{
+    net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+/// Returns true if a TCP connection to the given `host` and `port` can be established within the
+/// given `timeout`. The `host` may be a name or an ip address and each resolved address is tried
+/// in turn with the `timeout` applying to each attempt. Resolution failures are treated as
+/// unreachable.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+/// use std::{net::TcpListener, time::Duration};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let port = listener.local_addr().unwrap().port();
+/// assert_eq!(net::is_reachable("127.0.0.1", port, Duration::from_secs(1)), true);
+/// ```
+pub fn is_reachable(host: &str, port: u16, timeout: Duration) -> bool {
+    match resolve(host) {
+        Ok(ips) => ips.into_iter().any(|ip| TcpStream::connect_timeout(&SocketAddr::new(ip, port), timeout).is_ok()),
+        Err(_) => false,
+    }
+}
+
+/// Resolve the given `host` name to its distinct ip addresses in the order returned by the system
+/// resolver. Ip addresses are returned as is and may be given in brackets e.g. `[::1]`. Returns a
+/// `NetError::Dns` when the host can't be resolved.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(net::resolve("127.0.0.1").unwrap(), vec![std::net::IpAddr::from([127, 0, 0, 1])]);
+/// ```
+pub fn resolve(host: &str) -> FuResult<Vec<IpAddr>> {
+    let name = host.trim_start_matches('[').trim_end_matches(']');
+    let mut ips: Vec<IpAddr> = Vec::new();
+    for addr in (name, 0).to_socket_addrs().map_err(|_| NetError::dns(host))? {
+        if !ips.contains(&addr.ip()) {
+            ips.push(addr.ip());
+        }
+    }
+    if ips.is_empty() {
+        return Err(NetError::dns(host).into());
+    }
+    Ok(ips)
+}
+
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use std::{
+        net::{IpAddr, TcpListener},
+        time::Duration,
+    };
+
+    #[test]
+    fn test_is_reachable() {
+        // listening port
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert_eq!(net::is_reachable("127.0.0.1", port, Duration::from_secs(1)), true);
+        assert_eq!(net::is_reachable("localhost", port, Duration::from_secs(1)), true);
+
+        // closed port
+        drop(listener);
+        assert_eq!(net::is_reachable("127.0.0.1", port, Duration::from_secs(1)), false);
+
+        // unresolvable host
+        assert_eq!(net::is_reachable("fungus.invalid", 80, Duration::from_secs(1)), false);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_is_reachable_live() {
+        assert_eq!(net::is_reachable("github.com", 443, Duration::from_secs(5)), true);
+        assert!(!net::resolve("github.com").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(net::resolve("127.0.0.1").unwrap(), vec![IpAddr::from([127, 0, 0, 1])]);
+        assert_eq!(net::resolve("[::1]").unwrap(), vec![IpAddr::from([0, 0, 0, 0, 0, 0, 0, 1])]);
+        assert!(net::resolve("localhost").unwrap().iter().all(|x| x.is_loopback()));
+        assert_eq!(net::resolve("fungus.invalid").unwrap_err().downcast_ref::<NetError>(), Some(&NetError::dns("fungus.invalid")));
+        assert_eq!(net::resolve("").unwrap_err().downcast_ref::<NetError>(), Some(&NetError::dns("")));
+    }
+}
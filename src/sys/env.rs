@@ -1,11 +1,29 @@
-use crate::{errors::*, sys::PathExt};
+use crate::{
+    errors::*,
+    sys::{self, user, PathExt},
+};
 use std::{
     env,
-    ffi::OsStr,
+    ffi::{CString, OsStr, OsString},
     io,
+    os::unix::ffi::OsStrExt,
     path::{Path, PathBuf},
 };
 
+/// Returns the current `PATH` with the given `dir` appended as the last entry, suitable for
+/// `sys::set_var("PATH", ...)`. Existing entries are deduplicated so that `dir` isn't added twice.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let path = sys::append_path("/foo/bar").unwrap();
+/// assert_eq!(sys::parse_paths(path.to_str().unwrap()).unwrap().last(), Some(&PathBuf::from("/foo/bar")));
+/// ```
+pub fn append_path<T: AsRef<Path>>(dir: T) -> FuResult<OsString> {
+    modify_path(dir.as_ref(), false)
+}
+
 /// Returns the arguments that this program was started with (normally passed
 /// via the command line).
 /// Wraps std::env::args
@@ -182,6 +200,20 @@ pub fn hastty() -> bool {
     unsafe { libc::isatty(libc::STDOUT_FILENO) != 0 }
 }
 
+/// Returns the current `PATH` with the given `dir` prepended as the first entry, suitable for
+/// `sys::set_var("PATH", ...)`. Existing entries are deduplicated so that `dir` isn't added twice.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let path = sys::prepend_path("/foo/bar").unwrap();
+/// assert_eq!(sys::parse_paths(path.to_str().unwrap()).unwrap().first(), Some(&PathBuf::from("/foo/bar")));
+/// ```
+pub fn prepend_path<T: AsRef<Path>>(dir: T) -> FuResult<OsString> {
+    modify_path(dir.as_ref(), true)
+}
+
 /// Changes the current working directory to the specified path.
 /// Provides basic path expansion
 ///
@@ -317,12 +349,148 @@ pub fn vars() -> env::Vars {
     env::vars()
 }
 
+/// Returns the full path of the first executable file named `name` in the `PATH` directories.
+/// The file must have the execute bit set for the current user. When `name` contains a path
+/// separator it is simply expanded and verified to be executable.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(sys::which("sh").unwrap().is_exec());
+/// assert!(sys::which("bogus-exec-name").is_err());
+/// ```
+pub fn which<T: AsRef<str>>(name: T) -> FuResult<PathBuf> {
+    let name = name.as_ref();
+    if name.contains('/') {
+        let path = Path::new(name).abs()?;
+        return match executable(&path) {
+            true => Ok(path),
+            false if path.exists() => Err(PathError::is_not_exec(path).into()),
+            false => Err(PathError::does_not_exist(path).into()),
+        };
+    }
+    match which_all(name)?.into_iter().next() {
+        Some(path) => Ok(path),
+        None => Err(PathError::does_not_exist(name).into()),
+    }
+}
+
+/// Returns the full paths of all executable files named `name` in the `PATH` directories in
+/// `PATH` order. Non-existent directories and directories named `name` are skipped.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(sys::which_all("sh").unwrap().len() > 0);
+/// ```
+pub fn which_all<T: AsRef<str>>(name: T) -> FuResult<Vec<PathBuf>> {
+    let name = name.as_ref();
+    let mut paths = vec![];
+    if name.is_empty() {
+        return Ok(paths);
+    }
+    for dir in user::path_dirs()? {
+        let path = dir.join(name);
+        if executable(&path) && !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+/// Private implementation to check that the given `path` is a file executable by the current user
+fn executable(path: &Path) -> bool {
+    if !path.is_file() {
+        return false;
+    }
+    match CString::new(path.as_os_str().as_bytes()) {
+        Ok(x) => unsafe { libc::access(x.as_ptr(), libc::X_OK) == 0 },
+        Err(_) => false,
+    }
+}
+
+/// Private implementation to add the given `dir` to the current `PATH` while deduplicating
+fn modify_path(dir: &Path, prepend: bool) -> FuResult<OsString> {
+    let mut paths = vec![];
+    if env::var_os("PATH").is_some() {
+        for path in user::path_dirs()? {
+            if path != dir && !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    match prepend {
+        true => paths.insert(0, dir.to_path_buf()),
+        false => paths.push(dir.to_path_buf()),
+    }
+    sys::join_paths(paths)
+}
+
 // Unit tests
 // -------------------------------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
 
+    #[test]
+    fn test_append_prepend_path() {
+        let dirs = user::path_dirs().unwrap();
+        let existing = dirs.first().unwrap().clone();
+
+        // prepend places the dir first
+        let paths = sys::parse_paths(sys::prepend_path("/foo/bar").unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(paths.first(), Some(&PathBuf::from("/foo/bar")));
+
+        // append places the dir last
+        let paths = sys::parse_paths(sys::append_path("/foo/bar").unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(paths.last(), Some(&PathBuf::from("/foo/bar")));
+
+        // duplicates are removed
+        let paths = sys::parse_paths(sys::append_path(&existing).unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(paths.last(), Some(&existing));
+        assert_eq!(paths.iter().filter(|x| *x == &existing).count(), 1);
+        let paths = sys::parse_paths(sys::prepend_path(&existing).unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(paths.first(), Some(&existing));
+        assert_eq!(paths.iter().filter(|x| *x == &existing).count(), 1);
+        let mut unique = paths.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), paths.len());
+
+        // separator rejection
+        assert!(sys::prepend_path("/foo:bar").is_err());
+    }
+
+    #[test]
+    fn test_which() {
+        let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("env_which");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::touch_p(&file1, 0o755).is_ok());
+        assert!(sys::touch_p(&file2, 0o644).is_ok());
+
+        // lookup by name
+        let sh = sys::which("sh").unwrap();
+        assert_eq!(sh.is_absolute(), true);
+        assert_eq!(sh.base().unwrap(), "sh");
+        assert_eq!(sys::which_all("sh").unwrap().first(), Some(&sh));
+        assert!(sys::which("bogus-exec-name").is_err());
+        assert_eq!(sys::which_all("bogus-exec-name").unwrap(), Vec::<PathBuf>::new());
+        assert_eq!(sys::which_all("").unwrap(), Vec::<PathBuf>::new());
+
+        // lookup by path
+        assert_eq!(sys::which(file1.to_str().unwrap()).unwrap(), file1);
+        assert_eq!(sys::which(file2.to_str().unwrap()).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::is_not_exec(&file2)));
+        assert_eq!(sys::which(tmpdir.to_str().unwrap()).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::is_not_exec(&tmpdir)));
+        assert_eq!(sys::which(tmpdir.mash("bogus").to_str().unwrap()).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(tmpdir.mash("bogus"))));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_tty() {
         assert!(sys::hastty() || !sys::hastty());
@@ -1,10 +1,40 @@
 use crate::{
     core::ToStringExt,
     errors::*,
-    sys::{self, user, PathExt},
+    sys::{self, PathExt},
     FuResult,
 };
-use std::path::{Path, PathBuf};
+use std::{
+    io::{BufRead, BufReader, Read},
+    os::unix::process::ExitStatusExt,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+/// ExecLine provides a single line of output from a running command
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExecLine {
+    Stdout(String), // line read from stdout without the trailing newline
+    Stderr(String), // line read from stderr without the trailing newline
+}
+
+/// Output provides the captured results of running a command
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Output {
+    pub status: i32,    // exit code or 128 + signal number if terminated by a signal
+    pub stdout: String, // captured stdout
+    pub stderr: String, // captured stderr
+}
+
+/// Private implementation to convert the exit status into a shell style exit code
+fn code(status: ExitStatus) -> i32 {
+    match status.code() {
+        Some(code) => code,
+        None => 128 + status.signal().unwrap_or(0),
+    }
+}
 
 /// Returns the full path to the directory of the current running executable.
 ///
@@ -24,35 +54,26 @@ pub fn exists<T: AsRef<Path>>(target: T) -> bool {
     lookup(target).is_ok()
 }
 
+/// Private implementation to forward lines from the given `reader` to the channel `tx` wrapped
+/// with the given `kind` on a separate thread.
+fn forward<R, F>(reader: R, tx: Sender<ExecLine>, kind: F) -> thread::JoinHandle<()>
+where
+    R: Read+Send+'static,
+    F: Fn(String) -> ExecLine+Send+'static,
+{
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            if tx.send(kind(line)).is_err() {
+                break;
+            }
+        }
+    })
+}
+
 /// Returns the full path of the given executable. Uses given path if resolvable and falls back on
-/// the system `PATH` if simply an exec name.
-/// ```
+/// the system `PATH` if simply an exec name. Wraps `sys::which`.
 pub fn lookup<T: AsRef<Path>>(target: T) -> FuResult<PathBuf> {
-    let path = target.as_ref();
-    match path.has("/") {
-        // Target is a path
-        true => {
-            let path = path.abs()?;
-            if !path.exists() {
-                return Err(PathError::does_not_exist(path).into());
-            } else if path.is_dir() || !path.is_exec() {
-                return Err(PathError::is_not_exec(path).into());
-            }
-            Ok(path)
-        },
-
-        // Target is a name
-        false => {
-            let base = path.to_string()?;
-            for dir in user::path_dirs()? {
-                let path = sys::mash(dir, &base);
-                if !path.is_dir() && path.is_exec() {
-                    return Ok(path);
-                }
-            }
-            Err(PathError::does_not_exist(target).into())
-        },
-    }
+    sys::which(target.as_ref().to_string()?)
 }
 
 /// Returns the current running executable's name.
@@ -68,6 +89,77 @@ pub fn name() -> FuResult<String> {
     Ok(sys::exe()?.base()?)
 }
 
+/// Run the given command `cmd` with the given `args` capturing its exit code, stdout and stderr.
+/// A non-zero exit code is not considered an error, see `run_ok` for that.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let output = exec::run("echo", &["hello"]).unwrap();
+/// assert_eq!(output.status, 0);
+/// assert_eq!(output.stdout, "hello\n");
+/// ```
+pub fn run(cmd: &str, args: &[&str]) -> FuResult<Output> {
+    let output = Command::new(cmd).args(args).output()?;
+    Ok(Output {
+        status: code(output.status),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    })
+}
+
+/// Wraps `run` returning an `ExecError::NonZeroExit` carrying the exit code and stderr when the
+/// command fails.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(exec::run_ok("true", &[]).is_ok());
+/// assert!(exec::run_ok("false", &[]).is_err());
+/// ```
+pub fn run_ok(cmd: &str, args: &[&str]) -> FuResult<()> {
+    let output = run(cmd, args)?;
+    if output.status != 0 {
+        return Err(ExecError::non_zero_exit(output.status, output.stderr.trim_end()).into());
+    }
+    Ok(())
+}
+
+/// Run the given command `cmd` with the given `args` invoking `on_line` for each line of stdout
+/// and stderr as it arrives rather than buffering the output. Both streams are read concurrently
+/// so neither blocks the other. Returns the exit code of the command.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let mut lines = vec![];
+/// assert_eq!(exec::run_stream("echo", &["hello"], |x| lines.push(x)).unwrap(), 0);
+/// assert_eq!(lines, vec![exec::ExecLine::Stdout("hello".to_string())]);
+/// ```
+pub fn run_stream<F: FnMut(ExecLine)>(cmd: &str, args: &[&str], mut on_line: F) -> FuResult<i32> {
+    let mut child = Command::new(cmd).args(args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+    // Read both streams on their own threads and deliver the lines on this thread
+    let (tx, rx) = mpsc::channel();
+    let mut handles = vec![];
+    if let Some(stdout) = child.stdout.take() {
+        handles.push(forward(stdout, tx.clone(), ExecLine::Stdout));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        handles.push(forward(stderr, tx, ExecLine::Stderr));
+    }
+    for line in rx {
+        on_line(line);
+    }
+    for handle in handles {
+        let _ = handle.join();
+    }
+    Ok(code(child.wait()?))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
@@ -102,6 +194,56 @@ mod tests {
     //     assert!(sys::remove_all(&tmpdir).is_ok());
     // }
 
+    #[test]
+    fn test_run() {
+        // capture stdout
+        let output = exec::run("echo", &["hello"]).unwrap();
+        assert_eq!(output, exec::Output { status: 0, stdout: "hello\n".to_string(), stderr: "".to_string() });
+
+        // capture stderr and exit code
+        let output = exec::run("sh", &["-c", "echo foo >&2; exit 3"]).unwrap();
+        assert_eq!(output, exec::Output { status: 3, stdout: "".to_string(), stderr: "foo\n".to_string() });
+
+        // terminated by signal
+        assert_eq!(exec::run("sh", &["-c", "kill -9 $$"]).unwrap().status, 137);
+
+        // doesn't exist
+        assert!(exec::run("bogus-exec-name", &[]).is_err());
+    }
+
+    #[test]
+    fn test_run_ok() {
+        assert!(exec::run_ok("echo", &["hello"]).is_ok());
+        assert_eq!(exec::run_ok("sh", &["-c", "echo foo >&2; exit 3"]).unwrap_err().downcast_ref::<ExecError>(), Some(&ExecError::non_zero_exit(3, "foo")));
+        assert!(exec::run_ok("bogus-exec-name", &[]).is_err());
+    }
+
+    #[test]
+    fn test_run_stream() {
+        let script = "echo out1; sleep 0.1; echo err1 >&2; sleep 0.1; echo out2; sleep 0.1; echo err2 >&2; exit 2";
+        let mut lines = vec![];
+        assert_eq!(exec::run_stream("sh", &["-c", script], |x| lines.push(x)).unwrap(), 2);
+        assert_eq!(lines, vec![
+            exec::ExecLine::Stdout("out1".to_string()),
+            exec::ExecLine::Stderr("err1".to_string()),
+            exec::ExecLine::Stdout("out2".to_string()),
+            exec::ExecLine::Stderr("err2".to_string()),
+        ]);
+
+        // large output on both streams doesn't block
+        let script = "seq 1 20000; seq 1 20000 >&2";
+        let (mut stdout, mut stderr) = (0, 0);
+        let status = exec::run_stream("sh", &["-c", script], |x| match x {
+            exec::ExecLine::Stdout(_) => stdout += 1,
+            exec::ExecLine::Stderr(_) => stderr += 1,
+        });
+        assert_eq!(status.unwrap(), 0);
+        assert_eq!((stdout, stderr), (20000, 20000));
+
+        // doesn't exist
+        assert!(exec::run_stream("bogus-exec-name", &[], |_| {}).is_err());
+    }
+
     #[test]
     fn test_name() {
         let exec_path = sys::exe().unwrap();
@@ -1,16 +1,26 @@
 use crate::{
+    core::*,
     errors::*,
-    sys::{self, PathExt},
+    sys::{self, group, user, PathExt},
+    unit::time,
     FuResult,
 };
 use blake2::{Blake2b, Digest};
 use regex::Regex;
+use sha2::{Sha256, Sha512};
 use std::{
     ffi::CString,
     fs::{self, File},
     io::{self, prelude::*, BufRead, BufReader},
-    os::unix::{self, ffi::OsStrExt, fs::PermissionsExt},
+    iter,
+    ops::{Deref, DerefMut},
+    os::unix::{
+        self,
+        ffi::OsStrExt,
+        fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt},
+    },
     path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
 };
 use walkdir::WalkDir;
 
@@ -153,6 +163,145 @@ impl Chmod {
     }
 }
 
+/// Append `[u8]` data to a file which means `str` or `String`, creating the file and any missing
+/// parent directories as needed. Handles path expansion. Returns the number of bytes written.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_append");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert_eq!(sys::append(&tmpfile, "foo").unwrap(), 3);
+/// assert_eq!(sys::append(&tmpfile, "bar").unwrap(), 3);
+/// assert_eq!(sys::readstring(&tmpfile).unwrap(), "foobar");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn append<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, data: U) -> FuResult<usize> {
+    let path = path.as_ref().abs()?;
+    mkdir(path.dir()?)?;
+    let mut f = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    f.write_all(data.as_ref())?;
+    f.sync_all()?;
+    Ok(data.as_ref().len())
+}
+
+/// Wraps `append` to add the given `line` to the end of the file only if no existing line matches
+/// it exactly, ignoring line endings. Creates the file and any missing parent directories as
+/// needed. A newline is added first when the file doesn't already end with one. Returns true if
+/// the line was added.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_append_line_if_missing");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert_eq!(sys::append_line_if_missing(&tmpfile, "foo=bar").unwrap(), true);
+/// assert_eq!(sys::append_line_if_missing(&tmpfile, "foo=bar").unwrap(), false);
+/// assert_eq!(sys::readstring(&tmpfile).unwrap(), "foo=bar\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn append_line_if_missing<T: AsRef<Path>>(path: T, line: &str) -> FuResult<bool> {
+    let path = path.as_ref().abs()?;
+    let mut data = String::new();
+    if path.exists() {
+        let current = readstring(&path)?;
+        if current.lines().any(|x| x == line) {
+            return Ok(false);
+        }
+        if !current.is_empty() && !current.ends_with('\n') {
+            data.push('\n');
+        }
+    }
+    data.push_str(line);
+    data.push('\n');
+    append(&path, data)?;
+    Ok(true)
+}
+
+/// Copies the given file to `<name>.<timestamp>.bak` alongside it using the local time formatted
+/// as `%Y%m%d%H%M%S`, preserving mode and times. Handles path expansion and returns the absolute
+/// path of the backup. Never overwrites, a `.1`, `.2`, ... suffix is added when a backup from the
+/// same second already exists.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_backup");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "foo").is_ok());
+/// let backup = sys::backup(&file1).unwrap();
+/// assert_eq!(sys::readstring(&backup).unwrap(), "foo");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn backup<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
+    let path = path.as_ref().abs()?;
+    let name = format!("{}.{}.bak", path.base()?, time::Local::now().format("%Y%m%d%H%M%S"));
+    backup_p(&path, iter::once(name.clone()).chain((1..).map(|i| format!("{}.{}", name, i))))
+}
+
+/// Copies the given file to `<name>.bak` alongside it or the first of `<name>.bak.1`,
+/// `<name>.bak.2`, ... that doesn't exist yet, preserving mode and times. Handles path expansion
+/// and returns the absolute path of the backup.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_backup_numbered");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "foo").is_ok());
+/// assert_eq!(sys::backup_numbered(&file1).unwrap(), tmpdir.mash("file1.bak"));
+/// assert_eq!(sys::backup_numbered(&file1).unwrap(), tmpdir.mash("file1.bak.1"));
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn backup_numbered<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
+    let path = path.as_ref().abs()?;
+    let name = format!("{}.bak", path.base()?);
+    backup_p(&path, iter::once(name.clone()).chain((1..).map(|i| format!("{}.{}", name, i))))
+}
+
+/// Private implementation to copy the given file to the first of the given `names` alongside it
+/// that doesn't exist yet, preserving mode and times.
+fn backup_p<T: Iterator<Item = String>>(path: &Path, mut names: T) -> FuResult<PathBuf> {
+    let meta = match fs::metadata(path) {
+        Ok(x) if x.is_file() => x,
+        Ok(_) => return Err(PathError::is_not_file(path).into()),
+        Err(_) => return Err(PathError::does_not_exist(path).into()),
+    };
+
+    // Claim the backup name atomically so an existing file is never overwritten
+    let dir = path.dir()?;
+    let (dst, mut f) = loop {
+        let dst = match names.next() {
+            Some(name) => dir.mash(name),
+            None => return Err(PathError::exists_already(path).into()),
+        };
+        match fs::OpenOptions::new().write(true).create_new(true).open(&dst) {
+            Ok(f) => break (dst, f),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err.into()),
+        }
+    };
+
+    // Remove the partial backup if anything fails along the way
+    let guard = defer(|| {
+        let _ = fs::remove_file(&dst);
+    });
+    io::copy(&mut File::open(path)?, &mut f)?;
+    f.set_permissions(meta.permissions())?;
+    f.sync_all()?;
+    settimes(&dst, &meta)?;
+    guard.cancel();
+    Ok(dst)
+}
+
 /// Wraps `chmod_p` to apply the given `mode` to all files/dirs using recursion and invoking
 /// the mode change on the close of this function call.
 ///
@@ -204,10 +353,89 @@ pub fn chmod_p<T: AsRef<Path>>(path: T) -> FuResult<Chmod> {
     Ok(Chmod { path, mode, dirs: false, files: false, recursive: true })
 }
 
+/// Change the mode of the `path` using the given symbolic mode `spec` e.g. `u+rwx,go-w` applied
+/// relative to the current mode of each path. Provides path expansion and globbing. See
+/// `parse_mode` for the supported syntax.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_chmod_sym");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch_p(&file1, 0o644).is_ok());
+/// assert!(sys::chmod_sym(&file1, "u+x,go-r").is_ok());
+/// assert_eq!(file1.mode().unwrap(), 0o100700);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn chmod_sym<T: AsRef<Path>>(path: T, spec: &str) -> FuResult<()> {
+    let path = path.as_ref().abs()?;
+
+    // Handle globbing
+    let sources = sys::glob(&path)?;
+    if sources.is_empty() {
+        return Err(PathError::does_not_exist(&path).into());
+    }
+
+    // Execute the chmod for all sources relative to their own mode
+    for source in sources {
+        let mode = parse_mode(source.mode()?, spec)?;
+        source.setperms(fs::Permissions::from_mode(mode & 0o7777))?;
+    }
+    Ok(())
+}
+
+/// Change the mode of the given `root` and everything below it applying `dir_mode` to directories
+/// and `file_mode` to regular files. Handles path expansion. Links and other special files are
+/// left untouched. Directory modes are applied after their contents so that a restrictive
+/// `dir_mode` doesn't block the walk.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_chmod_tree");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("dir1/file1");
+/// assert!(sys::touch_p(&file1, 0o600).is_ok());
+/// assert!(sys::chmod_tree(&tmpdir, 0o755, 0o644).is_ok());
+/// assert_eq!(tmpdir.mash("dir1").mode().unwrap(), 0o40755);
+/// assert_eq!(file1.mode().unwrap(), 0o100644);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn chmod_tree<T: AsRef<Path>>(root: T, dir_mode: u32, file_mode: u32) -> FuResult<()> {
+    let root = root.as_ref().abs()?;
+    let meta = fs::symlink_metadata(&root).map_err(|_| PathError::does_not_exist(&root))?;
+    if meta.is_file() {
+        return Ok(fs::set_permissions(&root, fs::Permissions::from_mode(file_mode))?);
+    }
+    if !meta.is_dir() {
+        return Ok(());
+    }
+
+    let mut dirs = vec![root.clone()];
+    for entry in sys::walk(&root)? {
+        let entry = entry?;
+        let meta = entry.metadata();
+        if meta.is_dir() {
+            dirs.push(entry.into_path());
+        } else if meta.is_file() {
+            fs::set_permissions(entry.path(), fs::Permissions::from_mode(file_mode))?;
+        }
+    }
+    for dir in dirs.iter().rev() {
+        fs::set_permissions(dir, fs::Permissions::from_mode(dir_mode))?;
+    }
+    Ok(())
+}
+
 /// Change the ownership of the `path` providing path expansion, globbing, recursion and error
 /// tracing. Follows links.
-//// ### Examples
-/// ```ignore
+///
+/// ### Examples
+/// ```
 /// use fungus::prelude::*;
 ///
 /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_chown");
@@ -222,18 +450,58 @@ pub fn chown<T: AsRef<Path>>(path: T, uid: u32, gid: u32) -> FuResult<()> {
     chown_p(path, uid, gid, true)
 }
 
+/// Change the ownership of the `path` and everything under it providing path expansion, globbing
+/// and error tracing. Wraps `lchown` so links are never dereferenced but rather the links
+/// themselves are changed.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_chown_all");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch(tmpdir.mash("file1")).is_ok());
+/// assert!(sys::chown_all(&tmpdir, user::getuid(), user::getgid()).is_ok());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn chown_all<T: AsRef<Path>>(path: T, uid: u32, gid: u32) -> FuResult<()> {
+    lchown(path, uid, gid)
+}
+
+/// Wraps `chown` resolving the given `user` and `group` names to their ids first.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_chown_named");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::touch(&file1).is_ok());
+/// assert!(sys::chown_named(&file1, user::name().unwrap(), group::current().unwrap().name).is_ok());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn chown_named<T: AsRef<Path>, U: AsRef<str>, V: AsRef<str>>(path: T, user: U, group: V) -> FuResult<()> {
+    let uid = user::lookup_by_name(user)?.uid;
+    let gid = group::lookup_by_name(group)?.gid;
+    chown(path, uid, gid)
+}
+
 /// Change the ownership of the `path` providing path expansion, globbing, recursion and error
-/// tracing. Does not follow links.
-//// ### Examples
-/// ```ignore
+/// tracing. Does not follow links but rather changes the links themselves.
+///
+/// ### Examples
+/// ```
 /// use fungus::prelude::*;
 ///
-/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_chown");
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_lchown");
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// assert!(sys::mkdir(&tmpdir).is_ok());
 /// let file1 = tmpdir.mash("file1");
 /// assert!(sys::touch(&file1).is_ok());
-/// assert!(sys::chown(&file1, user::getuid(), user::getgid()).is_ok());
+/// assert!(sys::lchown(&file1, user::getuid(), user::getgid()).is_ok());
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
 pub fn lchown<T: AsRef<Path>>(path: T, uid: u32, gid: u32) -> FuResult<()> {
@@ -250,7 +518,7 @@ fn chown_p<T: AsRef<Path>>(path: T, uid: u32, gid: u32, follow: bool) -> FuResul
         return Err(PathError::does_not_exist(&path).into());
     }
 
-    // Execute the chmod for all sources
+    // Execute the chown for all sources
     for source in sources {
         for entry in WalkDir::new(&source).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
             let srcpath = entry?.into_path();
@@ -293,6 +561,35 @@ fn chown_p<T: AsRef<Path>>(path: T, uid: u32, gid: u32, follow: bool) -> FuResul
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
 pub fn copy<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<PathBuf> {
+    copy_all_with(src, dst, |_, _, _| {})
+}
+
+/// Wraps `copy` invoking the given `progress` callback once per regular file copied with the
+/// source file path, the bytes copied so far and the total bytes to be copied. The total is
+/// computed up front from the regular files to be copied. Links are recreated rather than
+/// followed and don't count towards the byte totals.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_copy_all_with");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("file2");
+/// assert!(sys::write(&file1, "foo").is_ok());
+/// let mut progress = vec![];
+/// assert!(sys::copy_all_with(&file1, &file2, |_, copied, total| progress.push((copied, total))).is_ok());
+/// assert_eq!(progress, vec![(3, 3)]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn copy_all_with<T, U, F>(src: T, dst: U, mut progress: F) -> FuResult<PathBuf>
+where
+    T: AsRef<Path>,
+    U: AsRef<Path>,
+    F: FnMut(&Path, u64, u64),
+{
     let mut clone = true;
     let dstabs = dst.as_ref().abs()?;
 
@@ -307,7 +604,19 @@ pub fn copy<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<PathBuf>
         clone = false;
     }
 
+    // Compute the total bytes of regular files up front
+    let mut total = 0;
+    for srcroot in &sources {
+        for entry in WalkDir::new(srcroot).follow_links(false) {
+            let meta = entry?.metadata()?;
+            if meta.is_file() {
+                total += meta.len();
+            }
+        }
+    }
+
     // Recurse on sources
+    let mut copied = 0;
     for srcroot in sources {
         for entry in WalkDir::new(&srcroot).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
             let srcpath = entry?.into_path();
@@ -331,6 +640,11 @@ pub fn copy<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<PathBuf>
                 // Copy file
                 _ => {
                     copyfile(&srcpath, &dstpath)?;
+                    let meta = fs::symlink_metadata(&srcpath)?;
+                    if meta.is_file() {
+                        copied += meta.len();
+                        progress(&srcpath, copied, total);
+                    }
                 },
             }
         }
@@ -339,6 +653,138 @@ pub fn copy<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<PathBuf>
     Ok(dstabs)
 }
 
+/// Policy for handling existing destination files and links in `copy_with`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overwrite {
+    /// Always replace the destination
+    #[default]
+    Always,
+
+    /// Never replace the destination
+    Never,
+
+    /// Replace the destination only when the source has a newer modification time
+    IfNewer,
+}
+
+/// Options for controlling the behavior of `copy_with`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CopyOpts {
+    /// Copy the targets of links rather than recreating the links themselves
+    pub follow_symlinks: bool,
+
+    /// Set the permissions of the destination to those of the source
+    pub preserve_mode: bool,
+
+    /// Set the access and modification times of the destination to those of the source
+    pub preserve_times: bool,
+
+    /// Policy for handling existing destination files and links
+    pub overwrite: Overwrite,
+}
+
+/// Copies src to dst recursively with rsync like semantics controlled by the given `opts`.
+/// Handles path expansion and globbing and returns an absolute path of the destination.
+///
+/// The dst will be copied to if it is an existing directory.
+/// The dst will be a clone of the src if it doesn't exist.
+/// Existing directories are merged while existing files and links are handled by the
+/// `opts.overwrite` policy. Without `opts.preserve_mode` new files and directories are created
+/// with the default mode as determined by the umask.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_copy_with");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("file2");
+/// assert!(sys::write(&file1, "foo").is_ok());
+/// assert!(sys::write(&file2, "bar").is_ok());
+/// let opts = sys::CopyOpts { overwrite: sys::Overwrite::Never, ..Default::default() };
+/// assert!(sys::copy_with(&file1, &file2, opts).is_ok());
+/// assert_eq!(sys::readstring(&file2).unwrap(), "bar");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn copy_with<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U, opts: CopyOpts) -> FuResult<PathBuf> {
+    let dstabs = dst.as_ref().abs()?;
+
+    // Handle globbing
+    let sources = sys::glob(&src)?;
+    if sources.is_empty() {
+        return Err(PathError::does_not_exist(&src).into());
+    }
+
+    // Copy into destination vs clone as destination
+    let clone = !dstabs.is_dir() && sources.len() == 1;
+    mkdir(if clone { dstabs.dir()? } else { dstabs.clone() })?;
+
+    // Directory permissions and times are set last as populating them changes their times and a
+    // read only mode would block populating them at all.
+    let mut dirs: Vec<(PathBuf, fs::Metadata)> = vec![];
+    for srcroot in sources {
+        // WalkDir always traverses a root link so only recreate the link when not following
+        let walker = match !opts.follow_symlinks && srcroot.is_symlink() {
+            true => WalkDir::new(&srcroot).max_depth(0),
+            false => WalkDir::new(&srcroot).follow_links(opts.follow_symlinks),
+        };
+        for entry in walker.sort_by(|x, y| x.file_name().cmp(y.file_name())) {
+            let srcpath = entry?.into_path();
+            let dstpath = match clone {
+                true => dstabs.mash(srcpath.trim_prefix(&srcroot)),
+                false => dstabs.mash(srcpath.trim_prefix(srcroot.dir()?)),
+            };
+            let meta = match opts.follow_symlinks {
+                true => fs::metadata(&srcpath)?,
+                false => fs::symlink_metadata(&srcpath)?,
+            };
+            if meta.is_dir() {
+                fs::create_dir_all(&dstpath)?;
+                dirs.push((dstpath, meta));
+                continue;
+            }
+
+            // Apply the overwrite policy to existing destination files and links
+            if let Ok(dstmeta) = fs::symlink_metadata(&dstpath) {
+                let skip = match opts.overwrite {
+                    Overwrite::Always => false,
+                    Overwrite::Never => true,
+                    Overwrite::IfNewer => dstmeta.modified()? >= meta.modified()?,
+                };
+                if skip {
+                    continue;
+                }
+                if !dstmeta.is_dir() {
+                    fs::remove_file(&dstpath)?;
+                }
+            }
+
+            // fs::copy always copies the permissions so stream the data otherwise
+            if meta.file_type().is_symlink() {
+                unix::fs::symlink(fs::read_link(&srcpath)?, &dstpath)?;
+            } else if opts.preserve_mode {
+                fs::copy(&srcpath, &dstpath)?;
+            } else {
+                io::copy(&mut File::open(&srcpath)?, &mut File::create(&dstpath)?)?;
+            }
+            if opts.preserve_times {
+                settimes(&dstpath, &meta)?;
+            }
+        }
+    }
+    for (path, meta) in dirs.iter().rev() {
+        if opts.preserve_mode {
+            fs::set_permissions(path, meta.permissions())?;
+        }
+        if opts.preserve_times {
+            settimes(path, meta)?;
+        }
+    }
+
+    Ok(dstabs)
+}
+
 /// Copyfile provides a flexible options for copying files
 #[derive(Debug)]
 pub struct Copyfile {
@@ -463,72 +909,389 @@ pub fn copyfile_p<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<Co
     })
 }
 
-/// Computes and returns the digest of the given `path`.
+/// Returns the number of bytes in the given `path` as reported by the metadata. Handles path
+/// expansion. Returns a `PathError::IsNotFile` for anything other than a regular file e.g.
+/// directories, devices or pipes. Note `/proc` files report a size of zero.
 ///
 /// ### Examples
 /// ```
 /// use fungus::prelude::*;
 ///
-/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_digest");
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_count_bytes");
 /// assert!(sys::remove_all(&tmpdir).is_ok());
-/// assert!(sys::mkdir(&tmpdir).is_ok());
-/// let file1 = tmpdir.mash("file1");
-/// let file2 = tmpdir.mash("file2");
-/// assert!(sys::write(&file1, "this is a test").is_ok());
-/// assert!(sys::copyfile(&file1, &file2).is_ok());
-/// assert_iter_eq(sys::digest(&file1).unwrap(), sys::digest(&file2).unwrap());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::write(&tmpfile, "one\ntwo\n").is_ok());
+/// assert_eq!(sys::count_bytes(&tmpfile).unwrap(), 8);
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
-pub fn digest<T: AsRef<Path>>(path: T) -> FuResult<Vec<u8>> {
-    Ok(Blake2b::digest(&readbytes(path)?).into_iter().collect())
+pub fn count_bytes<T: AsRef<Path>>(path: T) -> FuResult<u64> {
+    let path = path.as_ref().abs()?;
+    let meta = fs::metadata(&path)?;
+    if !meta.is_file() {
+        return Err(PathError::is_not_file(&path).into());
+    }
+    Ok(meta.len())
 }
 
-/// Returns the first captured string from the given regular expression `rx`.
+/// Returns the number of lines in the given `path` by scanning for newlines without reading the
+/// whole file into memory. Handles path expansion. A final line without a trailing newline is
+/// counted as well.
 ///
 /// ### Examples
 /// ```
 /// use fungus::prelude::*;
 ///
-/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_extract_string");
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_count_lines");
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// let tmpfile = tmpdir.mash("file1");
-/// assert!(sys::mkdir(&tmpdir).is_ok());
-/// let rx = Regex::new(r"'([^']+)'\s+\((\d{4})\)").unwrap();
-/// assert!(sys::write(&tmpfile, "Not my favorite movie: 'Citizen Kane' (1941).").is_ok());
-/// assert_eq!(sys::extract_string(&tmpfile, &rx).unwrap(), "Citizen Kane");
+/// assert!(sys::write(&tmpfile, "one\ntwo").is_ok());
+/// assert_eq!(sys::count_lines(&tmpfile).unwrap(), 2);
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
-pub fn extract_string<T: AsRef<Path>>(path: T, rx: &Regex) -> FuResult<String> {
-    let data = readstring(path)?;
-    let caps = rx.captures(&data).ok_or(FileError::FailedToExtractString)?;
-    let value = caps.get(1).ok_or(FileError::FailedToExtractString)?;
-    Ok(value.as_str().to_string())
+pub fn count_lines<T: AsRef<Path>>(path: T) -> FuResult<usize> {
+    let path = path.as_ref().abs()?;
+    let mut reader = BufReader::with_capacity(64 * 1024, File::open(&path)?);
+
+    let mut lines = 0;
+    let mut last = b'\n';
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        lines += buf.iter().filter(|x| **x == b'\n').count();
+        last = buf[buf.len() - 1];
+        let len = buf.len();
+        reader.consume(len);
+    }
+
+    // Count the final partial line
+    if last != b'\n' {
+        lines += 1;
+    }
+    Ok(lines)
 }
 
-/// Returns the first captured string from the given regular expression `rx`.
+/// DigestAlgo provides the supported digest algorithms
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DigestAlgo {
+    Blake2,
+    Sha256,
+    Sha512,
+}
+
+/// DigestReader wraps a reader updating a digest with every byte read through it, allowing data
+/// to be hashed while it is being streamed elsewhere rather than in a second pass.
 ///
 /// ### Examples
 /// ```
 /// use fungus::prelude::*;
 ///
-/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_extract_string_p");
-/// assert!(sys::remove_all(&tmpdir).is_ok());
-/// let tmpfile = tmpdir.mash("file1");
-/// assert!(sys::mkdir(&tmpdir).is_ok());
-/// let rx = Regex::new(r"'([^']+)'\s+\((\d{4})\)").unwrap();
-/// assert!(sys::write(&tmpfile, "Not my favorite movie: 'Citizen Kane' (1941).").is_ok());
-/// assert_eq!(sys::extract_string(&tmpfile, &rx).unwrap(), "Citizen Kane");
-/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let mut reader = sys::DigestReader::with_algo(&b"abc"[..], sys::DigestAlgo::Sha256);
+/// assert_eq!(io::copy(&mut reader, &mut io::sink()).unwrap(), 3);
+/// assert_eq!(hex::encode(reader.finalize()), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
 /// ```
-pub fn extract_string_p<T: AsRef<Path>, U: AsRef<str>>(path: T, rx: U) -> FuResult<String> {
-    extract_string(path, &Regex::new(rx.as_ref())?)
+pub struct DigestReader<R: Read> {
+    inner: R,
+    hasher: Hasher,
 }
 
-/// Returns the captured strings from the given regular expression `rx`.
-///
-/// ### Examples
-/// ```
-/// use fungus::prelude::*;
+/// Private implementation to hold the hasher for the selected digest algorithm
+enum Hasher {
+    Blake2(Blake2b),
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+impl<R: Read> DigestReader<R> {
+    /// Create a new digest reader wrapping the given `reader` using the Blake2b digest to match
+    /// `sys::digest`.
+    pub fn new(reader: R) -> Self {
+        Self::with_algo(reader, DigestAlgo::Blake2)
+    }
+
+    /// Create a new digest reader wrapping the given `reader` using the given `algo`.
+    pub fn with_algo(reader: R, algo: DigestAlgo) -> Self {
+        let hasher = match algo {
+            DigestAlgo::Blake2 => Hasher::Blake2(Blake2b::new()),
+            DigestAlgo::Sha256 => Hasher::Sha256(Sha256::new()),
+            DigestAlgo::Sha512 => Hasher::Sha512(Sha512::new()),
+        };
+        Self { inner: reader, hasher }
+    }
+
+    /// Consume the reader returning the digest of all bytes read through it.
+    pub fn finalize(self) -> Vec<u8> {
+        match self.hasher {
+            Hasher::Blake2(x) => x.finalize().to_vec(),
+            Hasher::Sha256(x) => x.finalize().to_vec(),
+            Hasher::Sha512(x) => x.finalize().to_vec(),
+        }
+    }
+}
+
+impl<R: Read> Read for DigestReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        match &mut self.hasher {
+            Hasher::Blake2(x) => x.update(&buf[..n]),
+            Hasher::Sha256(x) => x.update(&buf[..n]),
+            Hasher::Sha512(x) => x.update(&buf[..n]),
+        }
+        Ok(n)
+    }
+}
+
+/// Computes and returns the Blake2b digest of the given `path`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_digest");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("file2");
+/// assert!(sys::write(&file1, "this is a test").is_ok());
+/// assert!(sys::copyfile(&file1, &file2).is_ok());
+/// assert_iter_eq(sys::digest(&file1).unwrap(), sys::digest(&file2).unwrap());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn digest<T: AsRef<Path>>(path: T) -> FuResult<Vec<u8>> {
+    digest_with(path, DigestAlgo::Blake2)
+}
+
+/// Computes and returns a single Blake2b digest over the directory tree at the given `root`.
+/// Entries are walked in sorted order, without following links, feeding each entry's type,
+/// relative path and mode bits into the digest along with file contents and link targets. The
+/// result is thus independent of filesystem enumeration order but sensitive to any change in
+/// content, naming or permissions. Empty directories contribute their path. Special files e.g.
+/// fifos, sockets and devices are never opened, only their type and mode are included.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_digest_dir");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::write(tmpdir.mash("dir1/file1"), "this is a test").is_ok());
+/// assert!(sys::copy(tmpdir.mash("dir1"), tmpdir.mash("dir2")).is_ok());
+/// assert_eq!(sys::digest_dir(tmpdir.mash("dir1")).unwrap(), sys::digest_dir(tmpdir.mash("dir2")).unwrap());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn digest_dir<T: AsRef<Path>>(root: T) -> FuResult<Vec<u8>> {
+    let root = root.as_ref().abs()?;
+    let mut hasher = Blake2b::new();
+    for entry in sys::walk(&root)? {
+        let entry = entry?;
+        let meta = entry.metadata();
+        let (kind, data) = if meta.is_dir() {
+            (b'd', None)
+        } else if meta.is_file() {
+            (b'f', None)
+        } else if meta.file_type().is_symlink() {
+            (b'l', Some(entry.path().readlink()?.as_os_str().as_bytes().to_vec()))
+        } else {
+            // Full mode including the file type bits distinguishes the kind of special file
+            (b'o', Some(meta.mode().to_le_bytes().to_vec()))
+        };
+
+        // Length prefix variable fields so that adjacent entries can't be confused
+        let rel = entry.path().trim_prefix(&root);
+        let rel = rel.as_os_str().as_bytes();
+        hasher.update([kind]);
+        hasher.update((rel.len() as u64).to_le_bytes());
+        hasher.update(rel);
+        hasher.update((meta.mode() & 0o7777).to_le_bytes());
+        match data {
+            Some(data) => {
+                hasher.update((data.len() as u64).to_le_bytes());
+                hasher.update(&data);
+            },
+            None if kind == b'f' => {
+                hasher.update(meta.len().to_le_bytes());
+                digest_update(&mut hasher, &mut File::open(entry.path())?)?;
+            },
+            None => (),
+        }
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Wraps `digest_with` to compute and return the SHA-256 digest of the given `path`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_digest_sha256");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "abc").is_ok());
+/// assert_eq!(hex::encode(sys::digest_sha256(&file1).unwrap()), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn digest_sha256<T: AsRef<Path>>(path: T) -> FuResult<Vec<u8>> {
+    digest_with(path, DigestAlgo::Sha256)
+}
+
+/// Computes and returns the digest of the given `path` using the given `algo`. The file is
+/// streamed through the digest in fixed size chunks rather than loaded into memory.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_digest_with");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::touch(&file1).is_ok());
+/// assert_eq!(sys::digest_with(&file1, sys::DigestAlgo::Sha512).unwrap().len(), 64);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn digest_with<T: AsRef<Path>>(path: T, algo: DigestAlgo) -> FuResult<Vec<u8>> {
+    let path = path.as_ref().abs()?;
+    let mut f = File::open(&path)?;
+    match algo {
+        DigestAlgo::Blake2 => digest_reader::<Blake2b, _>(&mut f),
+        DigestAlgo::Sha256 => digest_reader::<Sha256, _>(&mut f),
+        DigestAlgo::Sha512 => digest_reader::<Sha512, _>(&mut f),
+    }
+}
+
+/// Private implementation to stream the given `reader` through the digest `D`
+fn digest_reader<D: Digest, R: Read>(reader: &mut R) -> FuResult<Vec<u8>> {
+    let mut hasher = D::new();
+    digest_update(&mut hasher, reader)?;
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Private implementation to stream the given `reader` into the given `hasher`
+fn digest_update<D: Digest, R: Read>(hasher: &mut D, reader: &mut R) -> FuResult<()> {
+    let mut buf = [0; 8192];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(())
+}
+
+/// Returns the first capture group of the first line matching the given regular expression `rx`.
+/// Lines are read lazily so the file is never fully loaded into memory. Returns
+/// `FileError::FailedToExtractString` when no line matches or `rx` has no capture group.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_extract");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "pkgname=linux\npkgver=5.4.8\n").is_ok());
+/// let rx = Regex::new(r"^pkgver=(.*)$").unwrap();
+/// assert_eq!(sys::extract(&tmpfile, &rx).unwrap(), "5.4.8");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn extract<T: AsRef<Path>>(path: T, rx: &Regex) -> FuResult<String> {
+    extract_p(path, rx, 1)?.pop().ok_or_else(|| FileError::FailedToExtractString.into())
+}
+
+/// Returns the first capture group of every line matching the given regular expression `rx`.
+/// Lines are read lazily so the file is never fully loaded into memory. Returns
+/// `FileError::FailedToExtractString` when no line matches or `rx` has no capture group.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_extract_all");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "depend=foo\npkgver=5.4.8\ndepend=bar\n").is_ok());
+/// let rx = Regex::new(r"^depend=(.*)$").unwrap();
+/// assert_eq!(sys::extract_all(&tmpfile, &rx).unwrap(), vec!["foo", "bar"]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn extract_all<T: AsRef<Path>>(path: T, rx: &Regex) -> FuResult<Vec<String>> {
+    extract_p(path, rx, usize::MAX)
+}
+
+/// Private implementation to return the first capture group of up to `max` lines matching the
+/// given regular expression `rx`.
+fn extract_p<T: AsRef<Path>>(path: T, rx: &Regex, max: usize) -> FuResult<Vec<String>> {
+    if rx.captures_len() < 2 {
+        return Err(FileError::FailedToExtractString.into());
+    }
+    let mut values = vec![];
+    for line in readlines_iter(path)? {
+        let line = line?;
+        if let Some(value) = rx.captures(&line).and_then(|x| x.get(1)) {
+            values.push(value.as_str().to_string());
+            if values.len() >= max {
+                break;
+            }
+        }
+    }
+    if values.is_empty() {
+        return Err(FileError::FailedToExtractString.into());
+    }
+    Ok(values)
+}
+
+/// Returns the first captured string from the given regular expression `rx`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_extract_string");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let rx = Regex::new(r"'([^']+)'\s+\((\d{4})\)").unwrap();
+/// assert!(sys::write(&tmpfile, "Not my favorite movie: 'Citizen Kane' (1941).").is_ok());
+/// assert_eq!(sys::extract_string(&tmpfile, &rx).unwrap(), "Citizen Kane");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn extract_string<T: AsRef<Path>>(path: T, rx: &Regex) -> FuResult<String> {
+    let data = readstring(path)?;
+    let caps = rx.captures(&data).ok_or(FileError::FailedToExtractString)?;
+    let value = caps.get(1).ok_or(FileError::FailedToExtractString)?;
+    Ok(value.as_str().to_string())
+}
+
+/// Returns the first captured string from the given regular expression `rx`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_extract_string_p");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let rx = Regex::new(r"'([^']+)'\s+\((\d{4})\)").unwrap();
+/// assert!(sys::write(&tmpfile, "Not my favorite movie: 'Citizen Kane' (1941).").is_ok());
+/// assert_eq!(sys::extract_string(&tmpfile, &rx).unwrap(), "Citizen Kane");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn extract_string_p<T: AsRef<Path>, U: AsRef<str>>(path: T, rx: U) -> FuResult<String> {
+    extract_string(path, &Regex::new(rx.as_ref())?)
+}
+
+/// Returns the captured strings from the given regular expression `rx`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
 ///
 /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_extract_strings");
 /// assert!(sys::remove_all(&tmpdir).is_ok());
@@ -568,6 +1331,151 @@ pub fn extract_strings_p<T: AsRef<Path>, U: AsRef<str>>(path: T, rx: U) -> FuRes
     extract_strings(path, &Regex::new(rx.as_ref())?)
 }
 
+/// Returns true if any line of the given `path` matches the given regular expression `re`. Lines
+/// are read lazily so the file is never fully loaded into memory and line endings are stripped
+/// before matching.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_file_contains_line");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "pkgname=linux\npkgver=5.4.8\n").is_ok());
+/// assert_eq!(sys::file_contains_line(&tmpfile, &Regex::new(r"^pkgver=").unwrap()).unwrap(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn file_contains_line<T: AsRef<Path>>(path: T, re: &Regex) -> FuResult<bool> {
+    for line in readlines_iter(path)? {
+        if re.is_match(&line?) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Creates a new hard link `dst` for the given `src` file. Handles path expansion and creates
+/// the parent directory as needed, returning the absolute path of the link. Returns a
+/// `FileError::CrossDevice` if `src` and `dst` are on different filesystems.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_hardlink");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("file2");
+/// assert!(sys::touch(&file1).is_ok());
+/// assert!(sys::hardlink(&file1, &file2).is_ok());
+/// assert_eq!(sys::same_file(&file1, &file2).unwrap(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn hardlink<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<PathBuf> {
+    let src = src.as_ref().abs()?;
+    let dst = dst.as_ref().abs()?;
+    if dst.exists() {
+        return Err(PathError::exists_already(dst).into());
+    }
+    mkdir(dst.dir()?)?;
+    match fs::hard_link(&src, &dst) {
+        Ok(_) => Ok(dst),
+        Err(err) if err.raw_os_error() == Some(libc::EXDEV) => Err(FileError::cross_device(src, dst).into()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Mirrors the given `src` directory tree to `dst` using hard links for files and fresh
+/// directories for directories, which is useful for cheap snapshots. Symlinks are recreated
+/// pointing to the same target. Handles path expansion and returns the absolute path of `dst`.
+/// Returns a `FileError::CrossDevice` if `src` and `dst` are on different filesystems.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_hardlink_all");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("src/file1");
+/// let file2 = tmpdir.mash("dst/file1");
+/// assert!(sys::touch(&file1).is_ok());
+/// assert!(sys::hardlink_all(tmpdir.mash("src"), tmpdir.mash("dst")).is_ok());
+/// assert_eq!(sys::same_file(&file1, &file2).unwrap(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn hardlink_all<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<PathBuf> {
+    let src = src.as_ref().abs()?;
+    let dst = dst.as_ref().abs()?;
+    if !src.is_dir() {
+        return Err(PathError::is_not_dir(src).into());
+    }
+
+    for entry in WalkDir::new(&src).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
+        let entry = entry?;
+        let path = dst.mash(entry.path().trim_prefix(&src));
+        if entry.file_type().is_dir() {
+            mkdir(&path)?;
+        } else if entry.file_type().is_symlink() {
+            symlink(&path, entry.path().readlink()?)?;
+        } else {
+            hardlink(entry.path(), &path)?;
+        }
+    }
+    Ok(dst)
+}
+
+/// Inserts the given `line` after the first line matching the given regular expression
+/// `anchor_re`, using the anchor's line ending. Nothing is done if a line already matches `line`
+/// exactly or no line matches the anchor. The file is rewritten via `write_atomic`. Returns true
+/// if the line was inserted.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_insert_line_after");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "[options]\nfoo=1\n").is_ok());
+/// assert_eq!(sys::insert_line_after(&tmpfile, &Regex::new(r"^\[options\]$").unwrap(), "bar=2").unwrap(), true);
+/// assert_eq!(sys::readstring(&tmpfile).unwrap(), "[options]\nbar=2\nfoo=1\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn insert_line_after<T: AsRef<Path>>(path: T, anchor_re: &Regex, line: &str) -> FuResult<bool> {
+    let path = path.as_ref().abs()?;
+    let data = readstring(&path)?;
+    if data.lines().any(|x| x == line) {
+        return Ok(false);
+    }
+
+    // Split just after the anchor's line ending
+    let mut offset = 0;
+    for current in data.split_inclusive('\n') {
+        let body = current.trim_end_matches(['\r', '\n']);
+        offset += current.len();
+        if anchor_re.is_match(body) {
+            let ending = match &current[body.len()..] {
+                "" => "\n",
+                x => x,
+            };
+            let mut result = String::with_capacity(data.len() + line.len() + 2);
+            result.push_str(&data[..offset]);
+            if current.len() == body.len() {
+                result.push_str(ending);
+            }
+            result.push_str(line);
+            result.push_str(ending);
+            result.push_str(&data[offset..]);
+            write_atomic(&path, result)?;
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 /// Creates the given directory and any parent directories needed, handling path expansion and
 /// returning an absolute path created.
 ///
@@ -589,6 +1497,39 @@ pub fn mkdir<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
     Ok(path)
 }
 
+/// Creates the given directory and any missing parent directories, handling path expansion and
+/// returning the absolute path. Missing parent directories are created with the default mode
+/// subject to the process umask while the final directory gets exactly the given `mode`
+/// regardless of the umask e.g. 0o700 for the XDG runtime directory. The final directory is
+/// created with the given `mode` from the start so it is never more permissive than requested.
+/// Returns a `PathError::IsNotDir` if the path exists and isn't a directory.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_mkdir_m");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir_m(tmpdir.mash("dir1/dir2"), 0o700).is_ok());
+/// assert_eq!(tmpdir.mash("dir1/dir2").mode().unwrap(), 0o40700);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn mkdir_m<T: AsRef<Path>>(path: T, mode: u32) -> FuResult<PathBuf> {
+    let path = path.as_ref().abs()?;
+    match fs::metadata(&path) {
+        Ok(meta) if !meta.is_dir() => return Err(PathError::is_not_dir(&path).into()),
+        Ok(_) => {},
+        Err(_) => {
+            mkdir(path.dir()?)?;
+            fs::DirBuilder::new().mode(mode).create(&path)?;
+        },
+    }
+
+    // The umask may have stripped bits during creation
+    fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+    Ok(path)
+}
+
 /// Wraps `mkdir` allowing for setting the directory's mode.
 ///
 /// ### Examples
@@ -607,6 +1548,122 @@ pub fn mkdir_p<T: AsRef<Path>>(path: T, mode: u32) -> FuResult<PathBuf> {
     Ok(path)
 }
 
+/// Move a file or directory recursively handling path expansion. Attempts a rename first and on
+/// `EXDEV` falls back to copying then removing the source, preserving permissions and times.
+/// Moves `src` into `dst` if `dst` is an existing directory and creates the destination's parent
+/// directory if needed. Returns the absolute path of the destination.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_move_all");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let dir1 = tmpdir.mash("dir1");
+/// let dir2 = tmpdir.mash("dir2");
+/// assert!(sys::mkdir(&dir1).is_ok());
+/// assert!(sys::touch(dir1.mash("file1")).is_ok());
+/// assert_eq!(sys::move_all(&dir1, &dir2).unwrap(), dir2);
+/// assert_eq!(dir1.exists(), false);
+/// assert_eq!(dir2.mash("file1").exists(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn move_all<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<PathBuf> {
+    let src = src.as_ref().abs()?;
+    if !src.exists() && !src.is_symlink() {
+        return Err(PathError::does_not_exist(&src).into());
+    }
+    let dst = move_dst(&src, dst)?;
+    move_rename(&src, &dst)?;
+    Ok(dst)
+}
+
+/// Move a file or link handling path expansion. Attempts a rename first and on `EXDEV` falls back
+/// to copying then removing the source, preserving permissions and times. Moves `src` into `dst`
+/// if `dst` is an existing directory and creates the destination's parent directory if needed.
+/// Returns the absolute path of the destination.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_move_file");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("dir1/file2");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch(&file1).is_ok());
+/// assert_eq!(sys::move_file(&file1, &file2).unwrap(), file2);
+/// assert_eq!(file1.exists(), false);
+/// assert_eq!(file2.exists(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn move_file<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<PathBuf> {
+    let src = src.as_ref().abs()?;
+    if !src.exists() && !src.is_symlink() {
+        return Err(PathError::does_not_exist(&src).into());
+    }
+    if src.is_dir() && !src.is_symlink() {
+        return Err(PathError::is_not_file(&src).into());
+    }
+    let dst = move_dst(&src, dst)?;
+    move_rename(&src, &dst)?;
+    Ok(dst)
+}
+
+/// Private implementation of the copy then remove fallback for moves across filesystems
+fn move_copy(src: &Path, dst: &Path) -> FuResult<()> {
+    // Directory permissions and times are set last as populating them changes their times and a
+    // read only mode would block populating them at all.
+    let mut dirs: Vec<(PathBuf, fs::Metadata)> = vec![];
+    for entry in WalkDir::new(src).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
+        let srcpath = entry?.into_path();
+        let dstpath = dst.mash(srcpath.trim_prefix(src));
+        let meta = fs::symlink_metadata(&srcpath)?;
+        if meta.file_type().is_symlink() {
+            unix::fs::symlink(fs::read_link(&srcpath)?, &dstpath)?;
+        } else if meta.is_dir() {
+            fs::create_dir_all(&dstpath)?;
+            dirs.push((dstpath, meta));
+            continue;
+        } else {
+            fs::copy(&srcpath, &dstpath)?;
+        }
+        settimes(&dstpath, &meta)?;
+    }
+    for (path, meta) in dirs.iter().rev() {
+        fs::set_permissions(path, meta.permissions())?;
+        settimes(path, meta)?;
+    }
+
+    // Remove the source only after the copy has fully succeeded
+    match fs::symlink_metadata(src)?.is_dir() {
+        true => fs::remove_dir_all(src)?,
+        false => fs::remove_file(src)?,
+    }
+    Ok(())
+}
+
+/// Private implementation of the move destination resolution
+fn move_dst<T: AsRef<Path>>(src: &Path, dst: T) -> FuResult<PathBuf> {
+    let dst = dst.as_ref().abs()?;
+    let dst = match dst.is_dir() {
+        true => dst.mash(src.base()?),
+        false => dst,
+    };
+    mkdir(dst.dir()?)?;
+    Ok(dst)
+}
+
+/// Private implementation of the move attempting a rename before falling back on a copy
+fn move_rename(src: &Path, dst: &Path) -> FuResult<()> {
+    match fs::rename(src, dst) {
+        Ok(_) => Ok(()),
+        Err(err) if err.raw_os_error() == Some(libc::EXDEV) => move_copy(src, dst),
+        Err(err) => Err(err.into()),
+    }
+}
+
 /// Move a file or directory handling path expansion and globbing. Replaces destination files if
 /// exist but always moves `src` into `dst` if `dst` is an existing directory.
 ///
@@ -649,8 +1706,76 @@ pub fn move_p<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<()> {
     Ok(())
 }
 
-/// Removes the given empty directory or file. Handles path expansion. Does
-/// not follow symbolic links but rather removes the links themselves.
+/// Returns the mode resulting from applying the given symbolic mode `spec` to the `current` mode.
+/// The `spec` is a comma separated list of clauses of the form `[ugoa]*([-+=][rwxXst]*)+` where
+/// an empty class is the same as `a`. `X` only grants execute if `current` is a directory or
+/// already has an execute bit set. File type bits of `current` are preserved.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(sys::parse_mode(0o644, "u+x,go-r").unwrap(), 0o700);
+/// assert_eq!(sys::parse_mode(0o100755, "a=r").unwrap(), 0o100444);
+/// ```
+pub fn parse_mode(current: u32, spec: &str) -> FuResult<u32> {
+    let mut mode = current;
+    for clause in spec.split(',') {
+        let mut chars = clause.chars().peekable();
+
+        // Parse the class which defaults to all
+        let mut class = 0;
+        while let Some(&c) = chars.peek() {
+            class |= match c {
+                'u' => 0o4700,
+                'g' => 0o2070,
+                'o' => 0o1007,
+                'a' => 0o7777,
+                _ => break,
+            };
+            chars.next();
+        }
+        if class == 0 {
+            class = 0o7777;
+        }
+
+        // Parse one or more operations with their permissions
+        if chars.peek().is_none() {
+            return Err(FileError::invalid_mode(spec).into());
+        }
+        while let Some(op) = chars.next() {
+            if op != '+' && op != '-' && op != '=' {
+                return Err(FileError::invalid_mode(spec).into());
+            }
+            let mut perms = 0;
+            while let Some(&c) = chars.peek() {
+                perms |= match c {
+                    'r' => 0o444,
+                    'w' => 0o222,
+                    'x' => 0o111,
+                    'X' if mode & 0o170000 == 0o040000 || mode & 0o111 != 0 => 0o111,
+                    'X' => 0,
+                    's' => 0o6000,
+                    't' => 0o1000,
+                    '+' | '-' | '=' => break,
+                    _ => return Err(FileError::invalid_mode(spec).into()),
+                };
+                chars.next();
+            }
+            let bits = perms & class;
+            match op {
+                '+' => mode |= bits,
+                '-' => mode &= !bits,
+                _ => mode = (mode & !class) | bits,
+            }
+        }
+    }
+    Ok(mode)
+}
+
+/// Removes the given single file, link or empty directory. Handles path expansion. Does not
+/// follow symbolic links but rather removes the links themselves. Will fail rather than remove a
+/// non-empty directory, use `remove_all` for that.
 ///
 /// ### Examples
 /// ```
@@ -663,18 +1788,20 @@ pub fn move_p<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<()> {
 /// ```
 pub fn remove<T: AsRef<Path>>(path: T) -> FuResult<()> {
     let path = path.as_ref().abs()?;
-    if let Ok(meta) = fs::metadata(&path) {
-        if meta.is_file() {
-            fs::remove_file(path)?;
-        } else if meta.is_dir() {
+    if let Ok(meta) = fs::symlink_metadata(&path) {
+        if meta.is_dir() {
             fs::remove_dir(path)?;
+        } else {
+            fs::remove_file(path)?;
         }
     }
     Ok(())
 }
 
 /// Removes the given directory after removing all of its contents. Handles path expansion. Does
-/// not follow symbolic links but rather removes the links themselves.
+/// not follow symbolic links but rather removes the links themselves. Refuses to remove the
+/// filesystem root, the user's home directory or the current working directory returning a
+/// `FileError::RefusedDangerousDelete`, use `remove_all_force` to bypass this guard.
 ///
 /// ### Examples
 /// ```
@@ -684,15 +1811,71 @@ pub fn remove<T: AsRef<Path>>(path: T) -> FuResult<()> {
 /// assert!(sys::mkdir(&tmpdir).is_ok());
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// assert_eq!(tmpdir.exists(), false);
+/// assert!(sys::remove_all("/").is_err());
 /// ```
 pub fn remove_all<T: AsRef<Path>>(path: T) -> FuResult<()> {
     let path = path.as_ref().abs()?;
-    if path.exists() {
+    let dangerous = [Some(PathBuf::from("/")), user::home_dir().and_then(|x| x.abs()).ok(), sys::cwd().ok()];
+    if dangerous.iter().flatten().any(|x| x == &path) {
+        return Err(FileError::refused_dangerous_delete(path).into());
+    }
+    remove_all_force(path)
+}
+
+/// Removes the given directory after removing all of its contents without guarding against
+/// dangerous paths. Handles path expansion. Does not follow symbolic links but rather removes
+/// the links themselves.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_remove_all_force");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::remove_all_force(&tmpdir).is_ok());
+/// assert_eq!(tmpdir.exists(), false);
+/// ```
+pub fn remove_all_force<T: AsRef<Path>>(path: T) -> FuResult<()> {
+    let path = path.as_ref().abs()?;
+    if fs::symlink_metadata(&path).is_ok() {
         fs::remove_dir_all(path)?;
     }
     Ok(())
 }
 
+/// Returns the contents of the `path` as a `String` guarding against reading untrusted files
+/// into memory. Handles path expansion. Returns a `FileError::TooLarge` if the file is larger
+/// than `max` bytes, checked via the metadata before reading and enforced while reading.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_read_string_limited");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::write(&tmpfile, "this is a test").is_ok());
+/// assert_eq!(sys::read_string_limited(&tmpfile, 14).unwrap(), "this is a test");
+/// assert!(sys::read_string_limited(&tmpfile, 13).is_err());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn read_string_limited<T: AsRef<Path>>(path: T, max: usize) -> FuResult<String> {
+    let path = path.as_ref().abs()?;
+    let f = File::open(&path)?;
+    let size = f.metadata()?.len();
+    if size > max as u64 {
+        return Err(FileError::too_large(size, max).into());
+    }
+
+    // Special files may report a zero size so limit the read as well
+    let mut data = String::new();
+    let read = f.take(max as u64 + 1).read_to_string(&mut data)?;
+    if read > max {
+        return Err(FileError::too_large(read as u64, max).into());
+    }
+    Ok(data)
+}
+
 /// Returns the contents of the `path` as a `Vec<u8>`.
 ///
 /// ### Examples
@@ -736,6 +1919,52 @@ pub fn readlines<T: AsRef<Path>>(path: T) -> FuResult<Vec<String>> {
     }
 }
 
+/// Wraps `readlines_iter` to skip lines for which the given predicate `pred` returns false. Errors
+/// are always passed through to the caller.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_readlines_filtered");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "foo\n\nbar").is_ok());
+/// let lines = sys::readlines_filtered(&tmpfile, |x| !x.is_empty()).unwrap();
+/// assert_eq!(lines.map(|x| x.unwrap()).collect::<Vec<String>>(), vec!["foo", "bar"]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn readlines_filtered<T, F>(path: T, mut pred: F) -> FuResult<impl Iterator<Item = FuResult<String>>>
+where
+    T: AsRef<Path>,
+    F: FnMut(&str) -> bool,
+{
+    Ok(readlines_iter(path)?.filter(move |x| match x {
+        Ok(line) => pred(line),
+        Err(_) => true,
+    }))
+}
+
+/// Returns an Iterator over the lines of the file, reading them lazily so that huge files can be
+/// processed with constant memory. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_readlines_iter");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "foo\nbar").is_ok());
+/// assert_eq!(sys::readlines_iter(&tmpfile).unwrap().map(|x| x.unwrap()).collect::<Vec<String>>(), vec!["foo", "bar"]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn readlines_iter<T: AsRef<Path>>(path: T) -> FuResult<impl Iterator<Item = FuResult<String>>> {
+    Ok(readlines_p(path)?.map(|x| x.map_err(|e| e.into())))
+}
+
 /// Returns an Iterator to the Reader of the lines of the file.
 ///
 /// ### Examples
@@ -778,6 +2007,46 @@ pub fn readstring<T: AsRef<Path>>(path: T) -> FuResult<String> {
     }
 }
 
+/// Replaces every match of the given regular expression `re` in each line of the given `path` with
+/// the given `replacement`, returning the number of replacements made. The `replacement` may
+/// reference capture groups e.g. `$1`. Lines are matched without their line endings which are
+/// preserved as is. The file is only rewritten, via `write_atomic`, when a replacement was made.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_replace_in_file");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "pkgname=linux\npkgver=5.4.8\n").is_ok());
+/// assert_eq!(sys::replace_in_file(&tmpfile, &Regex::new(r"^pkgver=.*$").unwrap(), "pkgver=5.4.9").unwrap(), 1);
+/// assert_eq!(sys::readstring(&tmpfile).unwrap(), "pkgname=linux\npkgver=5.4.9\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn replace_in_file<T: AsRef<Path>>(path: T, re: &Regex, replacement: &str) -> FuResult<usize> {
+    let path = path.as_ref().abs()?;
+    let data = readstring(&path)?;
+    let mut count = 0;
+    let mut result = String::with_capacity(data.len());
+    for line in data.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\r', '\n']);
+        let matches = re.find_iter(body).count();
+        if matches > 0 {
+            count += matches;
+            result.push_str(&re.replace_all(body, replacement));
+            result.push_str(&line[body.len()..]);
+        } else {
+            result.push_str(line);
+        }
+    }
+    if count > 0 {
+        write_atomic(&path, result)?;
+    }
+    Ok(count)
+}
+
 /// Returns true if the new mode is revoking permissions as compared to the old mode as pertains
 /// directory read/execute permissions. This is useful when recursively modifying file permissions.
 ///
@@ -791,6 +2060,36 @@ pub fn revoking_mode(old: u32, new: u32) -> bool {
     old & 0o0500 > new & 0o0500 || old & 0o0050 > new & 0o0050 || old & 0o0005 > new & 0o0005
 }
 
+/// Wraps `tempfile` to return a `TempFile` handle that removes the file once it goes out of
+/// scope unless `persist` is called.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let path = {
+///     let mut tmpfile = sys::scoped_tempfile("foo").unwrap();
+///     assert!(tmpfile.write_all(b"foobar").is_ok());
+///     assert_eq!(sys::readstring(&tmpfile).unwrap(), "foobar");
+///     tmpfile.path().to_path_buf()
+/// };
+/// assert_eq!(path.exists(), false);
+/// ```
+pub fn scoped_tempfile<T: AsRef<str>>(prefix: T) -> FuResult<TempFile> {
+    let (file, path) = tempfile(prefix)?;
+    Ok(TempFile { file, path, persist: false })
+}
+
+/// Private implementation to set the access and modification times of `path` to those of `meta`.
+/// Does not follow links.
+fn settimes<T: AsRef<Path>>(path: T, meta: &fs::Metadata) -> FuResult<()> {
+    let times = [
+        libc::timespec { tv_sec: meta.atime() as libc::time_t, tv_nsec: meta.atime_nsec() as libc::c_long },
+        libc::timespec { tv_sec: meta.mtime() as libc::time_t, tv_nsec: meta.mtime_nsec() as libc::c_long },
+    ];
+    utimens(path, &times, false)
+}
+
 /// Creates a new symbolic link. Handles path expansion and returns an absolute path to the
 /// link while still creating the symbolic link as a relative path to the target.
 ///
@@ -817,8 +2116,103 @@ pub fn symlink<T: AsRef<Path>, U: AsRef<Path>>(link: T, target: U) -> FuResult<P
     Ok(path)
 }
 
-/// Create an empty file similar to the linux touch command. Handles path expansion.
-/// Uses default file creation permissions 0o666 - umask usually ends up being 0o644.
+/// TempFile provides an open temporary file that is removed when dropped
+#[derive(Debug)]
+pub struct TempFile {
+    file: File,    // open handle to the temporary file
+    path: PathBuf, // path to the temporary file
+    persist: bool, // skip removal on drop
+}
+
+impl TempFile {
+    /// Returns the path to the temporary file
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpfile = sys::scoped_tempfile("foo").unwrap();
+    /// assert_eq!(tmpfile.path().exists(), true);
+    /// ```
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Consume the handle without removing the file and return its path. It is then up to the
+    /// calling code to clean up the file.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpfile = sys::scoped_tempfile("foo").unwrap().persist();
+    /// assert_eq!(tmpfile.exists(), true);
+    /// assert!(sys::remove(&tmpfile).is_ok());
+    /// ```
+    pub fn persist(mut self) -> PathBuf {
+        self.persist = true;
+        self.path.clone()
+    }
+}
+
+impl AsRef<Path> for TempFile {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Deref for TempFile {
+    type Target = File;
+
+    fn deref(&self) -> &File {
+        &self.file
+    }
+}
+
+impl DerefMut for TempFile {
+    fn deref_mut(&mut self) -> &mut File {
+        &mut self.file
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persist {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Creates a new uniquely named file with mode 0600 that can be used for temporary work and
+/// returns its open handle along with its path. The file is created under `$TMPDIR` when set else
+/// under `user::runtime_dir()` which falls back on `/tmp`. It is up to the calling code to ensure
+/// the file returned is properly cleaned up when done with.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let (mut f, path) = sys::tempfile("foo").unwrap();
+/// assert!(f.write_all(b"foobar").is_ok());
+/// assert_eq!(sys::readstring(&path).unwrap(), "foobar");
+/// assert!(sys::remove(&path).is_ok());
+/// ```
+pub fn tempfile<T: AsRef<str>>(prefix: T) -> FuResult<(File, PathBuf)> {
+    let root = user::temp_root()?;
+    loop {
+        let suffix: String = iter::repeat_with(fastrand::alphanumeric).take(8).collect();
+        let path = root.mash(format!("{}-{}", prefix.as_ref(), suffix));
+        match fs::OpenOptions::new().read(true).write(true).create_new(true).mode(0o600).open(&path) {
+            Ok(f) => return Ok((f, path)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// Create an empty file similar to the linux touch command or bump the access and modification
+/// times to now if it already exists. Handles path expansion and creates the parent directory
+/// as needed. Uses default file creation permissions 0o666 - umask usually ends up being 0o644.
 ///
 /// ### Examples
 /// ```
@@ -833,10 +2227,53 @@ pub fn symlink<T: AsRef<Path>, U: AsRef<Path>>(link: T, target: U) -> FuResult<P
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
 pub fn touch<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
+    let path = path.as_ref().abs()?;
+    if path.exists() {
+        let now = libc::timespec { tv_sec: 0, tv_nsec: libc::UTIME_NOW };
+        utimens(&path, &[now, now], true)?;
+    } else {
+        mkdir(path.dir()?)?;
+        File::create(&path)?;
+    }
+    Ok(path)
+}
+
+/// Create an empty file if it doesn't exist and set its modification time to the given `mtime`
+/// leaving the access time untouched. Handles path expansion and creates the parent directory.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+/// use std::time::{Duration, SystemTime};
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_touch_at");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+/// assert!(sys::touch_at(&tmpfile, mtime).is_ok());
+/// assert_eq!(tmpfile.metadata().unwrap().modified().unwrap(), mtime);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn touch_at<T: AsRef<Path>>(path: T, mtime: SystemTime) -> FuResult<PathBuf> {
     let path = path.as_ref().abs()?;
     if !path.exists() {
+        mkdir(path.dir()?)?;
         File::create(&path)?;
     }
+    let mtime = match mtime.duration_since(UNIX_EPOCH) {
+        Ok(x) => libc::timespec { tv_sec: x.as_secs() as libc::time_t, tv_nsec: x.subsec_nanos() as libc::c_long },
+
+        // Times before the epoch are negative seconds with positive nanoseconds
+        Err(err) => {
+            let x = err.duration();
+            match x.subsec_nanos() {
+                0 => libc::timespec { tv_sec: -(x.as_secs() as libc::time_t), tv_nsec: 0 },
+                n => libc::timespec { tv_sec: -(x.as_secs() as libc::time_t) - 1, tv_nsec: (1_000_000_000 - n) as libc::c_long },
+            }
+        },
+    };
+    let atime = libc::timespec { tv_sec: 0, tv_nsec: libc::UTIME_OMIT };
+    utimens(&path, &[atime, mtime], true)?;
     Ok(path)
 }
 
@@ -860,7 +2297,20 @@ pub fn touch_p<T: AsRef<Path>>(path: T, mode: u32) -> FuResult<PathBuf> {
     Ok(path)
 }
 
-/// Write `[u8]` data to a file which means `str` or `String`. Handles path expansion.
+/// Private implementation to set the access and modification `times` of `path` optionally
+/// following links.
+fn utimens<T: AsRef<Path>>(path: T, times: &[libc::timespec; 2], follow: bool) -> FuResult<()> {
+    let osstr = CString::new(path.as_ref().as_os_str().as_bytes())?;
+    let flags = if follow { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+    let ret = unsafe { libc::utimensat(libc::AT_FDCWD, osstr.as_ptr(), times.as_ptr(), flags) };
+    if ret != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// Write `[u8]` data to a file which means `str` or `String`, creating any missing parent
+/// directories as needed. Handles path expansion. Returns the number of bytes written.
 ///
 /// ### Examples
 /// ```
@@ -870,17 +2320,68 @@ pub fn touch_p<T: AsRef<Path>>(path: T, mode: u32) -> FuResult<PathBuf> {
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// let tmpfile = tmpdir.mash("file1");
 /// assert!(sys::mkdir(&tmpdir).is_ok());
-/// assert!(sys::write(&tmpfile, "this is a test").is_ok());
+/// assert_eq!(sys::write(&tmpfile, "this is a test").unwrap(), 14);
 /// assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is a test");
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
-pub fn write<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, data: U) -> FuResult<()> {
+pub fn write<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, data: U) -> FuResult<usize> {
     let path = path.as_ref().abs()?;
+    mkdir(path.dir()?)?;
     let mut f = File::create(path)?;
     f.write_all(data.as_ref())?;
 
     // f.sync_all() works better than f.flush()?
     f.sync_all()?;
+    Ok(data.as_ref().len())
+}
+
+/// Write `[u8]` data to a file atomically such that readers never see a partially written file.
+/// Handles path expansion. The data is written to a temp file in the same directory, synced to
+/// disk then renamed over the target. The mode of an existing target file is preserved and links
+/// are resolved via `canonicalize_lossy` so that the link target is replaced not the link.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_write_atomic");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write_atomic(&tmpfile, "this is a test").is_ok());
+/// assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is a test");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn write_atomic<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, data: U) -> FuResult<()> {
+    let path = path.as_ref().canonicalize_lossy()?;
+    let dir = path.dir()?;
+    let perms = fs::metadata(&path).ok().map(|x| x.permissions());
+
+    // Create a uniquely named temp file in the same directory to ensure the rename is atomic
+    let (tmpfile, mut f) = loop {
+        let suffix: String = iter::repeat_with(fastrand::alphanumeric).take(8).collect();
+        let tmpfile = dir.mash(format!(".{}.{}.tmp", path.base()?, suffix));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&tmpfile) {
+            Ok(f) => break (tmpfile, f),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err.into()),
+        }
+    };
+
+    // Remove the temp file if anything fails along the way
+    let guard = defer(|| {
+        let _ = fs::remove_file(&tmpfile);
+    });
+    f.write_all(data.as_ref())?;
+    if let Some(perms) = perms {
+        f.set_permissions(perms)?;
+    }
+    f.sync_all()?;
+    fs::rename(&tmpfile, &path)?;
+    guard.cancel();
+
+    // Sync the directory to persist the rename
+    File::open(&dir)?.sync_all()?;
     Ok(())
 }
 
@@ -952,6 +2453,7 @@ pub fn writelines_p<T: AsRef<Path>>(path: T, data: &[String], mode: u32) -> FuRe
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
 
     // Test setup
     fn setup() -> PathBuf {
@@ -965,6 +2467,106 @@ mod tests {
         assert_eq!(format!("{}", FileError::FailedToExtractString), "failed to extract string from file");
     }
 
+    #[test]
+    fn test_append() {
+        let tmpdir = setup().mash("file_append");
+        let tmpfile = tmpdir.mash("dir1/file1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // creates the file and parent directories
+        assert_eq!(sys::append(&tmpfile, "foo").unwrap(), 3);
+        assert_eq!(sys::append(&tmpfile, "bar\n").unwrap(), 4);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "foobar\n");
+        assert_eq!(fs::metadata(&tmpfile).unwrap().len(), 7);
+
+        // empty append leaves the file alone
+        assert_eq!(sys::append(&tmpfile, "").unwrap(), 0);
+        assert_eq!(fs::metadata(&tmpfile).unwrap().len(), 7);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_append_line_if_missing() {
+        let tmpdir = setup().mash("file_append_line_if_missing");
+        let tmpfile = tmpdir.mash("dir1/file1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // creates the file and parent directories
+        assert_eq!(sys::append_line_if_missing(&tmpfile, "foo=1").unwrap(), true);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "foo=1\n");
+
+        // second call is a no-op
+        assert_eq!(sys::append_line_if_missing(&tmpfile, "foo=1").unwrap(), false);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "foo=1\n");
+
+        // partial matches don't count and a missing trailing newline is added
+        assert!(sys::write(&tmpfile, "foo=12\r\nbar=2").is_ok());
+        assert_eq!(sys::append_line_if_missing(&tmpfile, "foo=1").unwrap(), true);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "foo=12\r\nbar=2\nfoo=1\n");
+
+        // line endings are ignored when comparing
+        assert_eq!(sys::append_line_if_missing(&tmpfile, "foo=12").unwrap(), false);
+        assert_eq!(sys::append_line_if_missing(&tmpfile, "bar=2").unwrap(), false);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_backup() {
+        use std::time::{Duration, SystemTime};
+        let tmpdir = setup().mash("file_backup");
+        let file1 = tmpdir.mash("file1.conf");
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::write_p(&file1, "foo", 0o600).is_ok());
+        assert!(sys::touch_at(&file1, mtime).is_ok());
+
+        // timestamped alongside the original preserving content, mode and mtime
+        let backup1 = sys::backup(&file1).unwrap();
+        assert_eq!(backup1.dir().unwrap(), tmpdir);
+        assert!(Regex::new(r"^file1\.conf\.\d{14}\.bak(\.1)?$").unwrap().is_match(&backup1.base().unwrap()));
+        assert_eq!(sys::readstring(&backup1).unwrap(), "foo");
+        assert_eq!(backup1.mode().unwrap(), 0o100600);
+        assert_eq!(fs::metadata(&backup1).unwrap().modified().unwrap(), mtime);
+
+        // never overwrites an existing backup
+        let backup2 = sys::backup(&file1).unwrap();
+        assert_ne!(backup2, backup1);
+        assert_eq!(sys::readstring(&backup2).unwrap(), "foo");
+
+        // not a file
+        assert_eq!(sys::backup(&tmpdir).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::is_not_file(&tmpdir)));
+        assert_eq!(sys::backup(tmpdir.mash("bogus")).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(tmpdir.mash("bogus"))));
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_backup_numbered() {
+        let tmpdir = setup().mash("file_backup_numbered");
+        let file1 = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::write_p(&file1, "foo", 0o640).is_ok());
+
+        // increments avoiding overwrites
+        assert_eq!(sys::backup_numbered(&file1).unwrap(), tmpdir.mash("file1.bak"));
+        assert!(sys::write(&file1, "bar").is_ok());
+        assert_eq!(sys::backup_numbered(&file1).unwrap(), tmpdir.mash("file1.bak.1"));
+        assert_eq!(sys::backup_numbered(&file1).unwrap(), tmpdir.mash("file1.bak.2"));
+        assert_eq!(sys::readstring(tmpdir.mash("file1.bak")).unwrap(), "foo");
+        assert_eq!(sys::readstring(tmpdir.mash("file1.bak.1")).unwrap(), "bar");
+        assert_eq!(tmpdir.mash("file1.bak.2").mode().unwrap(), 0o100640);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_chmod() {
         let tmpdir = setup().mash("file_chmod");
@@ -1090,6 +2692,154 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_chmod_tree() {
+        let tmpdir = setup().mash("file_chmod_tree");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = dir1.mash("dir2");
+        let file1 = tmpdir.mash("file1");
+        let file2 = dir2.mash("file2");
+        let target = setup().mash("file_chmod_tree_target");
+        let link1 = dir1.mash("link1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::remove(&target).is_ok());
+        assert!(sys::mkdir_p(&dir2, 0o700).is_ok());
+        assert!(sys::touch_p(&file1, 0o600).is_ok());
+        assert!(sys::touch_p(&file2, 0o777).is_ok());
+        assert!(sys::touch_p(&target, 0o600).is_ok());
+        assert!(sys::symlink(&link1, &target).is_ok());
+
+        // doesn't exist
+        assert_eq!(sys::chmod_tree(tmpdir.mash("foo"), 0o755, 0o644).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(tmpdir.mash("foo"))));
+
+        // directories and files get their own modes while links are untouched
+        assert!(sys::chmod_tree(&tmpdir, 0o750, 0o640).is_ok());
+        assert_eq!(tmpdir.mode().unwrap(), 0o40750);
+        assert_eq!(dir1.mode().unwrap(), 0o40750);
+        assert_eq!(dir2.mode().unwrap(), 0o40750);
+        assert_eq!(file1.mode().unwrap(), 0o100640);
+        assert_eq!(file2.mode().unwrap(), 0o100640);
+        assert_eq!(link1.is_symlink(), true);
+        assert_eq!(target.mode().unwrap(), 0o100600);
+
+        // restrictive directory modes are applied last
+        assert!(sys::chmod_tree(&tmpdir, 0o500, 0o400).is_ok());
+        assert_eq!(dir2.mode().unwrap(), 0o40500);
+        assert_eq!(file2.mode().unwrap(), 0o100400);
+
+        // a file root just gets the file mode
+        assert!(sys::chmod_tree(&tmpdir, 0o755, 0o644).is_ok());
+        assert!(sys::chmod_tree(&file1, 0o755, 0o600).is_ok());
+        assert_eq!(file1.mode().unwrap(), 0o100600);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::remove(&target).is_ok());
+    }
+
+    #[test]
+    fn test_chmod_sym() {
+        let tmpdir = setup().mash("file_chmod_sym");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir_p(&dir1, 0o750).is_ok());
+        assert!(sys::touch_p(&file1, 0o644).is_ok());
+
+        assert!(sys::chmod_sym(&file1, "+x").is_ok());
+        assert_eq!(file1.mode().unwrap(), 0o100755);
+        assert!(sys::chmod_sym(&file1, "u=rw,go-rwx").is_ok());
+        assert_eq!(file1.mode().unwrap(), 0o100600);
+        assert!(sys::chmod_sym(&dir1, "o+X").is_ok());
+        assert_eq!(dir1.mode().unwrap(), 0o40751);
+
+        // globbing applies relative to each mode
+        assert!(sys::chmod_sym(tmpdir.mash("*1"), "g+w").is_ok());
+        assert_eq!(dir1.mode().unwrap(), 0o40771);
+        assert_eq!(file1.mode().unwrap(), 0o100620);
+
+        // invalid spec leaves the mode untouched
+        assert_eq!(sys::chmod_sym(&file1, "u+q").unwrap_err().downcast_ref::<FileError>(), Some(&FileError::invalid_mode("u+q")));
+        assert_eq!(file1.mode().unwrap(), 0o100620);
+
+        // doesn't exist
+        assert!(sys::chmod_sym(tmpdir.mash("foo"), "+x").unwrap_err().is::<PathError>());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_chown() {
+        let tmpdir = setup().mash("file_chown");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = dir1.mash("file1");
+        let link1 = dir1.mash("link1");
+        let (uid, gid) = (user::getuid(), user::getgid());
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+        assert!(sys::symlink(&link1, "file1").is_ok());
+
+        // chown to the current ids is a no-op that should still succeed
+        assert!(sys::chown(&file1, uid, gid).is_ok());
+        assert!(sys::lchown(&link1, uid, gid).is_ok());
+        assert!(sys::chown_all(&tmpdir, uid, gid).is_ok());
+        assert!(sys::chown(tmpdir.mash("dir*"), uid, gid).is_ok());
+        assert_eq!((file1.uid().unwrap(), file1.gid().unwrap()), (uid, gid));
+
+        // chown by names
+        let user = user::name().unwrap();
+        let group = group::current().unwrap().name;
+        assert!(sys::chown_named(&file1, &user, &group).is_ok());
+        assert!(sys::chown_named(&file1, "fungus-bogus", &group).unwrap_err().is::<UserError>());
+        assert!(sys::chown_named(&file1, &user, "fungus-bogus").unwrap_err().is::<GroupError>());
+
+        // doesn't exist
+        let file2 = tmpdir.mash("file2");
+        assert_eq!(sys::chown(&file2, uid, gid).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(&file2)));
+        assert_eq!(sys::chown_all(&file2, uid, gid).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(&file2)));
+        assert_eq!(sys::lchown(&file2, uid, gid).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(&file2)));
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copy_all_with() {
+        let tmpdir = setup().mash("file_copy_all_with");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+        let file1 = dir1.mash("file1");
+        let file2 = dir1.mash("sub/file2");
+        let link1 = dir1.mash("link1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(dir1.mash("sub")).is_ok());
+        assert!(sys::write(&file1, "foo").is_ok());
+        assert!(sys::write(&file2, "foobar").is_ok());
+        assert!(sys::symlink(&link1, "file1").is_ok());
+
+        // invoked once per regular file with increasing totals
+        let mut progress = vec![];
+        assert!(sys::copy_all_with(&dir1, &dir2, |path, copied, total| progress.push((path.to_path_buf(), copied, total))).is_ok());
+        assert_eq!(progress, vec![(file1, 3, 9), (file2, 9, 9)]);
+
+        // links are recreated rather than followed
+        assert_eq!(dir2.mash("link1").is_symlink(), true);
+        assert_eq!(dir2.mash("link1").readlink().unwrap(), PathBuf::from("file1"));
+        assert_eq!(sys::readstring(dir2.mash("sub/file2")).unwrap(), "foobar");
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_copy_empty() {
         let tmpdir = setup().mash("file_copy_empty");
@@ -1220,6 +2970,77 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_copy_with() {
+        use std::time::{Duration, SystemTime};
+        let tmpdir = setup().mash("file_copy_with");
+        let srcdir = tmpdir.mash("src");
+        let file1 = srcdir.mash("file1");
+        let link1 = srcdir.mash("link1");
+        let dstdir = tmpdir.mash("dst");
+        let dstfile1 = dstdir.mash("file1");
+        let dstlink1 = dstdir.mash("link1");
+        let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let new = SystemTime::UNIX_EPOCH + Duration::from_secs(2_000_000_000);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "foo").is_ok());
+        assert!(sys::chmod(&file1, 0o700).is_ok());
+        assert!(sys::touch_at(&file1, old).is_ok());
+        assert!(sys::symlink(&link1, "file1").is_ok());
+
+        // clone with links recreated, mode and times preserved
+        let opts = sys::CopyOpts { preserve_mode: true, preserve_times: true, ..Default::default() };
+        assert_eq!(sys::copy_with(&srcdir, &dstdir, opts.clone()).unwrap(), dstdir);
+        assert_eq!(sys::readstring(&dstfile1).unwrap(), "foo");
+        assert_eq!(dstfile1.mode().unwrap(), 0o100700);
+        assert_eq!(dstfile1.metadata().unwrap().modified().unwrap(), old);
+        assert_eq!(dstlink1.is_symlink(), true);
+        assert_eq!(dstlink1.readlink().unwrap(), PathBuf::from("file1"));
+
+        // follow links copies the target instead
+        assert!(sys::remove_all(&dstdir).is_ok());
+        let opts = sys::CopyOpts { follow_symlinks: true, ..Default::default() };
+        assert!(sys::copy_with(&srcdir, &dstdir, opts).is_ok());
+        assert_eq!(dstlink1.is_symlink(), false);
+        assert_eq!(sys::readstring(&dstlink1).unwrap(), "foo");
+        assert_eq!(dstfile1.mode().unwrap() & 0o111, 0);
+        assert!(dstfile1.metadata().unwrap().modified().unwrap() > old);
+
+        // a root link is recreated unless following links
+        assert!(sys::remove_all(&dstdir).is_ok());
+        assert!(sys::copy_with(&link1, &dstlink1, sys::CopyOpts::default()).is_ok());
+        assert_eq!(dstlink1.is_symlink(), true);
+        assert!(sys::remove_all(&dstdir).is_ok());
+        assert!(sys::copy_with(&link1, &dstlink1, sys::CopyOpts { follow_symlinks: true, ..Default::default() }).is_ok());
+        assert_eq!(dstlink1.is_symlink(), false);
+        assert_eq!(sys::readstring(&dstlink1).unwrap(), "foo");
+
+        // never overwrites
+        assert!(sys::write(&dstfile1, "bar").is_ok());
+        let opts = sys::CopyOpts { overwrite: sys::Overwrite::Never, ..Default::default() };
+        assert!(sys::copy_with(&file1, &dstfile1, opts).is_ok());
+        assert_eq!(sys::readstring(&dstfile1).unwrap(), "bar");
+
+        // if newer skips a newer destination and replaces an older one
+        let opts = sys::CopyOpts { overwrite: sys::Overwrite::IfNewer, ..Default::default() };
+        assert!(sys::touch_at(&dstfile1, new).is_ok());
+        assert!(sys::copy_with(&file1, &dstfile1, opts.clone()).is_ok());
+        assert_eq!(sys::readstring(&dstfile1).unwrap(), "bar");
+        assert!(sys::touch_at(&file1, new + Duration::from_secs(1)).is_ok());
+        assert!(sys::copy_with(&file1, &dstfile1, opts).is_ok());
+        assert_eq!(sys::readstring(&dstfile1).unwrap(), "foo");
+
+        // always overwrites even a read only destination
+        assert!(sys::write(&dstfile1, "bar").is_ok());
+        assert!(sys::chmod(&dstfile1, 0o444).is_ok());
+        assert!(sys::touch_at(&dstfile1, new + Duration::from_secs(10)).is_ok());
+        assert!(sys::copy_with(&file1, &dstfile1, sys::CopyOpts::default()).is_ok());
+        assert_eq!(sys::readstring(&dstfile1).unwrap(), "foo");
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_copyfile() {
         let tmpdir = setup().mash("file_copyfile");
@@ -1254,49 +3075,242 @@ mod tests {
         assert_eq!(tmpdir.mode().unwrap(), file3.dir().unwrap().mode().unwrap());
         assert_eq!(file1.mode().unwrap(), file3.mode().unwrap());
 
-        // empty destination path
-        assert!(sys::copyfile(&file1, "").is_err());
+        // empty destination path
+        assert!(sys::copyfile(&file1, "").is_err());
+
+        // empty source path
+        assert!(sys::copyfile("", &file3).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copyfile_p() {
+        let tmpdir = setup().mash("file_copyfile_p");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // copy to same dir
+        assert!(sys::touch_p(&file1, 0o644).is_ok());
+        assert!(sys::copyfile_p(&file1, &file2).unwrap().mode(0o555).copy().is_ok());
+        assert_eq!(file2.mode().unwrap(), 0o100555);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_count_bytes_and_lines() {
+        let tmpdir = setup().mash("file_count_bytes_and_lines");
+        let file1 = tmpdir.mash("file1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::count_bytes(&file1).is_err());
+        assert!(sys::count_lines(&file1).is_err());
+
+        // empty
+        assert!(sys::write(&file1, "").is_ok());
+        assert_eq!(sys::count_bytes(&file1).unwrap(), 0);
+        assert_eq!(sys::count_lines(&file1).unwrap(), 0);
+
+        // trailing newline
+        assert!(sys::write(&file1, "one\ntwo\nthree\n").is_ok());
+        assert_eq!(sys::count_bytes(&file1).unwrap(), 14);
+        assert_eq!(sys::count_lines(&file1).unwrap(), 3);
+
+        // without trailing newline
+        assert!(sys::write(&file1, "one\ntwo\nthree").is_ok());
+        assert_eq!(sys::count_bytes(&file1).unwrap(), 13);
+        assert_eq!(sys::count_lines(&file1).unwrap(), 3);
+
+        // blank lines and a single partial line
+        assert!(sys::write(&file1, "\n\n").is_ok());
+        assert_eq!(sys::count_lines(&file1).unwrap(), 2);
+        assert!(sys::write(&file1, "one").is_ok());
+        assert_eq!(sys::count_lines(&file1).unwrap(), 1);
+
+        // spanning multiple buffer fills
+        let data = "foobar\n".repeat(20_000);
+        assert!(sys::write(&file1, &data).is_ok());
+        assert_eq!(sys::count_bytes(&file1).unwrap(), data.len() as u64);
+        assert_eq!(sys::count_lines(&file1).unwrap(), 20_000);
+
+        // only regular files are supported
+        let fifo1 = tmpdir.mash("fifo1");
+        let fifo = CString::new(fifo1.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o644) }, 0);
+        assert_eq!(sys::count_bytes(&fifo1).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::is_not_file(&fifo1)));
+        assert_eq!(sys::count_bytes(&tmpdir).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::is_not_file(&tmpdir)));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_digest() {
+        let tmpdir = setup().mash("file_digest");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // test
+        assert!(sys::write(&file1, "this is a test").is_ok());
+        assert!(sys::copyfile(&file1, &file2).is_ok());
+        assert_iter_eq(sys::digest(&file1).unwrap(), sys::digest(&file2).unwrap());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_digest_dir() {
+        let tmpdir = setup().mash("file_digest_dir");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        for dir in vec![&dir1, &dir2] {
+            assert!(sys::write(dir.mash("file1"), "this is a test").is_ok());
+            assert!(sys::write(dir.mash("sub/file2"), "another test").is_ok());
+            assert!(sys::mkdir(dir.mash("empty")).is_ok());
+            assert!(sys::symlink(dir.mash("link1"), "file1").is_ok());
+        }
 
-        // empty source path
-        assert!(sys::copyfile("", &file3).is_err());
+        // identical trees hash equal
+        let digest = sys::digest_dir(&dir1).unwrap();
+        assert_eq!(digest.len(), 64);
+        assert_eq!(sys::digest_dir(&dir2).unwrap(), digest);
+
+        // a single byte change alters the hash
+        assert!(sys::write(dir2.mash("sub/file2"), "another tesT").is_ok());
+        assert_ne!(sys::digest_dir(&dir2).unwrap(), digest);
+        assert!(sys::write(dir2.mash("sub/file2"), "another test").is_ok());
+        assert_eq!(sys::digest_dir(&dir2).unwrap(), digest);
+
+        // empty directories contribute their path
+        assert!(sys::remove(dir2.mash("empty")).is_ok());
+        assert_ne!(sys::digest_dir(&dir2).unwrap(), digest);
+        assert!(sys::mkdir(dir2.mash("empty2")).is_ok());
+        assert_ne!(sys::digest_dir(&dir2).unwrap(), digest);
+        assert!(sys::remove(dir2.mash("empty2")).is_ok());
+        assert!(sys::mkdir(dir2.mash("empty")).is_ok());
+        assert_eq!(sys::digest_dir(&dir2).unwrap(), digest);
+
+        // mode bits are included
+        assert!(sys::chmod(dir2.mash("file1"), 0o600).is_ok());
+        assert_ne!(sys::digest_dir(&dir2).unwrap(), digest);
+
+        // special files are hashed by type and mode without being opened
+        let fifo = CString::new(dir1.mash("fifo1").as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o644) }, 0);
+        let with_fifo = sys::digest_dir(&dir1).unwrap();
+        assert_ne!(with_fifo, digest);
+        assert_eq!(sys::digest_dir(&dir1).unwrap(), with_fifo);
+        assert!(sys::remove(dir1.mash("fifo1")).is_ok());
+        assert_eq!(sys::digest_dir(&dir1).unwrap(), digest);
+
+        // not a directory
+        assert_eq!(sys::digest_dir(dir1.mash("file1")).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::is_not_dir(dir1.mash("file1"))));
 
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
     #[test]
-    fn test_copyfile_p() {
-        let tmpdir = setup().mash("file_copyfile_p");
+    fn test_digest_reader() {
+        let tmpdir = setup().mash("file_digest_reader");
         let file1 = tmpdir.mash("file1");
-        let file2 = tmpdir.mash("file2");
+        let data = "a".repeat(100_000);
 
         // setup
         assert!(sys::remove_all(&tmpdir).is_ok());
-        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&file1, &data).is_ok());
+
+        // matches the file based digests over the same bytes
+        let mut reader = sys::DigestReader::new(data.as_bytes());
+        let mut buf = vec![];
+        assert_eq!(reader.read_to_end(&mut buf).unwrap(), data.len());
+        assert_eq!(buf, data.as_bytes());
+        assert_eq!(reader.finalize(), sys::digest(&file1).unwrap());
+        for algo in vec![sys::DigestAlgo::Blake2, sys::DigestAlgo::Sha256, sys::DigestAlgo::Sha512] {
+            let mut reader = sys::DigestReader::with_algo(data.as_bytes(), algo);
+            assert_eq!(io::copy(&mut reader, &mut io::sink()).unwrap(), data.len() as u64);
+            assert_eq!(reader.finalize(), sys::digest_with(&file1, algo).unwrap());
+        }
 
-        // copy to same dir
-        assert!(sys::touch_p(&file1, 0o644).is_ok());
-        assert!(sys::copyfile_p(&file1, &file2).unwrap().mode(0o555).copy().is_ok());
-        assert_eq!(file2.mode().unwrap(), 0o100555);
+        // nothing read
+        let reader = sys::DigestReader::with_algo(data.as_bytes(), sys::DigestAlgo::Sha256);
+        assert_eq!(hex::encode(reader.finalize()), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
 
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
     #[test]
-    fn test_digest() {
-        let tmpdir = setup().mash("file_digest");
+    fn test_digest_with() {
+        let tmpdir = setup().mash("file_digest_with");
+        let empty = tmpdir.mash("empty");
         let file1 = tmpdir.mash("file1");
         let file2 = tmpdir.mash("file2");
 
         // setup
         assert!(sys::remove_all(&tmpdir).is_ok());
         assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::touch(&empty).is_ok());
+        assert!(sys::write(&file1, "abc").is_ok());
+        assert!(sys::write(&file2, "a".repeat(100_000)).is_ok());
 
-        // test
-        assert!(sys::write(&file1, "this is a test").is_ok());
-        assert!(sys::copyfile(&file1, &file2).is_ok());
-        assert_iter_eq(sys::digest(&file1).unwrap(), sys::digest(&file2).unwrap());
+        // known vectors for an empty file
+        assert_eq!(hex::encode(sys::digest_sha256(&empty).unwrap()), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hex::encode(sys::digest_with(&empty, sys::DigestAlgo::Sha512).unwrap()), "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e");
+        assert_eq!(hex::encode(sys::digest_with(&empty, sys::DigestAlgo::Blake2).unwrap()), "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce");
+
+        // known vectors for a small file
+        assert_eq!(hex::encode(sys::digest_sha256(&file1).unwrap()), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hex::encode(sys::digest_with(&file1, sys::DigestAlgo::Sha512).unwrap()), "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f");
+        assert_eq!(sys::digest_with(&file1, sys::DigestAlgo::Blake2).unwrap(), sys::digest(&file1).unwrap());
+
+        // chunked streaming matches the in memory digest
+        assert_eq!(sys::digest_sha256(&file2).unwrap(), <sha2::Sha256 as sha2::Digest>::digest(&sys::readbytes(&file2).unwrap()).to_vec());
+
+        // doesn't exist
+        assert!(sys::digest_sha256(tmpdir.mash("foo")).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_extract() {
+        let tmpdir = setup().mash("file_extract");
+        let file1 = tmpdir.mash("file1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "# test\npkgbase=linux\npkgver=5.4.8.arch1\npkgrel=1\npkgver=5.5.0.arch1\n").is_ok());
+
+        // first match
+        let rx = Regex::new(r"^pkgver=(\d+\.\d+\.\d+).*").unwrap();
+        assert_eq!(sys::extract(&file1, &rx).unwrap(), "5.4.8");
+
+        // all matches
+        assert_eq!(sys::extract_all(&file1, &rx).unwrap(), vec!["5.4.8", "5.5.0"]);
+
+        // no match
+        let rx = Regex::new(r"^foo=(.*)").unwrap();
+        assert_eq!(sys::extract(&file1, &rx).unwrap_err().downcast_ref::<FileError>(), Some(&FileError::FailedToExtractString));
+        assert_eq!(sys::extract_all(&file1, &rx).unwrap_err().downcast_ref::<FileError>(), Some(&FileError::FailedToExtractString));
+
+        // no capture group
+        let rx = Regex::new(r"^pkgver=.*").unwrap();
+        assert_eq!(sys::extract(&file1, &rx).unwrap_err().downcast_ref::<FileError>(), Some(&FileError::FailedToExtractString));
+        assert_eq!(sys::extract_all(&file1, &rx).unwrap_err().downcast_ref::<FileError>(), Some(&FileError::FailedToExtractString));
 
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
@@ -1347,6 +3361,154 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_file_contains_line() {
+        let tmpdir = setup().mash("file_file_contains_line");
+        let tmpfile = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&tmpfile, "pkgname=linux\r\npkgver=5.4.8\r\n").is_ok());
+
+        // line endings are stripped before matching
+        assert_eq!(sys::file_contains_line(&tmpfile, &Regex::new(r"^pkgver=5\.4\.8$").unwrap()).unwrap(), true);
+        assert_eq!(sys::file_contains_line(&tmpfile, &Regex::new(r"linux").unwrap()).unwrap(), true);
+        assert_eq!(sys::file_contains_line(&tmpfile, &Regex::new(r"^pkgrel=").unwrap()).unwrap(), false);
+
+        // doesn't exist
+        assert!(sys::file_contains_line(tmpdir.mash("bogus"), &Regex::new(r"foo").unwrap()).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_hardlink() {
+        let tmpdir = setup().mash("file_hardlink");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("dir1/file2");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // source doesn't exist
+        assert!(sys::hardlink(&file1, &file2).is_err());
+
+        // link shares the same inode
+        assert!(sys::write(&file1, "foo").is_ok());
+        assert_eq!(sys::hardlink(&file1, &file2).unwrap(), file2);
+        assert_eq!(sys::same_file(&file1, &file2).unwrap(), true);
+        assert!(sys::append(&file2, "bar").is_ok());
+        assert_eq!(sys::readstring(&file1).unwrap(), "foobar");
+
+        // destination exists already
+        assert_eq!(sys::hardlink(&file1, &file2).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::exists_already(&file2)));
+
+        // across devices
+        let shm = PathBuf::from("/dev/shm");
+        if shm.is_dir() && sys::metadata(&shm).unwrap().dev() != sys::metadata(&file1).unwrap().dev() {
+            let file3 = shm.mash(format!("fungus_file_hardlink_{}", std::process::id()));
+            assert_eq!(sys::hardlink(&file1, &file3).unwrap_err().downcast_ref::<FileError>(), Some(&FileError::cross_device(&file1, &file3)));
+        }
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_hardlink_all() {
+        let tmpdir = setup().mash("file_hardlink_all");
+        let src = tmpdir.mash("src");
+        let dst = tmpdir.mash("dst");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // source isn't a directory
+        assert!(sys::hardlink_all(&src, &dst).is_err());
+
+        // mirror the tree
+        assert!(sys::write(src.mash("file1"), "file1").is_ok());
+        assert!(sys::write(src.mash("dir1/file2"), "file2").is_ok());
+        assert!(sys::mkdir(src.mash("dir2")).is_ok());
+        assert!(sys::symlink(src.mash("link1"), "dir1/file2").is_ok());
+        assert_eq!(sys::hardlink_all(&src, &dst).unwrap(), dst);
+        assert_eq!(sys::same_file(src.mash("file1"), dst.mash("file1")).unwrap(), true);
+        assert_eq!(sys::same_file(src.mash("dir1/file2"), dst.mash("dir1/file2")).unwrap(), true);
+        assert_eq!(sys::same_file(src.mash("dir1"), dst.mash("dir1")).unwrap(), false);
+        assert_eq!(dst.mash("dir2").is_dir(), true);
+        assert_eq!(dst.mash("link1").readlink().unwrap(), PathBuf::from("dir1/file2"));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_insert_line_after() {
+        let tmpdir = setup().mash("file_insert_line_after");
+        let tmpfile = tmpdir.mash("file1");
+        let anchor = Regex::new(r"^\[options\]$").unwrap();
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&tmpfile, "[main]\nfoo=1\n[options]\nbar=2\n[options]\n").is_ok());
+
+        // inserted after the first matching line only
+        assert_eq!(sys::insert_line_after(&tmpfile, &anchor, "baz=3").unwrap(), true);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "[main]\nfoo=1\n[options]\nbaz=3\nbar=2\n[options]\n");
+
+        // second call is a no-op
+        assert_eq!(sys::insert_line_after(&tmpfile, &anchor, "baz=3").unwrap(), false);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "[main]\nfoo=1\n[options]\nbaz=3\nbar=2\n[options]\n");
+
+        // no matching anchor
+        assert_eq!(sys::insert_line_after(&tmpfile, &Regex::new(r"^\[bogus\]$").unwrap(), "qux=4").unwrap(), false);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "[main]\nfoo=1\n[options]\nbaz=3\nbar=2\n[options]\n");
+
+        // anchor's line ending is used
+        assert!(sys::write(&tmpfile, "[options]\r\nfoo=1\r\n").is_ok());
+        assert_eq!(sys::insert_line_after(&tmpfile, &anchor, "bar=2").unwrap(), true);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "[options]\r\nbar=2\r\nfoo=1\r\n");
+
+        // anchor on the last line without a newline
+        assert!(sys::write(&tmpfile, "foo=1\n[options]").is_ok());
+        assert_eq!(sys::insert_line_after(&tmpfile, &anchor, "bar=2").unwrap(), true);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "foo=1\n[options]\nbar=2\n");
+
+        // doesn't exist
+        assert!(sys::insert_line_after(tmpdir.mash("bogus"), &anchor, "foo").is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_mkdir_m() {
+        let tmpdir = setup().mash("file_mkdir_m");
+        let dir1 = tmpdir.mash("dir1/dir2/dir3");
+        let file1 = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // nested with missing parents
+        assert!(sys::mkdir_m(&dir1, 0o700).is_ok());
+        assert_eq!(dir1.mode().unwrap(), 0o40700);
+        assert_eq!(dir1.dir().unwrap().is_dir(), true);
+
+        // existing directory gets the new mode
+        assert!(sys::mkdir_m(&dir1, 0o750).is_ok());
+        assert_eq!(dir1.mode().unwrap(), 0o40750);
+
+        // bits normally stripped by the umask are kept
+        assert!(sys::mkdir_m(&dir1, 0o777).is_ok());
+        assert_eq!(dir1.mode().unwrap(), 0o40777);
+
+        // existing file is left alone
+        assert!(sys::write_p(&file1, "foo", 0o644).is_ok());
+        assert_eq!(sys::mkdir_m(&file1, 0o700).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::is_not_dir(&file1)));
+        assert_eq!(file1.mode().unwrap(), 0o100644);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_mkdir_p() {
         let tmpdir = setup().mash("file_mkdir_p");
@@ -1366,6 +3528,115 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_move_all() {
+        let tmpdir = setup().mash("file_move_all");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+        let dir3 = tmpdir.mash("dir3");
+        let file1 = dir1.mash("file1");
+        let link1 = dir1.mash("link1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::write(&file1, "foo").is_ok());
+        assert!(sys::symlink(&link1, "file1").is_ok());
+
+        // doesn't exist
+        assert_eq!(sys::move_all(tmpdir.mash("foo"), &dir2).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(tmpdir.mash("foo"))));
+
+        // move dir1 to dir2
+        assert_eq!(sys::move_all(&dir1, &dir2).unwrap(), dir2);
+        assert_eq!(dir1.exists(), false);
+        assert_eq!(sys::readstring(dir2.mash("file1")).unwrap(), "foo");
+        assert_eq!(dir2.mash("link1").readlink().unwrap(), PathBuf::from("file1"));
+
+        // move dir2 into existing dir3
+        assert!(sys::mkdir(&dir3).is_ok());
+        assert_eq!(sys::move_all(&dir2, &dir3).unwrap(), dir3.mash("dir2"));
+        assert_eq!(dir2.exists(), false);
+        assert_eq!(dir3.mash("dir2/file1").exists(), true);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_move_copy() {
+        let tmpdir = setup().mash("file_move_copy");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+        let file1 = dir1.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let link1 = dir1.mash("link1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::write_p(&file1, "foo", 0o600).is_ok());
+        assert!(sys::symlink(&link1, "file1").is_ok());
+        assert!(sys::chmod(&dir1, 0o555).is_ok());
+        let file1_meta = fs::metadata(&file1).unwrap();
+        let dir1_meta = fs::metadata(&dir1).unwrap();
+
+        // force the fallback used when a rename crosses filesystems
+        assert!(super::move_copy(&dir1, &dir2).is_ok());
+        assert_eq!(dir1.exists(), false);
+        assert_eq!(sys::readstring(dir2.mash("file1")).unwrap(), "foo");
+        assert_eq!(dir2.mash("link1").readlink().unwrap(), PathBuf::from("file1"));
+
+        // permissions and times are preserved
+        let meta = fs::metadata(dir2.mash("file1")).unwrap();
+        assert_eq!(meta.mode(), file1_meta.mode());
+        assert_eq!((meta.mtime(), meta.mtime_nsec()), (file1_meta.mtime(), file1_meta.mtime_nsec()));
+        let meta = fs::metadata(&dir2).unwrap();
+        assert_eq!(meta.mode(), dir1_meta.mode());
+        assert_eq!((meta.mtime(), meta.mtime_nsec()), (dir1_meta.mtime(), dir1_meta.mtime_nsec()));
+
+        // single file
+        let file1 = dir2.mash("file1");
+        assert!(sys::chmod(&dir2, 0o755).is_ok());
+        let file1_meta = fs::metadata(&file1).unwrap();
+        assert!(super::move_copy(&file1, &file2).is_ok());
+        assert_eq!(file1.exists(), false);
+        let meta = fs::metadata(&file2).unwrap();
+        assert_eq!(meta.mode(), file1_meta.mode());
+        assert_eq!((meta.mtime(), meta.mtime_nsec()), (file1_meta.mtime(), file1_meta.mtime_nsec()));
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_move_file() {
+        let tmpdir = setup().mash("file_move_file");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("dir2/file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::write(&file1, "foo").is_ok());
+
+        // directories are rejected
+        assert_eq!(sys::move_file(&dir1, &file2).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::is_not_file(&dir1)));
+
+        // move file1 creating the destination's parent
+        assert_eq!(sys::move_file(&file1, &file2).unwrap(), file2);
+        assert_eq!(file1.exists(), false);
+        assert_eq!(sys::readstring(&file2).unwrap(), "foo");
+
+        // move file2 into existing dir1
+        assert_eq!(sys::move_file(&file2, &dir1).unwrap(), dir1.mash("file2"));
+        assert_eq!(file2.exists(), false);
+        assert_eq!(sys::readstring(dir1.mash("file2")).unwrap(), "foo");
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_move_p() {
         let tmpdir = setup().mash("file_move_p");
@@ -1411,6 +3682,66 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_parse_mode() {
+        // add execute
+        assert_eq!(sys::parse_mode(0o644, "+x").unwrap(), 0o755);
+        assert_eq!(sys::parse_mode(0o644, "u+x").unwrap(), 0o744);
+        assert_eq!(sys::parse_mode(0o644, "a+x").unwrap(), 0o755);
+        assert_eq!(sys::parse_mode(0o644, "ug+x").unwrap(), 0o754);
+
+        // set exactly
+        assert_eq!(sys::parse_mode(0o755, "u=rw").unwrap(), 0o655);
+        assert_eq!(sys::parse_mode(0o777, "a=r").unwrap(), 0o444);
+        assert_eq!(sys::parse_mode(0o777, "go=").unwrap(), 0o700);
+
+        // clear bits
+        assert_eq!(sys::parse_mode(0o777, "go-w").unwrap(), 0o755);
+        assert_eq!(sys::parse_mode(0o777, "-rwx").unwrap(), 0o000);
+        assert_eq!(sys::parse_mode(0o644, "o-x").unwrap(), 0o644);
+
+        // multiple clauses and operations
+        assert_eq!(sys::parse_mode(0o600, "u+x,g+r,o+r").unwrap(), 0o744);
+        assert_eq!(sys::parse_mode(0o644, "u+x-w,go-r").unwrap(), 0o500);
+
+        // conditional execute, setuid/setgid and sticky
+        assert_eq!(sys::parse_mode(0o100644, "a+X").unwrap(), 0o100644);
+        assert_eq!(sys::parse_mode(0o100744, "a+X").unwrap(), 0o100755);
+        assert_eq!(sys::parse_mode(0o40700, "go+X").unwrap(), 0o40711);
+        assert_eq!(sys::parse_mode(0o755, "u+s").unwrap(), 0o4755);
+        assert_eq!(sys::parse_mode(0o755, "g+s").unwrap(), 0o2755);
+        assert_eq!(sys::parse_mode(0o777, "+t").unwrap(), 0o1777);
+        assert_eq!(sys::parse_mode(0o4755, "u-s").unwrap(), 0o755);
+
+        // invalid
+        assert_eq!(sys::parse_mode(0o644, "").unwrap_err().downcast_ref::<FileError>(), Some(&FileError::invalid_mode("")));
+        assert_eq!(sys::parse_mode(0o644, "u").unwrap_err().downcast_ref::<FileError>(), Some(&FileError::invalid_mode("u")));
+        assert_eq!(sys::parse_mode(0o644, "u+q").unwrap_err().downcast_ref::<FileError>(), Some(&FileError::invalid_mode("u+q")));
+        assert_eq!(sys::parse_mode(0o644, "z+x").unwrap_err().downcast_ref::<FileError>(), Some(&FileError::invalid_mode("z+x")));
+        assert_eq!(sys::parse_mode(0o644, "u+x,").unwrap_err().downcast_ref::<FileError>(), Some(&FileError::invalid_mode("u+x,")));
+    }
+
+    #[test]
+    fn test_read_string_limited() {
+        let tmpdir = setup().mash("file_read_string_limited");
+        let tmpfile = tmpdir.mash("file1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::read_string_limited(&tmpfile, 10).is_err());
+
+        // within the limit
+        assert!(sys::write(&tmpfile, "foobar").is_ok());
+        assert_eq!(sys::read_string_limited(&tmpfile, 6).unwrap(), "foobar");
+        assert_eq!(sys::read_string_limited(&tmpfile, 1024).unwrap(), "foobar");
+
+        // above the limit
+        assert_eq!(sys::read_string_limited(&tmpfile, 5).unwrap_err().downcast_ref::<FileError>(), Some(&FileError::too_large(6, 5)));
+
+        // special files without a size are limited while reading
+        assert_eq!(sys::read_string_limited("/dev/zero", 8).unwrap_err().downcast_ref::<FileError>(), Some(&FileError::too_large(9, 8)));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_readbytes() {
         let tmpdir = setup().mash("file_readbytes");
@@ -1445,6 +3776,32 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_readlines_iter() {
+        let tmpdir = setup().mash("file_readlines_iter");
+        let tmpfile = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&tmpfile, "foo\n\nbar\n  \nbaz\n").is_ok());
+
+        // all lines
+        assert_eq!(sys::readlines_iter(&tmpfile).unwrap().count(), 5);
+        assert_eq!(sys::readlines_iter(&tmpfile).unwrap().collect::<FuResult<Vec<String>>>().unwrap(), vec!["foo", "", "bar", "  ", "baz"]);
+
+        // filter out blank lines
+        let lines = sys::readlines_filtered(&tmpfile, |x| !x.trim().is_empty()).unwrap();
+        assert_eq!(lines.collect::<FuResult<Vec<String>>>().unwrap(), vec!["foo", "bar", "baz"]);
+
+        // doesn't exist
+        assert!(sys::readlines_iter(tmpdir.mash("bogus")).is_err());
+        assert!(sys::readlines_filtered(tmpdir.mash("bogus"), |_| true).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_readlines_p() {
         let tmpdir = setup().mash("file_readlines_p");
@@ -1495,6 +3852,26 @@ mod tests {
         assert_eq!(tmpfile.exists(), true);
         assert!(sys::remove(&tmpfile).is_ok());
         assert_eq!(tmpfile.exists(), false);
+
+        // Won't remove a non-empty directory
+        assert!(sys::touch(tmpdir.mash("file1")).is_ok());
+        assert!(sys::remove(&tmpdir).is_err());
+        assert_eq!(tmpdir.mash("file1").exists(), true);
+
+        // Removes links rather than their targets
+        let link1 = tmpdir.mash("link1");
+        let link2 = tmpdir.mash("link2");
+        assert!(sys::symlink(&link1, "file1").is_ok());
+        assert!(sys::symlink(&link2, "bogus").is_ok());
+        assert!(sys::remove(&link1).is_ok());
+        assert!(sys::remove(&link2).is_ok());
+        assert_eq!(sys::is_symlink(&link1), false);
+        assert_eq!(sys::is_symlink(&link2), false);
+        assert_eq!(tmpdir.mash("file1").exists(), true);
+
+        // Doesn't exist
+        assert!(sys::remove(tmpdir.mash("bogus")).is_ok());
+        assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
     #[test]
@@ -1505,6 +3882,48 @@ mod tests {
         assert_eq!(tmpdir.exists(), true);
         assert!(sys::remove_all(&tmpdir).is_ok());
         assert_eq!(tmpdir.exists(), false);
+
+        // Refuses dangerous paths
+        assert_eq!(sys::remove_all("/").unwrap_err().downcast_ref::<FileError>(), Some(&FileError::refused_dangerous_delete("/")));
+        assert_eq!(sys::remove_all("/foo/..").unwrap_err().downcast_ref::<FileError>(), Some(&FileError::refused_dangerous_delete("/")));
+        assert_eq!(sys::remove_all("~").unwrap_err().downcast_ref::<FileError>(), Some(&FileError::refused_dangerous_delete(user::home_dir().unwrap())));
+        assert_eq!(sys::remove_all(".").unwrap_err().downcast_ref::<FileError>(), Some(&FileError::refused_dangerous_delete(sys::cwd().unwrap())));
+
+        // Force variant removes normal paths
+        assert!(sys::write(tmpdir.mash("dir1/file1"), "foo").is_ok());
+        assert!(sys::remove_all_force(&tmpdir).is_ok());
+        assert_eq!(tmpdir.exists(), false);
+        assert!(sys::remove_all_force(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_replace_in_file() {
+        let tmpdir = setup().mash("file_replace_in_file");
+        let tmpfile = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write_p(&tmpfile, "pkgname=linux\r\npkgver=5.4.8\ndepends=(foo foo)", 0o600).is_ok());
+
+        // replace a matching line preserving line endings and mode
+        assert_eq!(sys::replace_in_file(&tmpfile, &Regex::new(r"^pkgname=(.*)$").unwrap(), "pkgname=${1}-lts").unwrap(), 1);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "pkgname=linux-lts\r\npkgver=5.4.8\ndepends=(foo foo)");
+        assert_eq!(tmpfile.mode().unwrap(), 0o100600);
+
+        // multiple matches on a line are all counted
+        assert_eq!(sys::replace_in_file(&tmpfile, &Regex::new(r"foo").unwrap(), "bar").unwrap(), 2);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "pkgname=linux-lts\r\npkgver=5.4.8\ndepends=(bar bar)");
+
+        // no matches leaves the file untouched
+        assert_eq!(sys::replace_in_file(&tmpfile, &Regex::new(r"^pkgrel=").unwrap(), "pkgrel=2").unwrap(), 0);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "pkgname=linux-lts\r\npkgver=5.4.8\ndepends=(bar bar)");
+
+        // doesn't exist
+        assert!(sys::replace_in_file(tmpdir.mash("bogus"), &Regex::new(r"foo").unwrap(), "bar").is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
     #[test]
@@ -1552,6 +3971,44 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_tempfile() {
+        // open handle and path refer to the same file
+        let (mut f, path) = sys::tempfile("test_tempfile").unwrap();
+        assert_eq!(path.is_file(), true);
+        assert_eq!(path.mode().unwrap() & 0o7777, 0o600);
+        assert!(path.base().unwrap().starts_with("test_tempfile-"));
+        assert!(f.write_all(b"this is a test").is_ok());
+        assert_eq!(sys::readstring(&path).unwrap(), "this is a test");
+        assert!(sys::remove(&path).is_ok());
+
+        // names are unique
+        let (_, path1) = sys::tempfile("test_tempfile").unwrap();
+        let (_, path2) = sys::tempfile("test_tempfile").unwrap();
+        assert_ne!(path1, path2);
+        assert!(sys::remove(&path1).is_ok());
+        assert!(sys::remove(&path2).is_ok());
+
+        // removed on drop
+        let path = {
+            let mut tmpfile = sys::scoped_tempfile("test_tempfile").unwrap();
+            assert!(tmpfile.write_all(b"this is a test").is_ok());
+            assert!(tmpfile.seek(io::SeekFrom::Start(0)).is_ok());
+            let mut data = String::new();
+            assert!(tmpfile.read_to_string(&mut data).is_ok());
+            assert_eq!(data, "this is a test");
+            assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is a test");
+            assert_eq!(tmpfile.path(), tmpfile.as_ref());
+            tmpfile.path().to_path_buf()
+        };
+        assert_eq!(path.exists(), false);
+
+        // survives when persisted
+        let path = sys::scoped_tempfile("test_tempfile").unwrap().persist();
+        assert_eq!(path.is_file(), true);
+        assert!(sys::remove(&path).is_ok());
+    }
+
     #[test]
     fn test_touch() {
         let tmpdir = setup().mash("file_touch");
@@ -1563,6 +4020,51 @@ mod tests {
 
         // test
         assert!(sys::touch(&tmpfile).is_ok());
+        assert_eq!(tmpfile.exists(), true);
+
+        // bumps the times of an existing file to now
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        assert!(sys::touch_at(&tmpfile, old).is_ok());
+        assert!(sys::touch(&tmpfile).is_ok());
+        assert!(tmpfile.metadata().unwrap().modified().unwrap() > old);
+
+        // creates the parent directory
+        let tmpfile = tmpdir.mash("dir1/file1");
+        assert!(sys::touch(&tmpfile).is_ok());
+        assert_eq!(tmpfile.exists(), true);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_touch_at() {
+        let tmpdir = setup().mash("file_touch_at");
+        let tmpfile = tmpdir.mash("file1");
+        let past = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::new(1_000_000_000, 123_456_789);
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // create a new file with a fixed past mtime
+        assert!(sys::touch_at(&tmpfile, past).is_ok());
+        let meta = tmpfile.metadata().unwrap();
+        assert_eq!(meta.modified().unwrap(), past);
+        assert_eq!((meta.mtime(), meta.mtime_nsec()), (1_000_000_000, 123_456_789));
+
+        // access time is untouched
+        let atime = (meta.atime(), meta.atime_nsec());
+        let later = past + std::time::Duration::from_secs(60);
+        assert!(sys::touch_at(&tmpfile, later).is_ok());
+        let meta = tmpfile.metadata().unwrap();
+        assert_eq!(meta.modified().unwrap(), later);
+        assert_eq!((meta.atime(), meta.atime_nsec()), atime);
+
+        // times before the epoch
+        let before = std::time::SystemTime::UNIX_EPOCH - std::time::Duration::new(10, 250_000_000);
+        assert!(sys::touch_at(&tmpfile, before).is_ok());
+        assert_eq!(tmpfile.metadata().unwrap().modified().unwrap(), before);
 
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
@@ -1595,13 +4097,65 @@ mod tests {
         assert!(sys::mkdir(&tmpdir).is_ok());
 
         // test
-        assert!(sys::write(&tmpfile, "this is a test").is_ok());
+        assert_eq!(sys::write(&tmpfile, "this is a test").unwrap(), 14);
         assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is a test");
 
+        // overwrite
+        assert_eq!(sys::write(&tmpfile, "foo").unwrap(), 3);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "foo");
+
+        // creates missing parent directories
+        assert_eq!(sys::write(tmpdir.mash("dir1/file2"), "bar").unwrap(), 3);
+        assert_eq!(sys::readstring(tmpdir.mash("dir1/file2")).unwrap(), "bar");
+
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_write_atomic() {
+        let tmpdir = setup().mash("file_write_atomic");
+        let tmpfile = tmpdir.mash("file1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // new file
+        assert!(sys::write_atomic(&tmpfile, "this is a test").is_ok());
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is a test");
+
+        // overwrite preserving the mode
+        assert!(sys::chmod(&tmpfile, 0o600).is_ok());
+        assert!(sys::write_atomic(&tmpfile, "this is another test").is_ok());
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is another test");
+        assert_eq!(tmpfile.mode().unwrap(), 0o100600);
+
+        // no leftover temp files
+        assert_iter_eq(sys::all_paths(&tmpdir).unwrap(), vec![tmpfile.clone()]);
+
+        // failures leave the target alone
+        assert!(sys::write_atomic(tmpdir.mash("dir1/file2"), "foo").is_err());
+        assert_iter_eq(sys::all_paths(&tmpdir).unwrap(), vec![tmpfile.clone()]);
+
+        // links are kept with the target being replaced
+        let link1 = tmpdir.mash("link1");
+        assert!(sys::symlink(&link1, "file1").is_ok());
+        assert!(sys::write_atomic(&link1, "through the link").is_ok());
+        assert_eq!(link1.is_symlink(), true);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "through the link");
+        assert_iter_eq(sys::all_paths(&tmpdir).unwrap(), vec![tmpfile.clone(), link1.clone()]);
+
+        // dangling links are kept with the target being created
+        let link2 = tmpdir.mash("link2");
+        let file3 = tmpdir.mash("file3");
+        assert!(sys::symlink(&link2, "file3").is_ok());
+        assert!(sys::write_atomic(&link2, "dangling").is_ok());
+        assert_eq!(link2.is_symlink(), true);
+        assert_eq!(sys::readstring(&file3).unwrap(), "dangling");
+        assert_iter_eq(sys::all_paths(&tmpdir).unwrap(), vec![tmpfile, file3, link1, link2]);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_write_p() {
         let tmpdir = setup().mash("file_write_p");
@@ -0,0 +1,125 @@
+use crate::{errors::*, sys};
+use std::{ffi::CString, mem, ptr};
+
+/// Group provides details for a specific group.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Group {
+    pub gid: u32,             // group id
+    pub name: String,         // group name
+    pub members: Vec<String>, // supplementary group member user names
+}
+
+/// Get the current user's primary group
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(group::current().is_ok());
+/// ```
+pub fn current() -> FuResult<Group> {
+    lookup(unsafe { libc::getgid() })
+}
+
+/// Lookup a group by group id
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(group::lookup(0).unwrap().name, "root");
+/// ```
+pub fn lookup(gid: u32) -> FuResult<Group> {
+    let mut buf = vec![0; 2048];
+    loop {
+        let mut res = ptr::null_mut::<libc::group>();
+        let mut group = unsafe { mem::zeroed::<libc::group>() };
+        let ret = unsafe { libc::getgrgid_r(gid, &mut group, buf.as_mut_ptr(), buf.len(), &mut res) };
+
+        // Groups with many members may not fit into the buffer
+        if ret == libc::ERANGE {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if res.is_null() || res != &mut group {
+            return Err(GroupError::does_not_exist_by_id(gid).into());
+        }
+        return to_group(&group);
+    }
+}
+
+/// Lookup a group by group name
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(group::lookup_by_name("root").unwrap().gid, 0);
+/// ```
+pub fn lookup_by_name<T: AsRef<str>>(name: T) -> FuResult<Group> {
+    let name = name.as_ref();
+    let cname = match CString::new(name) {
+        Ok(x) => x,
+        Err(_) => return Err(GroupError::does_not_exist_by_name(name).into()),
+    };
+
+    let mut buf = vec![0; 2048];
+    loop {
+        let mut res = ptr::null_mut::<libc::group>();
+        let mut group = unsafe { mem::zeroed::<libc::group>() };
+        let ret = unsafe { libc::getgrnam_r(cname.as_ptr(), &mut group, buf.as_mut_ptr(), buf.len(), &mut res) };
+
+        // Groups with many members may not fit into the buffer
+        if ret == libc::ERANGE {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if res.is_null() || res != &mut group {
+            return Err(GroupError::does_not_exist_by_name(name).into());
+        }
+        return to_group(&group);
+    }
+}
+
+/// Private implementation to convert a libc::group object into a Group object
+fn to_group(group: &libc::group) -> FuResult<Group> {
+    // Group name for the lookedup group. We always want this and it should always exist.
+    let name = unsafe { sys::libc::to_string(group.gr_name)? };
+
+    // Member names are a null terminated array of strings
+    let mut members = vec![];
+    if !group.gr_mem.is_null() {
+        let mut i = 0;
+        loop {
+            let member = unsafe { *group.gr_mem.offset(i) };
+            if member.is_null() {
+                break;
+            }
+            members.push(unsafe { sys::libc::to_string(member)? });
+            i += 1;
+        }
+    }
+
+    Ok(Group { gid: group.gr_gid, name, members })
+}
+
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_group_lookup() {
+        let group = group::lookup(user::getgid()).unwrap();
+        assert_eq!(group.gid, user::getgid());
+        assert_ne!(group.name, "");
+        assert_eq!(group::lookup_by_name(&group.name).unwrap(), group);
+        assert_eq!(group::current().unwrap(), group);
+
+        assert_eq!(group::lookup(0).unwrap().name, "root");
+        assert_eq!(group::lookup_by_name("root").unwrap().gid, 0);
+        assert_eq!(group::lookup(u32::MAX - 1).unwrap_err().downcast_ref::<GroupError>(), Some(&GroupError::does_not_exist_by_id(u32::MAX - 1)));
+        assert_eq!(group::lookup_by_name("fungus-bogus").unwrap_err().downcast_ref::<GroupError>(), Some(&GroupError::does_not_exist_by_name("fungus-bogus")));
+    }
+}
@@ -1,5 +1,13 @@
-use crate::{errors::*, sys};
-use std::io;
+use crate::{
+    errors::*,
+    sys::{self, PathExt},
+};
+use std::{
+    ffi::CString,
+    fs, io, mem,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
 
 /// Type of operating system rust is running on
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -140,6 +148,110 @@ pub fn info() -> FuResult<Info> {
     Ok(Info { arch: arch(), kernel: version.to_string(), release: release.to_string() })
 }
 
+/// Capacity of the filesystem backing a path as returned by `disk_usage`
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct DiskUsage {
+    pub total: u64,     // Total size of the filesystem in bytes
+    pub free: u64,      // Free bytes including those reserved for root
+    pub available: u64, // Free bytes available to unprivileged users
+}
+
+/// Returns the capacity of the filesystem backing the given `path` via `statvfs`. Complements
+/// `sys::du` which measures the size of a tree rather than the filesystem it resides on. Handles
+/// path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let usage = sys::disk_usage("/").unwrap();
+/// assert!(usage.available <= usage.free && usage.free <= usage.total);
+/// ```
+pub fn disk_usage<T: AsRef<Path>>(path: T) -> FuResult<DiskUsage> {
+    let path = path.as_ref().abs()?;
+    let cpath = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat = unsafe { mem::zeroed::<libc::statvfs>() };
+    if unsafe { libc::statvfs(cpath.as_ptr(), &mut stat) } != 0 {
+        let err = io::Error::last_os_error();
+        return match err.kind() {
+            io::ErrorKind::NotFound => Err(PathError::does_not_exist(&path).into()),
+            _ => Err(err.into()),
+        };
+    }
+
+    // Block counts are in units of the fragment size
+    let frsize = stat.f_frsize as u64;
+    Ok(DiskUsage { total: stat.f_blocks as u64 * frsize, free: stat.f_bfree as u64 * frsize, available: stat.f_bavail as u64 * frsize })
+}
+
+/// Returns the filesystem type e.g. `ext4`, `tmpfs` or `overlay` of the mount backing the given
+/// `path` as reported by `/proc/self/mounts`. Handles path expansion and resolves links so the
+/// path must exist.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(!sys::fs_type("/").unwrap().is_empty());
+/// ```
+pub fn fs_type<T: AsRef<Path>>(path: T) -> FuResult<String> {
+    let abs = path.as_ref().abs()?;
+    let path = fs::canonicalize(&abs).map_err(|_| PathError::does_not_exist(&abs))?;
+
+    // The deepest mount containing the path wins with later mounts shadowing earlier ones
+    let mut found: Option<(usize, String)> = None;
+    for line in sys::readlines("/proc/self/mounts")? {
+        let mut fields = line.split(' ');
+        if let (Some(_), Some(target), Some(kind)) = (fields.next(), fields.next(), fields.next()) {
+            let target = PathBuf::from(unescape_mount(target));
+            let depth = target.components().count();
+            let deeper = match found {
+                Some((x, _)) => depth >= x,
+                None => true,
+            };
+            if deeper && path.starts_with(&target) {
+                found = Some((depth, kind.to_string()));
+            }
+        }
+    }
+    Ok(found.ok_or_else(|| OsError::mount_not_found(&path))?.1)
+}
+
+/// Returns true if the given `path` is backed by a `tmpfs` mount which shouldn't be used for
+/// large files. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(sys::is_tmpfs("/").unwrap(), sys::fs_type("/").unwrap() == "tmpfs");
+/// ```
+pub fn is_tmpfs<T: AsRef<Path>>(path: T) -> FuResult<bool> {
+    Ok(fs_type(path)? == "tmpfs")
+}
+
+/// Private implementation to decode the octal escapes used for whitespace and backslashes in the
+/// fields of `/proc/self/mounts` e.g. `\040` for a space.
+fn unescape_mount(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 4).and_then(|x| u8::from_str_radix(std::str::from_utf8(x).ok()?, 8).ok());
+        match (bytes[i], octal) {
+            (b'\\', Some(x)) => {
+                out.push(x);
+                i += 4;
+            },
+            (x, _) => {
+                out.push(x);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
 // Substitute stdout and stderr
 pub struct Stdio<T: io::Write, U: io::Write> {
     pub out: T,
@@ -156,6 +268,51 @@ mod tests {
     use crate::prelude::*;
     use std::io::{self, Write};
 
+    #[test]
+    fn test_disk_usage() {
+        let usage = sys::disk_usage("/").unwrap();
+        assert!(usage.total > 0);
+        assert!(usage.available <= usage.total);
+        assert!(usage.free <= usage.total);
+
+        // paths on the same mount report the same total
+        let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("os_disk_usage");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert_eq!(sys::disk_usage(&tmpdir).unwrap().total, sys::disk_usage(tmpdir.dir().unwrap()).unwrap().total);
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // doesn't exist
+        assert_eq!(sys::disk_usage("/foobar").unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist("/foobar")));
+    }
+
+    #[test]
+    fn test_fs_type() {
+        // root always has a type
+        assert!(!sys::fs_type("/").unwrap().is_empty());
+        assert_eq!(sys::is_tmpfs("/").unwrap(), sys::fs_type("/").unwrap() == "tmpfs");
+
+        // temp paths resolve to their mount
+        let tmpdir = user::temp_dir("os_fs_type").unwrap();
+        assert!(!sys::fs_type(&tmpdir).unwrap().is_empty());
+        assert!(sys::is_tmpfs(&tmpdir).is_ok());
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // proc is always its own mount
+        assert_eq!(sys::fs_type("/proc/self").unwrap(), "proc");
+
+        // doesn't exist
+        assert_eq!(sys::fs_type("/foobar").unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist("/foobar")));
+    }
+
+    #[test]
+    fn test_unescape_mount() {
+        assert_eq!(super::unescape_mount("/mnt/foo"), "/mnt/foo");
+        assert_eq!(super::unescape_mount("/mnt/foo\\040bar"), "/mnt/foo bar");
+        assert_eq!(super::unescape_mount("/mnt/foo\\134bar\\011"), "/mnt/foo\\bar\t");
+        assert_eq!(super::unescape_mount("/mnt/foo\\0"), "/mnt/foo\\0");
+    }
+
     #[test]
     fn test_info() {
         assert!(sys::info().is_ok());
@@ -1,18 +1,130 @@
 use crate::{
     core::*,
     errors::*,
-    sys::{self, user},
+    sys::{self, group, user},
 };
 use gory::*;
 use std::{
-    collections::HashMap,
-    ffi::OsStr,
+    collections::{HashMap, HashSet},
+    ffi::{CString, OsStr, OsString},
     fs, io,
-    os::unix::fs::{MetadataExt, PermissionsExt},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{MetadataExt, PermissionsExt},
+    },
     path::{Component, Path, PathBuf},
+    time::SystemTime,
 };
 use walkdir::WalkDir;
 
+/// Stat provides a richer view of a path's metadata suitable for listing tools
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stat {
+    pub path: PathBuf,     // path the metadata is for
+    pub size: u64,         // size in bytes
+    pub mtime: SystemTime, // last modification time
+    pub mode: u32,         // raw mode including the file type
+    pub symbolic: String,  // symbolic mode e.g. -rwxr-xr-x
+    pub uid: u32,          // owner user id
+    pub gid: u32,          // owner group id
+    pub user: String,      // owner user name or the user id if not found
+    pub group: String,     // owner group name or the group id if not found
+}
+
+/// DirEntry provides a path yielded by a `Walker` along with its metadata cached from the walk
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    path: PathBuf,      // path of the entry
+    meta: fs::Metadata, // metadata of the entry, following links only when the walker does
+    depth: usize,       // depth of the entry relative to the walk root
+}
+impl DirEntry {
+    /// Returns the depth of the entry relative to the walk root, children of the root are `1`
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Consumes the entry returning its path
+    pub fn into_path(self) -> PathBuf {
+        self.path
+    }
+
+    /// Returns the cached metadata of the entry
+    pub fn metadata(&self) -> &fs::Metadata {
+        &self.meta
+    }
+
+    /// Returns the path of the entry
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Walker provides a lazy depth first iterator over the entries of a directory tree sorted by
+/// file name. Options must be set before iterating as the walk is started on the first call to
+/// `next`.
+#[derive(Debug)]
+pub struct Walker {
+    root: PathBuf,                   // root directory to walk
+    max_depth: usize,                // maximum depth to descend to
+    follow_links: bool,              // follow links when walking
+    skip_hidden: bool,               // skip hidden files and directories
+    iter: Option<walkdir::IntoIter>, // underlying walk once started
+}
+impl Walker {
+    /// Update the `follow_links` option. Default is disabled.
+    /// When `yes` is `true`, links are followed and their targets' metadata is cached.
+    pub fn follow_links(mut self, yes: bool) -> Self {
+        self.follow_links = yes;
+        self
+    }
+
+    /// Update the `max_depth` option. Default is unlimited.
+    /// A `depth` of `1` yields only the immediate children of the root.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Update the `skip_hidden` option. Default is disabled.
+    /// When `yes` is `true`, entries starting with a `.` are skipped along with their contents.
+    pub fn skip_hidden(mut self, yes: bool) -> Self {
+        self.skip_hidden = yes;
+        self
+    }
+}
+impl Iterator for Walker {
+    type Item = FuResult<DirEntry>;
+
+    fn next(&mut self) -> Option<FuResult<DirEntry>> {
+        // WalkDir would raise the max depth to the min depth rather than yield nothing
+        if self.max_depth == 0 {
+            return None;
+        }
+        if self.iter.is_none() {
+            let walker = WalkDir::new(&self.root).min_depth(1).max_depth(self.max_depth).follow_links(self.follow_links);
+            self.iter = Some(walker.sort_by(|x, y| x.file_name().cmp(y.file_name())).into_iter());
+        }
+        let iter = self.iter.as_mut()?;
+        loop {
+            let entry = match iter.next()? {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err.into())),
+            };
+            if self.skip_hidden && entry.file_name().as_bytes().first() == Some(&b'.') {
+                if entry.file_type().is_dir() {
+                    iter.skip_current_dir();
+                }
+                continue;
+            }
+            return Some(match entry.metadata() {
+                Ok(meta) => Ok(DirEntry { depth: entry.depth(), path: entry.into_path(), meta }),
+                Err(err) => Err(err.into()),
+            });
+        }
+    }
+}
+
 /// Return the path in an absolute clean form
 ///
 /// ### Examples
@@ -21,10 +133,29 @@ use walkdir::WalkDir;
 ///
 /// let home = user::home_dir().unwrap();
 /// assert_eq!(PathBuf::from(&home), sys::abs("~").unwrap());
+/// assert_eq!(sys::cwd().unwrap().mash("bar"), sys::abs("./foo/../bar").unwrap());
 /// ```
 pub fn abs<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
-    let path = path.as_ref();
+    abs_p(path.as_ref(), || Ok(sys::cwd()?))
+}
+
+/// Return the path in an absolute clean form the same as `abs` but resolving relative paths
+/// against the given `base` rather than the current working directory. The `base` itself is
+/// resolved with `abs` if needed.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(sys::abs_from("../bar", "/foo/blah").unwrap(), PathBuf::from("/foo/bar"));
+/// assert_eq!(sys::abs_from("./bar", "/foo").unwrap(), PathBuf::from("/foo/bar"));
+/// ```
+pub fn abs_from<T: AsRef<Path>, B: AsRef<Path>>(path: T, base: B) -> FuResult<PathBuf> {
+    abs_p(path.as_ref(), || abs(base))
+}
 
+/// Private implementation of `abs` and `abs_from` only calling `base` when needed
+fn abs_p<F: FnOnce() -> FuResult<PathBuf>>(path: &Path, base: F) -> FuResult<PathBuf> {
     // Check for empty string
     if path.empty() {
         return Err(PathError::Empty.into());
@@ -41,7 +172,7 @@ pub fn abs<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
 
     // Expand relative directories if needed
     if !path_buf.is_absolute() {
-        let mut curr = sys::cwd()?;
+        let mut curr = base()?;
         while let Ok(path) = path_buf.first() {
             match path {
                 Component::CurDir => {
@@ -182,6 +313,26 @@ pub fn all_paths<T: AsRef<Path>>(path: T) -> FuResult<Vec<PathBuf>> {
     Err(PathError::does_not_exist(abs).into())
 }
 
+/// Returns a `find` predicate matching files with the given extension. A leading `.` in the
+/// extension is ignored.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_by_ext");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1.rs");
+/// assert!(sys::touch(&file1).is_ok());
+/// assert!(sys::touch(tmpdir.mash("file2.md")).is_ok());
+/// assert_iter_eq(sys::find(&tmpdir, sys::by_ext("rs")).unwrap(), vec![file1]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn by_ext<T: AsRef<str>>(ext: T) -> impl Fn(&Path, &fs::Metadata) -> bool {
+    let ext = ext.as_ref().trim_start_matches('.').to_string();
+    move |path: &Path, meta: &fs::Metadata| meta.is_file() && path.extension() == Some(OsStr::new(&ext))
+}
+
 /// Returns all directories for the given path, sorted by filename. Handles path expansion.
 /// Paths are returned as abs paths. Doesn't include the path itself only its children nor
 /// is this recursive.
@@ -219,6 +370,64 @@ pub fn dirs<T: AsRef<Path>>(path: T) -> FuResult<Vec<PathBuf>> {
     Err(PathError::does_not_exist(abs).into())
 }
 
+/// Returns the total apparent size in bytes of the given path recursively. Handles path
+/// expansion. Only files and symlinks are counted and symlinks are not followed, counting only
+/// the size of the link itself. Hard links are only counted once.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_du");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(tmpdir.mash("file1"), "123").is_ok());
+/// assert!(sys::write(tmpdir.mash("file2"), "12").is_ok());
+/// assert_eq!(sys::du(&tmpdir).unwrap(), 5);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn du<T: AsRef<Path>>(path: T) -> FuResult<u64> {
+    du_p(path, |meta| if meta.is_dir() { 0 } else { meta.len() })
+}
+
+/// Returns the total on-disk usage in bytes of the given path recursively, based on the blocks
+/// allocated for each entry via `MetadataExt::blocks`. Handles path expansion. Directories are
+/// included and symlinks are not followed, counting only the link itself. Hard links are only
+/// counted once.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_du_blocks");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(tmpdir.mash("file1"), "123").is_ok());
+/// assert_eq!(sys::du_blocks(&tmpdir).unwrap() % 512, 0);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn du_blocks<T: AsRef<Path>>(path: T) -> FuResult<u64> {
+    du_p(path, |meta| meta.blocks() * 512)
+}
+
+/// Private implementation of the disk usage summing the `size` of each distinct entry
+fn du_p<T: AsRef<Path>, F: Fn(&fs::Metadata) -> u64>(path: T, size: F) -> FuResult<u64> {
+    let abs = path.as_ref().abs()?;
+    if !abs.exists() {
+        return Err(PathError::does_not_exist(abs).into());
+    }
+
+    let mut total = 0;
+    let mut distinct = HashSet::<(u64, u64)>::new();
+    for entry in WalkDir::new(&abs).follow_links(false) {
+        let meta = entry?.metadata()?;
+        if distinct.insert((meta.dev(), meta.ino())) {
+            total += size(&meta);
+        }
+    }
+    Ok(total)
+}
+
 /// Returns true if the given path exists. Handles path expansion.
 ///
 /// ### Examples
@@ -233,8 +442,8 @@ pub fn exists<T: AsRef<Path>>(path: T) -> bool {
 
 /// Expand all environment variables in the path as well as the home directory.
 ///
-/// WARNING: Does not expand partials e.g. "/foo${BAR}ing/blah" only complete components
-/// e.g. "/foo/${BAR}/blah"
+/// Both `$VAR` and `${VAR}` forms are expanded anywhere in the path e.g. "/foo${BAR}ing/blah".
+/// Undefined variables result in a `PathError::UndefinedVar` rather than expanding to empty.
 ///
 /// ### Examples
 /// ```
@@ -242,6 +451,8 @@ pub fn exists<T: AsRef<Path>>(path: T) -> bool {
 ///
 /// let home = user::home_dir().unwrap();
 /// assert_eq!(PathBuf::from(&home).mash("foo"), PathBuf::from("~/foo").expand().unwrap());
+/// sys::set_var("PATH_DOC_EXPAND", "bar");
+/// assert_eq!(PathBuf::from("/foo/${PATH_DOC_EXPAND}ing").expand().unwrap(), PathBuf::from("/foo/baring"));
 /// ```
 pub fn expand<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
     let mut path = path.as_ref().to_path_buf();
@@ -269,35 +480,56 @@ pub fn expand<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
 
     // Expand other variables that may exist in the path
     let pathstr = path.to_string()?;
-    if pathstr.matches('$').some() {
-        let mut path_buf = PathBuf::new();
-        for x in path.components() {
-            match x {
-                Component::Normal(y) => {
-                    let seg = y.to_string()?;
-                    if let Some(chunk) = seg.strip_prefix("${") {
-                        if let Some(key) = chunk.strip_suffix("}") {
-                            let var = sys::var(key)?;
-                            path_buf.push(var);
-                        } else {
-                            return Err(PathError::invalid_expansion(seg).into());
-                        }
-                    } else if let Some(key) = seg.strip_prefix('$') {
-                        let var = sys::var(key)?;
-                        path_buf.push(var);
-                    } else {
-                        path_buf.push(seg);
-                    }
-                },
-                _ => path_buf.push(x),
-            }
-        }
-        path = path_buf;
+    if pathstr.contains('$') {
+        path = PathBuf::from(expand_vars(&pathstr)?);
     }
 
     Ok(path)
 }
 
+/// Private implementation to expand `$VAR` and `${VAR}` references anywhere in the given `value`.
+/// A `$` not followed by a valid variable name is kept as is.
+fn expand_vars(value: &str) -> FuResult<String> {
+    let mut result = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+
+        // Read the variable name in either ${VAR} or $VAR form
+        let mut key = String::new();
+        if chars.peek() == Some(&'{') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(x) => key.push(x),
+                    None => return Err(PathError::invalid_expansion(value).into()),
+                }
+            }
+        } else {
+            while let Some(x) = chars.peek() {
+                if !(x.is_ascii_alphanumeric() || *x == '_') || (key.is_empty() && x.is_ascii_digit()) {
+                    break;
+                }
+                key.push(*x);
+                chars.next();
+            }
+            if key.is_empty() {
+                result.push(c);
+                continue;
+            }
+        }
+        match sys::var(&key) {
+            Ok(x) => result.push_str(&x),
+            Err(_) => return Err(PathError::undefined_var(key).into()),
+        }
+    }
+    Ok(result)
+}
+
 /// Returns all files for the given path, sorted by filename. Handles path expansion.
 /// Paths are returned as abs paths. Doesn't include the path itself only its children nor
 /// is this recursive.
@@ -336,6 +568,67 @@ pub fn files<T: AsRef<Path>>(path: T) -> FuResult<Vec<PathBuf>> {
     Err(PathError::does_not_exist(abs).into())
 }
 
+/// Returns all paths for the given path recursively, depth first sorted by filename, that match
+/// the given predicate. Handles path expansion. Paths are returned as abs paths. Doesn't include
+/// the path itself. Symlinks are not followed. Unreadable directories are skipped, see
+/// `find_with_errors` to retrieve the errors encountered along the way.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_find");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("dir1/file2");
+/// assert!(sys::mkdir(tmpdir.mash("dir1")).is_ok());
+/// assert!(sys::write(&file1, "1").is_ok());
+/// assert!(sys::write(&file2, "12").is_ok());
+/// assert_iter_eq(sys::find(&tmpdir, |_, meta| meta.is_file()).unwrap(), vec![file2.clone(), file1]);
+/// assert_iter_eq(sys::find(&tmpdir, sys::larger_than(1)).unwrap(), vec![file2]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn find<T, F>(path: T, pred: F) -> FuResult<Vec<PathBuf>>
+where
+    T: AsRef<Path>,
+    F: Fn(&Path, &fs::Metadata) -> bool,
+{
+    Ok(find_with_errors(path, pred)?.0)
+}
+
+/// Wraps `find` to also return the errors encountered for paths that were skipped e.g. unreadable
+/// directories, rather than aborting the search.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_find_with_errors");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::touch(&file1).is_ok());
+/// let (paths, errors) = sys::find_with_errors(&tmpdir, |_, _| true).unwrap();
+/// assert_iter_eq(paths, vec![file1]);
+/// assert_eq!(errors.is_empty(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn find_with_errors<T, F>(path: T, pred: F) -> FuResult<(Vec<PathBuf>, Vec<FuError>)>
+where
+    T: AsRef<Path>,
+    F: Fn(&Path, &fs::Metadata) -> bool,
+{
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut errors: Vec<FuError> = Vec::new();
+    for entry in walk(path)? {
+        match entry {
+            Ok(entry) if pred(entry.path(), entry.metadata()) => paths.push(entry.into_path()),
+            Ok(_) => {},
+            Err(err) => errors.push(err),
+        }
+    }
+    Ok((paths, errors))
+}
+
 /// Returns true if the given path exists and is a directory. Handles path expansion.
 ///
 /// ### Examples
@@ -351,7 +644,57 @@ pub fn is_dir<T: AsRef<Path>>(path: T) -> bool {
     }
 }
 
-/// Returns true if the given path exists and is an executable. Handles path expansion
+/// Returns true if the given path is an empty directory or a zero length regular file. Handles
+/// path expansion and follows links. Other kinds of files are never considered empty. Returns a
+/// `PathError::DoesNotExist` if the path doesn't exist.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_is_empty");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::touch(&file1).is_ok());
+/// assert_eq!(sys::is_empty(&file1).unwrap(), true);
+/// assert_eq!(sys::is_empty(&tmpdir).unwrap(), false);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn is_empty<T: AsRef<Path>>(path: T) -> FuResult<bool> {
+    let abs = path.as_ref().abs()?;
+    match fs::metadata(&abs) {
+        Ok(meta) if meta.is_dir() => is_empty_dir(abs),
+        Ok(meta) => Ok(meta.is_file() && meta.len() == 0),
+        Err(_) => Err(PathError::does_not_exist(abs).into()),
+    }
+}
+
+/// Returns true if the given path is a directory with no entries. Handles path expansion and
+/// follows links. Returns false for anything other than a directory and a
+/// `PathError::DoesNotExist` if the path doesn't exist.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_is_empty_dir");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert_eq!(sys::is_empty_dir(&tmpdir).unwrap(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn is_empty_dir<T: AsRef<Path>>(path: T) -> FuResult<bool> {
+    let abs = path.as_ref().abs()?;
+    match fs::metadata(&abs) {
+        Ok(meta) if meta.is_dir() => Ok(fs::read_dir(&abs)?.next().is_none()),
+        Ok(_) => Ok(false),
+        Err(_) => Err(PathError::does_not_exist(abs).into()),
+    }
+}
+
+/// Returns true if the given path exists and has any of the execute bits set regardless of who
+/// the current user is. Use `is_executable` to check if the current user may actually execute it.
+/// Handles path expansion
 ///
 /// ### Examples
 /// ```
@@ -375,6 +718,27 @@ pub fn is_exec<T: AsRef<Path>>(path: T) -> bool {
     }
 }
 
+/// Returns true if the given path exists and is executable, or searchable for directories, by the
+/// current user. Asks the kernel via `faccessat` using the process's effective credentials unlike
+/// `is_exec` which only checks for any execute bit being set. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_is_executable");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch_p(tmpdir.mash("file1"), 0o755).is_ok());
+/// assert!(sys::touch_p(tmpdir.mash("file2"), 0o644).is_ok());
+/// assert_eq!(sys::is_executable(tmpdir.mash("file1")), true);
+/// assert_eq!(sys::is_executable(tmpdir.mash("file2")), false);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn is_executable<T: AsRef<Path>>(path: T) -> bool {
+    permits(path, libc::X_OK)
+}
+
 /// Returns true if the given path exists and is a file. Handles path expansion
 ///
 /// ### Examples
@@ -390,6 +754,25 @@ pub fn is_file<T: AsRef<Path>>(path: T) -> bool {
     }
 }
 
+/// Returns true if the given path exists and is readable by the current user. Asks the kernel
+/// via `faccessat` using the process's effective credentials. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_is_readable");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch_p(tmpdir.mash("file1"), 0o600).is_ok());
+/// assert_eq!(sys::is_readable(tmpdir.mash("file1")), true);
+/// assert_eq!(sys::is_readable(tmpdir.mash("file2")), false);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn is_readable<T: AsRef<Path>>(path: T) -> bool {
+    permits(path, libc::R_OK)
+}
+
 /// Returns true if the given path exists and is readonly. Handles path expansion
 ///
 /// ### Examples
@@ -496,6 +879,25 @@ pub fn is_symlink_file<T: AsRef<Path>>(path: T) -> bool {
     }
 }
 
+/// Returns true if the given path exists and is writable by the current user. Asks the kernel
+/// via `faccessat` using the process's effective credentials. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_is_writable");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch_p(tmpdir.mash("file1"), 0o644).is_ok());
+/// assert_eq!(sys::is_writable(tmpdir.mash("file1")), true);
+/// assert_eq!(sys::is_writable(tmpdir.mash("file2")), false);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn is_writable<T: AsRef<Path>>(path: T) -> bool {
+    permits(path, libc::W_OK)
+}
+
 /// Returns the group ID of the owner of this file. Handles path expansion.
 ///
 /// ### Examples
@@ -535,6 +937,124 @@ pub fn glob<T: AsRef<Path>>(src: T) -> FuResult<Vec<PathBuf>> {
     Ok(paths)
 }
 
+/// Returns a vector of all paths from the given target glob with path expansion along with all
+/// of their descendants recursively. Matches are sorted by name and each matched directory is
+/// walked with its children sorted by filename. Paths are guaranteed to be distinct.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_glob_all");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let dir1 = tmpdir.mash("dir1");
+/// let file1 = dir1.mash("file1");
+/// let dir2 = tmpdir.mash("dir2");
+/// assert!(sys::mkdir(&dir1).is_ok());
+/// assert!(sys::mkdir(&dir2).is_ok());
+/// assert!(sys::touch(&file1).is_ok());
+/// assert_iter_eq(sys::glob_all(tmpdir.mash("dir*")).unwrap(), vec![dir1, file1, dir2]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn glob_all<T: AsRef<Path>>(src: T) -> FuResult<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut distinct = HashMap::<PathBuf, bool>::new();
+    for source in glob(src)? {
+        for entry in WalkDir::new(&source).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
+            let path = entry?.into_path();
+
+            // Ensure the path is distinct as matches may overlap
+            if !distinct.contains_key(&path) {
+                distinct.insert(path.clone(), true);
+                paths.push(path);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+/// Returns true if the given `candidate` string matches the given glob `pattern` without touching
+/// the filesystem. Supports `*` and `?` which don't match path separators, `[...]` character
+/// classes and `**` as its own path component to match any number of directories. Returns an
+/// error if the pattern is invalid.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(sys::glob_match("src/**/*.rs", "src/sys/path.rs").unwrap(), true);
+/// assert_eq!(sys::glob_match("*.rs", "src/sys/path.rs").unwrap(), false);
+/// ```
+pub fn glob_match(pattern: &str, candidate: &str) -> FuResult<bool> {
+    let opts = glob::MatchOptions { require_literal_separator: true, ..Default::default() };
+    Ok(glob::Pattern::new(pattern)?.matches_with(candidate, opts))
+}
+
+/// Join the given paths into a unix shell pathing string e.g. $PATH, $XDG_DATA_DIRS or
+/// $XDG_CONFIG_DIRS. This is the inverse of `parse_paths`. Returns a
+/// `PathError::ContainsSeparator` if any path contains a colon as it would corrupt the list.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let paths = vec![PathBuf::from("/foo1"), PathBuf::from("/foo2/bar")];
+/// assert_eq!(sys::join_paths(paths).unwrap(), OsString::from("/foo1:/foo2/bar"));
+/// ```
+pub fn join_paths<I: IntoIterator<Item = PathBuf>>(paths: I) -> FuResult<OsString> {
+    let mut joined = OsString::new();
+    for (i, path) in paths.into_iter().enumerate() {
+        if path.as_os_str().as_bytes().contains(&b':') {
+            return Err(PathError::contains_separator(path).into());
+        }
+        if i > 0 {
+            joined.push(":");
+        }
+        joined.push(path);
+    }
+    Ok(joined)
+}
+
+/// Returns a `find` predicate matching files larger than the given number of bytes.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_larger_than");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&file1, "12").is_ok());
+/// assert!(sys::write(tmpdir.mash("file2"), "1").is_ok());
+/// assert_iter_eq(sys::find(&tmpdir, sys::larger_than(1)).unwrap(), vec![file1]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn larger_than(bytes: u64) -> impl Fn(&Path, &fs::Metadata) -> bool {
+    move |_: &Path, meta: &fs::Metadata| meta.is_file() && meta.len() > bytes
+}
+
+/// Returns a `Stat` for the given `path` without following symbolic links i.e. a link will be
+/// described rather than its target. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_lstat");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let link1 = tmpdir.mash("link1");
+/// assert!(sys::symlink(&link1, "file1").is_ok());
+/// assert_eq!(sys::lstat(&link1).unwrap().symbolic, "lrwxrwxrwx");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn lstat<T: AsRef<Path>>(path: T) -> FuResult<Stat> {
+    let path = path.as_ref().abs()?;
+    let meta = fs::symlink_metadata(&path)?;
+    to_stat(path, &meta)
+}
+
 /// Returns a new owned [`PathBuf`] from `dir` mashed together with `base`.
 /// Differs from the `join` implementation as `mash` drops root prefix of the given `path` if
 /// it exists and also drops any trailing '/' on the new resulting path. More closely aligns
@@ -567,7 +1087,8 @@ pub fn metadata<T: AsRef<Path>>(path: T) -> FuResult<fs::Metadata> {
 }
 
 /// Parse unix shell pathing e.g. $PATH, $XDG_DATA_DIRS or $XDG_CONFIG_DIRS.
-/// List of directories seperated by :
+/// List of directories seperated by : with entries starting with `~` expanded via
+/// `PathExt::expand` e.g. `~/bin`. Entries that fail to expand are kept as is.
 ///
 /// ### Examples
 /// ```
@@ -582,6 +1103,7 @@ pub fn parse_paths<T: AsRef<str>>(value: T) -> FuResult<Vec<PathBuf>> {
         // Unix shell semantics: path element "" means "."
         let path = match dir == "" {
             true => sys::cwd()?,
+            false if dir.starts_with('~') => PathBuf::from(dir).expand().unwrap_or_else(|_| PathBuf::from(dir)),
             false => PathBuf::from(dir),
         };
         paths.push(path);
@@ -589,6 +1111,20 @@ pub fn parse_paths<T: AsRef<str>>(value: T) -> FuResult<Vec<PathBuf>> {
     Ok(paths)
 }
 
+/// Wraps `parse_paths` to drop duplicate entries preserving the order of first occurrence.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let paths = vec![PathBuf::from("/foo1"), PathBuf::from("/foo2/bar")];
+/// assert_iter_eq(sys::parse_paths_dedup("/foo1:/foo2/bar:/foo1").unwrap(), paths);
+/// ```
+pub fn parse_paths_dedup<T: AsRef<str>>(value: T) -> FuResult<Vec<PathBuf>> {
+    let mut seen = HashSet::new();
+    Ok(parse_paths(value)?.into_iter().filter(|x| seen.insert(x.clone())).collect())
+}
+
 /// Returns all directories/files for the given path, sorted by filename. Handles path
 /// expansion. Paths are returned as abs paths. Doesn't include the path itself only
 /// its children nor is this recursive.
@@ -626,7 +1162,20 @@ pub fn paths<T: AsRef<Path>>(path: T) -> FuResult<Vec<PathBuf>> {
     Err(PathError::does_not_exist(abs).into())
 }
 
-/// Returns the absolute path for the given link target. Handles path expansion
+/// Private implementation to check the given access `mode` i.e. `libc::R_OK`, `libc::W_OK` or
+/// `libc::X_OK` for the given `path` against the process's effective credentials.
+fn permits<T: AsRef<Path>>(path: T, mode: libc::c_int) -> bool {
+    let path = match path.as_ref().abs() {
+        Ok(x) => x,
+        Err(_) => return false,
+    };
+    match CString::new(path.as_os_str().as_bytes()) {
+        Ok(x) => unsafe { libc::faccessat(libc::AT_FDCWD, x.as_ptr(), mode, libc::AT_EACCESS) == 0 },
+        Err(_) => false,
+    }
+}
+
+/// Returns the raw target of the given link without resolving it. Handles path expansion
 ///
 /// ### Examples
 /// ```
@@ -648,6 +1197,32 @@ pub fn readlink<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
     Ok(abs)
 }
 
+/// Returns the absolute path for the given link target resolving relative targets against the
+/// link's directory. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_readlink_abs");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let link1 = tmpdir.mash("link1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch(&file1).is_ok());
+/// assert!(sys::symlink(&link1, "file1").is_ok());
+/// assert_eq!(sys::readlink_abs(link1).unwrap(), file1);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn readlink_abs<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
+    let abs = path.as_ref().abs()?;
+    let target = fs::read_link(&abs)?;
+    match target.is_absolute() {
+        true => target.clean(),
+        false => abs.dir()?.mash(target).clean(),
+    }
+}
+
 /// Return the current working path trimmed back to the relative dir
 ///
 /// ### Examples
@@ -675,6 +1250,94 @@ pub fn rel_to(dir: &str) -> FuResult<PathBuf> {
     Ok(path)
 }
 
+/// Returns true if the given paths refer to the same underlying file by comparing their device
+/// and inode numbers. Handles path expansion. Symlinks are followed. Returns an error if either
+/// path doesn't exist.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_same_file");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("file2");
+/// assert!(sys::touch(&file1).is_ok());
+/// assert!(fs::hard_link(&file1, &file2).is_ok());
+/// assert_eq!(sys::same_file(&file1, &file2).unwrap(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn same_file<T: AsRef<Path>>(a: T, b: T) -> FuResult<bool> {
+    let a = metadata(a)?;
+    let b = metadata(b)?;
+    Ok(a.dev() == b.dev() && a.ino() == b.ino())
+}
+
+/// Returns a `Stat` for the given `path` following symbolic links. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_stat");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write_p(&file1, "foo", 0o754).is_ok());
+/// let stat = sys::stat(&file1).unwrap();
+/// assert_eq!(stat.symbolic, "-rwxr-xr--");
+/// assert_eq!(stat.size, 3);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn stat<T: AsRef<Path>>(path: T) -> FuResult<Stat> {
+    let path = path.as_ref().abs()?;
+    let meta = fs::metadata(&path)?;
+    to_stat(path, &meta)
+}
+
+/// Private implementation to render the given `mode` symbolically e.g. `-rwxr-xr-x` including
+/// the file type prefix and the setuid, setgid and sticky bits.
+fn symbolic_mode(mode: u32) -> String {
+    let mut symbolic = String::with_capacity(10);
+    symbolic.push(match mode & libc::S_IFMT {
+        libc::S_IFDIR => 'd',
+        libc::S_IFLNK => 'l',
+        libc::S_IFCHR => 'c',
+        libc::S_IFBLK => 'b',
+        libc::S_IFIFO => 'p',
+        libc::S_IFSOCK => 's',
+        _ => '-',
+    });
+
+    // Owner, group and other triplets with their special bit and its exec character
+    for (shift, special, chars) in [(6, libc::S_ISUID, ('s', 'S')), (3, libc::S_ISGID, ('s', 'S')), (0, libc::S_ISVTX, ('t', 'T'))] {
+        let bits = mode >> shift;
+        symbolic.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        symbolic.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        symbolic.push(match (mode & special != 0, bits & 0o1 != 0) {
+            (true, true) => chars.0,
+            (true, false) => chars.1,
+            (false, true) => 'x',
+            (false, false) => '-',
+        });
+    }
+    symbolic
+}
+
+/// Private implementation to convert the given `path` and `meta` into a `Stat`
+fn to_stat(path: PathBuf, meta: &fs::Metadata) -> FuResult<Stat> {
+    Ok(Stat {
+        path,
+        size: meta.len(),
+        mtime: meta.modified()?,
+        mode: meta.mode(),
+        symbolic: symbolic_mode(meta.mode()),
+        uid: meta.uid(),
+        gid: meta.gid(),
+        user: user::lookup(meta.uid()).map(|x| x.name).unwrap_or_else(|_| meta.uid().to_string()),
+        group: group::lookup(meta.gid()).map(|x| x.name).unwrap_or_else(|_| meta.gid().to_string()),
+    })
+}
+
 /// Returns the user ID of the owner of this file. Handles path expansion.
 ///
 /// ### Examples
@@ -687,6 +1350,35 @@ pub fn uid<T: AsRef<Path>>(path: T) -> FuResult<u32> {
     Ok(metadata(path)?.uid())
 }
 
+/// Returns a lazy depth first `Walker` over the entries of the given directory `root` sorted by
+/// file name and not including the root itself. Handles path expansion. Links are not followed
+/// by default. Errors encountered along the way e.g. unreadable directories are yielded rather
+/// than aborting the walk.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_walk");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(tmpdir.mash("dir1/file2"), "").is_ok());
+/// assert!(sys::touch(&file1).is_ok());
+/// let paths: Vec<PathBuf> = sys::walk(&tmpdir).unwrap().max_depth(1).map(|x| x.unwrap().into_path()).collect();
+/// assert_iter_eq(paths, vec![tmpdir.mash("dir1"), file1]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn walk<T: AsRef<Path>>(root: T) -> FuResult<Walker> {
+    let root = root.as_ref().abs()?;
+    if !root.exists() {
+        return Err(PathError::does_not_exist(root).into());
+    }
+    if !root.is_dir() {
+        return Err(PathError::is_not_dir(root).into());
+    }
+    Ok(Walker { root, max_depth: usize::MAX, follow_links: false, skip_hidden: false, iter: None })
+}
+
 // Path extensions
 // -------------------------------------------------------------------------------------------------
 pub trait PathExt {
@@ -713,16 +1405,41 @@ pub trait PathExt {
     /// ```
     fn abs_from<T: AsRef<Path>>(&self, path: T) -> FuResult<PathBuf>;
 
-    /// Returns the final component of the `Path`, if there is one.
+    /// Returns the final component of the `Path`, if there is one, including any extension.
+    /// See `name` for the final component with its extension stripped.
+    ///
+    /// Note `base` deliberately keeps the full file name rather than stripping the extension as
+    /// `copy`, `move_p`, `backup`, `write_atomic` and `tar` all rely on it to name their targets.
+    /// Use `name` for the stripped form.
     ///
     /// ### Examples
     /// ```
     /// use fungus::prelude::*;
     ///
     /// assert_eq!("bar", PathBuf::from("/foo/bar").base().unwrap());
+    /// assert_eq!("bar.tar.gz", PathBuf::from("/foo/bar.tar.gz").base().unwrap());
     /// ```
     fn base(&self) -> FuResult<String>;
 
+    /// Returns the absolute path with its longest existing prefix canonicalized i.e. links
+    /// resolved and the remaining non-existent components appended after cleaning. The raw path
+    /// is used so that links resolve before `..` as with `fs::canonicalize` and dangling links are
+    /// followed to their target. Unlike `fs::canonicalize` this doesn't fail for paths that are
+    /// yet to be created.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("pathbuf_doc_canonicalize_lossy");
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// assert!(sys::mkdir(tmpdir.mash("dir1")).is_ok());
+    /// assert!(sys::symlink(tmpdir.mash("link1"), "dir1").is_ok());
+    /// assert_eq!(tmpdir.mash("link1/not/yet").canonicalize_lossy().unwrap(), tmpdir.mash("dir1/not/yet"));
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// ```
+    fn canonicalize_lossy(&self) -> FuResult<PathBuf>;
+
     /// Set the given mode for the `Path` and return the `Path`
     ///
     /// ### Examples
@@ -759,6 +1476,21 @@ pub trait PathExt {
     /// current directory.
     fn clean(&self) -> FuResult<PathBuf>;
 
+    /// Returns the components of the cleaned `Path` as Strings. Absolute paths will have `/` as
+    /// their first element while relative paths may begin with `..` elements. An empty `Path`
+    /// returns no components just like `Path::components` while paths cleaning to the current
+    /// directory e.g. `foo/..` return `.`.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(Path::new("").components_str().unwrap(), Vec::<String>::new());
+    /// assert_eq!(Path::new("/foo/./bar").components_str().unwrap(), vec!["/", "foo", "bar"]);
+    /// assert_eq!(Path::new("foo/../../bar").components_str().unwrap(), vec!["..", "bar"]);
+    /// ```
+    fn components_str(&self) -> FuResult<Vec<String>>;
+
     /// Returns the `Path` with the given string concatenated on.
     ///
     /// ### Examples
@@ -811,13 +1543,15 @@ pub trait PathExt {
     /// ```
     fn expand(&self) -> FuResult<PathBuf>;
 
-    /// Returns the extension of the path or an error.
+    /// Returns the lowercased extension of the path without the leading dot or an error. Dot
+    /// files e.g. `.bashrc` are considered to have no extension.
     ///
     /// ### Examples
     /// ```
     /// use fungus::prelude::*;
     ///
     /// assert_eq!(Path::new("foo.bar").ext().unwrap(), "bar");
+    /// assert_eq!(Path::new("foo.tar.GZ").ext().unwrap(), "gz");
     /// ```
     fn ext(&self) -> FuResult<String>;
 
@@ -879,6 +1613,19 @@ pub trait PathExt {
     /// ```
     fn has_suffix<T: AsRef<Path>>(&self, suffix: T) -> bool;
 
+    /// Returns true if the `Path` is an ancestor of the given `path` by comparing cleaned path
+    /// components rather than strings i.e. `/foo` is an ancestor of `/foo/bar` but not of
+    /// `/foobar`. A path is not its own ancestor.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(Path::new("/foo").is_ancestor_of("/foo/bar"), true);
+    /// assert_eq!(Path::new("/foo").is_ancestor_of("/foobar"), false);
+    /// ```
+    fn is_ancestor_of<T: AsRef<Path>>(&self, path: T) -> bool;
+
     /// Returns true if the `Path` exists and is a directory. Handles path expansion.
     ///
     /// ### Examples
@@ -889,7 +1636,9 @@ pub trait PathExt {
     /// ```
     fn is_dir(&self) -> bool;
 
-    /// Returns true if the `Path` exists and is an executable. Handles path expansion.
+    /// Returns true if the `Path` exists and has any of the execute bits set regardless of who the
+    /// current user is. See `sys::is_executable` to check against the current user. Handles path
+    /// expansion.
     ///
     /// ### Examples
     /// ```
@@ -1044,16 +1793,31 @@ pub trait PathExt {
     /// ```
     fn mode(&self) -> FuResult<u32>;
 
-    /// Returns the final component of the `Path` without an extension if there is one
+    /// Returns the final component of the `Path` without an extension if there is one.
+    /// Only the last extension is stripped and dotfiles are returned unchanged.
     ///
     /// ### Examples
     /// ```
     /// use fungus::prelude::*;
     ///
     /// assert_eq!(PathBuf::from("/foo/bar.foo").name().unwrap(), "bar");
+    /// assert_eq!(PathBuf::from("/foo/bar.tar.gz").name().unwrap(), "bar.tar");
+    /// assert_eq!(PathBuf::from("/foo/.bashrc").name().unwrap(), ".bashrc");
     /// ```
     fn name(&self) -> FuResult<String>;
 
+    /// Returns a new [`PathBuf`] with any backslashes converted to forward slashes and then
+    /// cleaned, for tolerating Windows style paths from e.g. config files. This is opt-in as a
+    /// backslash is a legitimate filename character on unix.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(Path::new("foo\\bar/blah").normalize_separators().unwrap(), PathBuf::from("foo/bar/blah"));
+    /// ```
+    fn normalize_separators(&self) -> FuResult<PathBuf>;
+
     /// Return the permissions for the `Path`
     ///
     /// ### Examples
@@ -1069,35 +1833,94 @@ pub trait PathExt {
     /// assert_eq!(file1.perms().unwrap().mode(), 0o100644);
     /// assert!(sys::remove_all(&tmpdir).is_ok());
     /// ```
-    fn perms(&self) -> FuResult<fs::Permissions>;
+    fn perms(&self) -> FuResult<fs::Permissions>;
+
+    /// Returns the raw target of the link without resolving it. Handles path expansion
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("pathbuf_doc_readlink");
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// let file1 = tmpdir.mash("file1");
+    /// let link1 = tmpdir.mash("link1");
+    /// assert!(sys::mkdir(&tmpdir).is_ok());
+    /// assert!(sys::touch(&file1).is_ok());
+    /// assert!(sys::symlink(&link1, &file1).is_ok());
+    /// assert_eq!(link1.readlink().unwrap(), file1);
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// ```
+    fn readlink(&self) -> FuResult<PathBuf>;
+
+    /// Returns the absolute path for the link target resolving relative targets against the
+    /// link's directory. Handles path expansion.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("pathbuf_doc_readlink_abs");
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// let file1 = tmpdir.mash("file1");
+    /// let link1 = tmpdir.mash("link1");
+    /// assert!(sys::mkdir(&tmpdir).is_ok());
+    /// assert!(sys::touch(&file1).is_ok());
+    /// assert!(sys::symlink(&link1, "file1").is_ok());
+    /// assert_eq!(link1.readlink_abs().unwrap(), file1);
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// ```
+    fn readlink_abs(&self) -> FuResult<PathBuf>;
+
+    /// Returns the `Path` relative to the given `base` by purely lexical processing, emitting
+    /// `..` components where needed. Aligns with the Golang implementation of `filepath.Rel`.
+    /// Both paths are cleaned first. Returns an error if the paths don't share a common root.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(Path::new("/foo/bar1").rel("/foo/bar2").unwrap(), PathBuf::from("../bar1"));
+    /// ```
+    fn rel<T: AsRef<Path>>(&self, base: T) -> FuResult<PathBuf>;
+
+    /// Returns the `Path` relative to the given `Path`
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(PathBuf::from("foo/bar1").relative_from("foo/bar2").unwrap(), PathBuf::from("bar1"));
+    /// ```
+    fn relative_from<T: AsRef<Path>>(&self, path: T) -> FuResult<PathBuf>;
 
-    /// Returns the absolute path for the link target. Handles path expansion
+    /// Returns the `Path` with the current user's home directory prefix collapsed to `~` which is
+    /// the inverse of `expand` and useful for display. The `Path` is cleaned before comparing so
+    /// redundant separators and `.` elements don't prevent a match. Paths outside the home
+    /// directory are returned unchanged.
     ///
     /// ### Examples
     /// ```
     /// use fungus::prelude::*;
     ///
-    /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("pathbuf_doc_readlink");
-    /// assert!(sys::remove_all(&tmpdir).is_ok());
-    /// let file1 = tmpdir.mash("file1");
-    /// let link1 = tmpdir.mash("link1");
-    /// assert!(sys::mkdir(&tmpdir).is_ok());
-    /// assert!(sys::touch(&file1).is_ok());
-    /// assert!(sys::symlink(&link1, &file1).is_ok());
-    /// assert_eq!(link1.readlink().unwrap(), file1);
-    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// let home = user::home_dir().unwrap();
+    /// assert_eq!(home.mash("foo").relative_home().unwrap(), PathBuf::from("~/foo"));
     /// ```
-    fn readlink(&self) -> FuResult<PathBuf>;
+    fn relative_home(&self) -> FuResult<PathBuf>;
 
-    /// Returns the `Path` relative to the given `Path`
+    /// Returns a new [`PathBuf`] with the file extension replaced by the given `ext` or added if
+    /// absent. Only the final extension of multi-dot names is replaced and dotfiles are treated
+    /// as having no extension. An empty `ext` strips the extension entirely. A leading `.` in
+    /// `ext` is ignored.
     ///
     /// ### Examples
     /// ```
     /// use fungus::prelude::*;
     ///
-    /// assert_eq!(PathBuf::from("foo/bar1").relative_from("foo/bar2").unwrap(), PathBuf::from("bar1"));
+    /// assert_eq!(Path::new("foo.c").set_ext("o").unwrap(), PathBuf::from("foo.o"));
+    /// assert_eq!(Path::new("foo.tar.gz").set_ext("xz").unwrap(), PathBuf::from("foo.tar.xz"));
     /// ```
-    fn relative_from<T: AsRef<Path>>(&self, path: T) -> FuResult<PathBuf>;
+    fn set_ext<T: AsRef<str>>(&self, ext: T) -> FuResult<PathBuf>;
 
     /// Set the given [`Permissions`] on the `Path` and return the `Path`
     ///
@@ -1118,6 +1941,18 @@ pub trait PathExt {
     /// ```
     fn setperms(&self, perms: fs::Permissions) -> FuResult<PathBuf>;
 
+    /// Returns the longest common prefix of the `Path` and the given `path` by comparing cleaned
+    /// path components rather than strings. Returns an empty path if nothing is shared.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(Path::new("/foo/bar1").shared_prefix("/foo/bar2"), PathBuf::from("/foo"));
+    /// assert_eq!(Path::new("/foo").shared_prefix("/foobar"), PathBuf::from("/"));
+    /// ```
+    fn shared_prefix<T: AsRef<Path>>(&self, path: T) -> PathBuf;
+
     /// Returns a new [`PathBuf`] with the file extension trimmed off.
     ///
     /// ### Examples
@@ -1190,6 +2025,46 @@ pub trait PathExt {
     fn uid(&self) -> FuResult<u32>;
 }
 
+/// Private implementation of `canonicalize_lossy` for the given absolute `path`. Dangling links
+/// are followed via `read_link` with `links` counting those followed so far to detect loops.
+fn canonicalize_lossy_p(path: PathBuf, links: usize) -> FuResult<PathBuf> {
+    // Canonicalize the longest existing prefix of the raw path so links resolve before `..`
+    let components: Vec<Component> = path.components().collect();
+    let mut i = components.len();
+    let mut real = loop {
+        match components[..i].iter().collect::<PathBuf>().canonicalize() {
+            Ok(real) => break real,
+            Err(err) if i <= 1 => return Err(err.into()),
+            Err(_) => i -= 1,
+        }
+    };
+
+    // Follow a dangling link at the start of the non-existent remainder
+    let rest = &components[i..];
+    if let Some(Component::Normal(name)) = rest.first() {
+        let link = real.join(name);
+        if fs::symlink_metadata(&link).map(|x| x.file_type().is_symlink()).unwrap_or(false) {
+            if links >= 40 {
+                return Err(io::Error::from_raw_os_error(libc::ELOOP).into());
+            }
+            let target = real.join(fs::read_link(&link)?);
+            return canonicalize_lossy_p(rest[1..].iter().fold(target, |acc, x| acc.join(x)), links + 1);
+        }
+    }
+
+    // Clean only the non-existent remainder
+    for component in rest {
+        match component {
+            Component::ParentDir => {
+                real.pop();
+            },
+            Component::Normal(x) => real.push(x),
+            _ => {},
+        }
+    }
+    Ok(real)
+}
+
 impl PathExt for Path {
     fn abs(&self) -> FuResult<PathBuf> {
         abs(self)
@@ -1218,6 +2093,14 @@ impl PathExt for Path {
         self.file_name().ok_or_else(|| PathError::filename_not_found(self))?.to_string()
     }
 
+    fn canonicalize_lossy(&self) -> FuResult<PathBuf> {
+        let path = self.expand()?;
+        match path.is_absolute() {
+            true => canonicalize_lossy_p(path, 0),
+            false => canonicalize_lossy_p(sys::cwd()?.join(path), 0),
+        }
+    }
+
     fn chmod(&self, mode: u32) -> FuResult<()> {
         sys::chmod(self, mode)?;
         Ok(())
@@ -1271,6 +2154,13 @@ impl PathExt for Path {
         Ok(path_buf)
     }
 
+    fn components_str(&self) -> FuResult<Vec<String>> {
+        if self.as_os_str().is_empty() {
+            return Ok(vec![]);
+        }
+        self.clean()?.components().map(|x| x.as_os_str().to_string()).collect()
+    }
+
     fn concat<T: AsRef<str>>(&self, val: T) -> FuResult<PathBuf> {
         Ok(PathBuf::from(format!("{}{}", self.to_string()?, val.as_ref())))
     }
@@ -1294,7 +2184,7 @@ impl PathExt for Path {
 
     fn ext(&self) -> FuResult<String> {
         match self.extension() {
-            Some(val) => val.to_string(),
+            Some(val) => Ok(val.to_string()?.to_lowercase()),
             None => Err(PathError::extension_not_found(self).into()),
         }
     }
@@ -1328,6 +2218,13 @@ impl PathExt for Path {
         }
     }
 
+    fn is_ancestor_of<T: AsRef<Path>>(&self, path: T) -> bool {
+        match (self.clean(), path.as_ref().clean()) {
+            (Ok(ancestor), Ok(path)) => path != ancestor && path.starts_with(&ancestor),
+            _ => false,
+        }
+    }
+
     fn is_dir(&self) -> bool {
         is_dir(self)
     }
@@ -1378,6 +2275,11 @@ impl PathExt for Path {
         self.trim_ext()?.base()
     }
 
+    fn normalize_separators(&self) -> FuResult<PathBuf> {
+        let bytes: Vec<u8> = self.as_os_str().as_bytes().iter().map(|&x| if x == b'\\' { b'/' } else { x }).collect();
+        PathBuf::from(OsStr::from_bytes(&bytes)).clean()
+    }
+
     fn perms(&self) -> FuResult<fs::Permissions> {
         Ok(self.metadata()?.permissions())
     }
@@ -1386,6 +2288,41 @@ impl PathExt for Path {
         readlink(self)
     }
 
+    fn readlink_abs(&self) -> FuResult<PathBuf> {
+        readlink_abs(self)
+    }
+
+    fn rel<T: AsRef<Path>>(&self, base: T) -> FuResult<PathBuf> {
+        let path = self.clean()?;
+        let base = base.as_ref().clean()?;
+        if path == base {
+            return Ok(PathBuf::from("."));
+        }
+        if path.is_absolute() != base.is_absolute() {
+            return Err(PathError::no_common_root(self).into());
+        }
+
+        // Skip past the shared components
+        let x: Vec<Component> = path.components().filter(|x| x != &Component::CurDir).collect();
+        let y: Vec<Component> = base.components().filter(|x| x != &Component::CurDir).collect();
+        let i = x.iter().zip(y.iter()).take_while(|(a, b)| a == b).count();
+
+        // Parent references left in the base can't be resolved lexically
+        if y[i..].contains(&Component::ParentDir) {
+            return Err(PathError::no_common_root(self).into());
+        }
+
+        // Back out of the base then into the path
+        let mut rel = PathBuf::new();
+        for _ in i..y.len() {
+            rel.push(Component::ParentDir);
+        }
+        for component in &x[i..] {
+            rel.push(component);
+        }
+        Ok(rel)
+    }
+
     fn relative_from<T: AsRef<Path>>(&self, base: T) -> FuResult<PathBuf> {
         let path = self.abs()?;
         let base = base.as_ref().abs()?;
@@ -1420,11 +2357,33 @@ impl PathExt for Path {
         Ok(path)
     }
 
+    fn relative_home(&self) -> FuResult<PathBuf> {
+        let home = user::home_dir()?.clean()?;
+        match self.clean()?.strip_prefix(&home) {
+            Ok(rel) => Ok(Path::new("~").join(rel)),
+            Err(_) => Ok(self.to_path_buf()),
+        }
+    }
+
+    fn set_ext<T: AsRef<str>>(&self, ext: T) -> FuResult<PathBuf> {
+        if self.file_name().is_none() {
+            return Err(PathError::filename_not_found(self).into());
+        }
+        Ok(self.with_extension(ext.as_ref().trim_start_matches('.')))
+    }
+
     fn setperms(&self, perms: fs::Permissions) -> FuResult<PathBuf> {
         fs::set_permissions(&self, perms)?;
         Ok(self.to_path_buf())
     }
 
+    fn shared_prefix<T: AsRef<Path>>(&self, path: T) -> PathBuf {
+        match (self.clean(), path.as_ref().clean()) {
+            (Ok(x), Ok(y)) => x.components().zip(y.components()).take_while(|(x, y)| x == y).map(|(x, _)| x).collect(),
+            _ => PathBuf::new(),
+        }
+    }
+
     fn trim_ext(&self) -> FuResult<PathBuf> {
         Ok(match self.extension() {
             Some(val) => self.trim_suffix(format!(".{}", val.to_string()?)),
@@ -1570,14 +2529,52 @@ mod tests {
         assert_eq!(sys::abs("~/foo/bar/../").unwrap(), home.mash("foo"));
         assert_eq!(sys::abs("~/foo/bar/../blah").unwrap(), home.mash("foo/blah"));
 
-        // // Move up the path multiple levels
-        // assert_eq!(sys::abs("./../../foo").unwrap(), home.mash("foo"));
-        // assert_eq!(sys::abs("../../foo").unwrap(), home.mash("foo"));
+        // clean relative paths
+        assert_eq!(sys::abs("foo/../bar").unwrap(), cwd.mash("bar"));
+        assert_eq!(sys::abs("./foo/../bar").unwrap(), cwd.mash("bar"));
+        assert_eq!(sys::abs("foo//bar/./baz/").unwrap(), cwd.mash("foo/bar/baz"));
+        assert_eq!(sys::abs("./foo/./bar/../../baz").unwrap(), cwd.mash("baz"));
+
+        // clean absolute paths
+        assert_eq!(sys::abs("/foo//bar/./baz").unwrap(), PathBuf::from("/foo/bar/baz"));
+        assert_eq!(sys::abs("/foo/bar/../baz/.").unwrap(), PathBuf::from("/foo/baz"));
+        assert_eq!(sys::abs("//foo///bar").unwrap(), PathBuf::from("/foo/bar"));
+
+        // Move up the path multiple levels
+        assert_eq!(sys::abs("./../../foo").unwrap(), prev.dir().unwrap().mash("foo"));
+        assert_eq!(sys::abs("../../foo").unwrap(), prev.dir().unwrap().mash("foo"));
+        assert_eq!(sys::abs("../foo/../bar").unwrap(), prev.mash("bar"));
 
         // // Move up until invalid
         // assert!(sys::abs("../../../../../foo").is_err());
     }
 
+    #[test]
+    fn test_abs_from() {
+        let base = PathBuf::from("/foo/bar");
+
+        // relative paths resolve against the base
+        assert_eq!(sys::abs_from("blah", &base).unwrap(), PathBuf::from("/foo/bar/blah"));
+        assert_eq!(sys::abs_from("./blah", &base).unwrap(), PathBuf::from("/foo/bar/blah"));
+        assert_eq!(sys::abs_from(".", &base).unwrap(), base);
+        assert_eq!(sys::abs_from("../blah", &base).unwrap(), PathBuf::from("/foo/blah"));
+        assert_eq!(sys::abs_from("..", &base).unwrap(), PathBuf::from("/foo"));
+        assert_eq!(sys::abs_from("../../blah/./../foo", &base).unwrap(), PathBuf::from("/foo"));
+
+        // absolute and home paths ignore the base
+        assert_eq!(sys::abs_from("/blah", &base).unwrap(), PathBuf::from("/blah"));
+        assert_eq!(sys::abs_from("~/blah", &base).unwrap(), user::home_dir().unwrap().mash("blah"));
+
+        // relative bases resolve against the current working directory
+        assert_eq!(sys::abs_from("../blah", "foo").unwrap(), sys::cwd().unwrap().mash("blah"));
+        assert_eq!(sys::abs_from("blah", "~").unwrap(), user::home_dir().unwrap().mash("blah"));
+
+        // empty
+        assert_eq!(sys::abs_from("", &base).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::Empty));
+        assert_eq!(sys::abs_from("foo", "").unwrap_err().downcast_ref::<PathError>(), Some(&PathError::Empty));
+        assert_eq!(sys::abs_from("/foo", "").unwrap(), PathBuf::from("/foo"));
+    }
+
     #[test]
     fn test_all_dirs() {
         let tmpdir = setup().mash("path_all_dirs");
@@ -1730,6 +2727,40 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_du() {
+        let tmpdir = setup().mash("path_du");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = tmpdir.mash("file1");
+        let file2 = dir1.mash("file2");
+        let link1 = tmpdir.mash("link1");
+        let hard1 = tmpdir.mash("hard1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // invalid target
+        assert!(sys::du(tmpdir.mash("foobar")).is_err());
+        assert!(sys::du_blocks(tmpdir.mash("foobar")).is_err());
+
+        // files of known sizes
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::write(&file1, vec![0u8; 1000]).is_ok());
+        assert!(sys::write(&file2, vec![0u8; 5000]).is_ok());
+        assert_eq!(sys::du(&file1).unwrap(), 1000);
+        assert_eq!(sys::du(&dir1).unwrap(), 5000);
+        assert_eq!(sys::du(&tmpdir).unwrap(), 6000);
+
+        // symlinks count only the link itself and hard links only once
+        assert!(sys::symlink(&link1, "dir1/file2").is_ok());
+        assert!(fs::hard_link(&file1, &hard1).is_ok());
+        assert_eq!(sys::du(&tmpdir).unwrap(), 6000 + "dir1/file2".len() as u64);
+
+        // on-disk usage includes the directories
+        let blocks: u64 = vec![&tmpdir, &dir1, &file1, &file2, &link1].iter().map(|x| fs::symlink_metadata(x).unwrap().blocks() * 512).sum();
+        assert_eq!(sys::du_blocks(&tmpdir).unwrap(), blocks);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_exists() {
         let tmpdir = setup().mash("path_exists");
@@ -1780,11 +2811,203 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_find() {
+        let tmpdir = setup().mash("path_find");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = dir1.mash("file1.rs");
+        let file2 = dir1.mash("file2.md");
+        let dir2 = tmpdir.mash("dir2");
+        let file3 = dir2.mash("file3.rs");
+        let file4 = tmpdir.mash("file4.rs");
+        let link1 = tmpdir.mash("link1.rs");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // invalid target
+        assert!(sys::find("", |_, _| true).is_err());
+        assert!(sys::find(tmpdir.mash("foobar"), |_, _| true).is_err());
+
+        // Create the fixture tree
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::mkdir(&dir2).is_ok());
+        assert!(sys::write(&file1, "1").is_ok());
+        assert!(sys::write(&file2, "12345").is_ok());
+        assert!(sys::write(&file3, "12345").is_ok());
+        assert!(sys::write(&file4, "").is_ok());
+        assert!(sys::symlink(&link1, &file3).is_ok());
+        assert!(sys::find(&file4, |_, _| true).is_err());
+
+        // depth first sorted by filename
+        assert_iter_eq(sys::find(&tmpdir, |_, _| true).unwrap(), vec![dir1.clone(), file1.clone(), file2.clone(), dir2.clone(), file3.clone(), file4.clone(), link1.clone()]);
+        assert_iter_eq(sys::find(&tmpdir, |_, meta| meta.is_dir()).unwrap(), vec![dir1.clone(), dir2.clone()]);
+
+        // predicates don't follow symlinks
+        assert_iter_eq(sys::find(&tmpdir, sys::by_ext("rs")).unwrap(), vec![file1.clone(), file3.clone(), file4.clone()]);
+        assert_iter_eq(sys::find(&tmpdir, sys::by_ext(".md")).unwrap(), vec![file2.clone()]);
+        assert_iter_eq(sys::find(&tmpdir, sys::larger_than(1)).unwrap(), vec![file2.clone(), file3.clone()]);
+        assert_iter_eq(sys::find(&tmpdir, |path, meta| sys::by_ext("rs")(path, meta) && sys::larger_than(0)(path, meta)).unwrap(), vec![file1.clone(), file3.clone()]);
+
+        // unreadable directories are skipped rather than aborting
+        if !user::is_root() {
+            assert!(sys::chmod(&dir1, 0o000).is_ok());
+            let (paths, errors) = sys::find_with_errors(&tmpdir, sys::by_ext("rs")).unwrap();
+            assert_iter_eq(paths, vec![file3.clone(), file4.clone()]);
+            assert_eq!(errors.len(), 1);
+            assert_iter_eq(sys::find(&tmpdir, sys::by_ext("rs")).unwrap(), vec![file3, file4]);
+            assert!(sys::chmod(&dir1, 0o755).is_ok());
+        }
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_walk() {
+        let tmpdir = setup().mash("path_walk");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = dir1.mash("dir2");
+        let file1 = dir1.mash("file1");
+        let file2 = dir2.mash("file2");
+        let hidden1 = tmpdir.mash(".hidden1");
+        let hidden2 = hidden1.mash("file3");
+        let hidden3 = dir1.mash(".file4");
+        let link1 = tmpdir.mash("link1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // invalid target
+        assert!(sys::walk("").is_err());
+        assert_eq!(sys::walk(&tmpdir).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(&tmpdir)));
+
+        // Create the fixture tree
+        assert!(sys::mkdir(&dir2).is_ok());
+        assert!(sys::mkdir(&hidden1).is_ok());
+        assert!(sys::write(&file1, "1").is_ok());
+        assert!(sys::write(&file2, "12").is_ok());
+        assert!(sys::touch(&hidden2).is_ok());
+        assert!(sys::touch(&hidden3).is_ok());
+        assert!(sys::symlink(&link1, &dir2).is_ok());
+        assert_eq!(sys::walk(&file1).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::is_not_dir(&file1)));
+
+        // depth first sorted by filename
+        let paths = |walker: sys::Walker| walker.map(|x| x.unwrap().into_path()).collect::<Vec<PathBuf>>();
+        assert_iter_eq(paths(sys::walk(&tmpdir).unwrap()), vec![hidden1.clone(), hidden2, dir1.clone(), hidden3, dir2.clone(), file2.clone(), file1.clone(), link1.clone()]);
+
+        // max depth limiting
+        assert_eq!(paths(sys::walk(&tmpdir).unwrap().max_depth(0)).is_empty(), true);
+        assert_iter_eq(paths(sys::walk(&tmpdir).unwrap().max_depth(1)), vec![hidden1.clone(), dir1.clone(), link1.clone()]);
+        assert_iter_eq(paths(sys::walk(&tmpdir).unwrap().max_depth(1).skip_hidden(true)), vec![dir1.clone(), link1.clone()]);
+
+        // hidden files and directory contents are skipped
+        assert_iter_eq(paths(sys::walk(&tmpdir).unwrap().skip_hidden(true)), vec![dir1.clone(), dir2.clone(), file2.clone(), file1.clone(), link1.clone()]);
+
+        // follow links descends into linked directories and caches the target's metadata
+        let entries: Vec<sys::DirEntry> = sys::walk(&tmpdir).unwrap().skip_hidden(true).follow_links(true).map(|x| x.unwrap()).collect();
+        assert_iter_eq(entries.iter().map(|x| x.path().to_path_buf()).collect::<Vec<PathBuf>>(), vec![dir1, dir2, file2, file1, link1.clone(), link1.mash("file2")]);
+        assert_eq!(entries[4].metadata().is_dir(), true);
+        assert_eq!(entries[5].depth(), 2);
+        assert_eq!(entries[5].metadata().len(), 2);
+        let entries: Vec<sys::DirEntry> = sys::walk(&tmpdir).unwrap().max_depth(1).map(|x| x.unwrap()).collect();
+        assert_eq!(entries[2].metadata().file_type().is_symlink(), true);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_rel_to() {
         assert_eq!(sys::rel_to("home").unwrap(), PathBuf::from("/home"));
     }
 
+    #[test]
+    fn test_same_file() {
+        let tmpdir = setup().mash("path_same_file");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let file3 = tmpdir.mash("file3");
+        let link1 = tmpdir.mash("link1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // doesn't exist
+        assert!(sys::same_file(&file1, &file1).is_err());
+
+        // same path, hardlinks and symlinks
+        assert!(sys::write(&file1, "foo").is_ok());
+        assert!(fs::hard_link(&file1, &file2).is_ok());
+        assert!(sys::symlink(&link1, &file1).is_ok());
+        assert_eq!(sys::same_file(&file1, &file1).unwrap(), true);
+        assert_eq!(sys::same_file(&file1, &tmpdir.mash("../path_same_file/file1")).unwrap(), true);
+        assert_eq!(sys::same_file(&file1, &file2).unwrap(), true);
+        assert_eq!(sys::same_file(&link1, &file2).unwrap(), true);
+
+        // copies with identical content
+        assert!(sys::copyfile(&file1, &file3).is_ok());
+        assert_eq!(sys::readstring(&file3).unwrap(), "foo".to_string());
+        assert_eq!(sys::same_file(&file1, &file3).unwrap(), false);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_stat() {
+        let tmpdir = setup().mash("path_stat");
+        let file1 = tmpdir.mash("file1");
+        let dir1 = tmpdir.mash("dir1");
+        let link1 = tmpdir.mash("link1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::write_p(&file1, "this is a test", 0o644).is_ok());
+        assert!(sys::mkdir_p(&dir1, 0o750).is_ok());
+        assert!(sys::symlink(&link1, "file1").is_ok());
+
+        // regular file
+        let stat = sys::stat(&file1).unwrap();
+        assert_eq!(stat.path, file1);
+        assert_eq!(stat.size, 14);
+        assert_eq!(stat.symbolic, "-rw-r--r--");
+        assert_eq!(stat.mode & 0o7777, 0o644);
+        assert_eq!(stat.mtime, file1.metadata().unwrap().modified().unwrap());
+        assert_eq!(stat.uid, user::getuid());
+        assert_eq!(stat.user, user::name().unwrap());
+        assert_eq!(stat.group, group::lookup(stat.gid).unwrap().name);
+
+        // directory
+        assert_eq!(sys::stat(&dir1).unwrap().symbolic, "drwxr-x---");
+
+        // symlinks are followed by stat but not by lstat
+        assert_eq!(sys::stat(&link1).unwrap().symbolic, "-rw-r--r--");
+        assert_eq!(sys::lstat(&link1).unwrap().symbolic, "lrwxrwxrwx");
+        assert_eq!(sys::lstat(&file1).unwrap(), sys::stat(&file1).unwrap());
+
+        // setuid, setgid and sticky bits with and without exec
+        let tests = vec![
+            (0o4755, "-rwsr-xr-x"),
+            (0o4644, "-rwSr--r--"),
+            (0o2755, "-rwxr-sr-x"),
+            (0o2745, "-rwxr-Sr-x"),
+            (0o1777, "-rwxrwxrwt"),
+            (0o1776, "-rwxrwxrwT"),
+            (0o7777, "-rwsrwsrwt"),
+            (0o7000, "---S--S--T"),
+            (0o0000, "----------"),
+        ];
+        for (mode, symbolic) in tests {
+            assert!(sys::chmod(&file1, mode).is_ok());
+            assert_eq!(sys::stat(&file1).unwrap().symbolic, symbolic);
+        }
+        assert!(sys::chmod(&dir1, 0o1777).is_ok());
+        assert_eq!(sys::stat(&dir1).unwrap().symbolic, "drwxrwxrwt");
+
+        // special files
+        assert_eq!(sys::stat("/dev/null").unwrap().symbolic.chars().next(), Some('c'));
+
+        // doesn't exist
+        assert!(sys::stat(tmpdir.mash("foo")).is_err());
+        assert!(sys::lstat(tmpdir.mash("foo")).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_uid() {
         assert!(sys::uid(".").is_ok());
@@ -1804,6 +3027,45 @@ mod tests {
         assert_eq!(sys::is_dir("/foobar"), false);
     }
 
+    #[test]
+    fn test_is_empty() {
+        let tmpdir = setup().mash("path_is_empty");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let link1 = tmpdir.mash("link1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // doesn't exist
+        assert_eq!(sys::is_empty(&tmpdir).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(&tmpdir)));
+        assert_eq!(sys::is_empty_dir(&tmpdir).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(&tmpdir)));
+
+        // empty directory
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert_eq!(sys::is_empty(&tmpdir).unwrap(), true);
+        assert_eq!(sys::is_empty_dir(&tmpdir).unwrap(), true);
+
+        // zero length file
+        assert!(sys::touch(&file1).is_ok());
+        assert_eq!(sys::is_empty(&file1).unwrap(), true);
+        assert_eq!(sys::is_empty_dir(&file1).unwrap(), false);
+
+        // populated directory and non-zero length file
+        assert!(sys::write(&file2, "foo").is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert_eq!(sys::is_empty(&tmpdir).unwrap(), false);
+        assert_eq!(sys::is_empty_dir(&tmpdir).unwrap(), false);
+        assert_eq!(sys::is_empty(&file2).unwrap(), false);
+        assert_eq!(sys::is_empty(&dir1).unwrap(), true);
+
+        // links are followed
+        assert!(sys::symlink(&link1, &dir1).is_ok());
+        assert_eq!(sys::is_empty_dir(&link1).unwrap(), true);
+        assert_eq!(sys::is_empty(&link1).unwrap(), true);
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_is_exec() {
         let tmpdir = setup().mash("path_is_exec");
@@ -1841,6 +3103,48 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_is_readable_writable_executable() {
+        let tmpdir = setup().mash("path_is_readable_writable_executable");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let dir1 = tmpdir.mash("dir1");
+        let root = user::is_root();
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // doesn't exist
+        assert_eq!(sys::is_readable(&file1), false);
+        assert_eq!(sys::is_writable(&file1), false);
+        assert_eq!(sys::is_executable(&file1), false);
+
+        // owner bits
+        assert!(sys::touch_p(&file1, 0o700).is_ok());
+        assert_eq!(sys::is_readable(&file1), true);
+        assert_eq!(sys::is_writable(&file1), true);
+        assert_eq!(sys::is_executable(&file1), true);
+
+        // no bits
+        assert!(sys::touch_p(&file2, 0o000).is_ok());
+        assert_eq!(sys::is_readable(&file2), root);
+        assert_eq!(sys::is_writable(&file2), root);
+        assert_eq!(sys::is_executable(&file2), false);
+
+        // owner bits take precedence over group and other bits
+        assert!(sys::chmod(&file2, 0o077).is_ok());
+        assert_eq!(sys::is_readable(&file2), root);
+        assert_eq!(sys::is_writable(&file2), root);
+        assert_eq!(sys::is_executable(&file2), root);
+
+        // root may search any directory
+        assert!(sys::mkdir_p(&dir1, 0o000).is_ok());
+        assert_eq!(sys::is_readable(&dir1), root);
+        assert_eq!(sys::is_executable(&dir1), root);
+        assert!(sys::chmod(&dir1, 0o755).is_ok());
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_is_readonly() {
         let tmpdir = setup().mash("path_is_readonly");
@@ -1959,6 +3263,72 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_glob_all() {
+        let tmpdir = setup().mash("path_glob_all");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = dir1.mash("dir2");
+        let file1 = dir1.mash("file1");
+        let file2 = dir2.mash("file2");
+        let dir3 = tmpdir.mash("dir3");
+        let file3 = tmpdir.mash("file3");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir2).is_ok());
+        assert!(sys::mkdir(&dir3).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+        assert!(sys::touch(&file2).is_ok());
+        assert!(sys::touch(&file3).is_ok());
+
+        // no matches
+        assert_eq!(sys::glob_all(tmpdir.mash("foo*")).unwrap().len(), 0);
+
+        // recurse matched directories only
+        let paths = sys::glob_all(tmpdir.mash("dir*")).unwrap();
+        assert_iter_eq(paths, vec![dir1.clone(), dir2.clone(), file2.clone(), file1.clone(), dir3.clone()]);
+
+        // all matches and ordering is stable
+        let paths = sys::glob_all(tmpdir.mash("*")).unwrap();
+        assert_iter_eq(&paths, &vec![dir1.clone(), dir2.clone(), file2.clone(), file1.clone(), dir3.clone(), file3]);
+        assert_iter_eq(&paths, &sys::glob_all(tmpdir.mash("*")).unwrap());
+
+        // overlapping matches are distinct
+        let paths = sys::glob_all(tmpdir.mash("**/dir*")).unwrap();
+        assert_iter_eq(paths, vec![dir1, dir2, file2, file1, dir3]);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        // single star doesn't cross separators
+        assert_eq!(sys::glob_match("*.rs", "main.rs").unwrap(), true);
+        assert_eq!(sys::glob_match("*.rs", "main.rs.bak").unwrap(), false);
+        assert_eq!(sys::glob_match("*.rs", "src/main.rs").unwrap(), false);
+        assert_eq!(sys::glob_match("src/*.rs", "src/main.rs").unwrap(), true);
+
+        // double star crosses separators
+        assert_eq!(sys::glob_match("**/*.rs", "main.rs").unwrap(), true);
+        assert_eq!(sys::glob_match("**/*.rs", "src/sys/path.rs").unwrap(), true);
+        assert_eq!(sys::glob_match("src/**/*.rs", "src/sys/path.rs").unwrap(), true);
+        assert_eq!(sys::glob_match("src/**", "src/sys/path.rs").unwrap(), true);
+        assert_eq!(sys::glob_match("src/**/*.rs", "tests/sys/path.rs").unwrap(), false);
+
+        // single character and character classes
+        assert_eq!(sys::glob_match("file?", "file1").unwrap(), true);
+        assert_eq!(sys::glob_match("file?", "file10").unwrap(), false);
+        assert_eq!(sys::glob_match("file[0-9]", "file1").unwrap(), true);
+        assert_eq!(sys::glob_match("file[0-9]", "filea").unwrap(), false);
+        assert_eq!(sys::glob_match("file[!0-9]", "filea").unwrap(), true);
+        assert_eq!(sys::glob_match("file[ab]", "fileb").unwrap(), true);
+
+        // invalid pattern
+        assert!(sys::glob_match("a**b", "ab").is_err());
+        assert!(sys::glob_match("file[", "file").is_err());
+    }
+
     #[test]
     fn test_metadata() {
         let meta = sys::metadata(setup()).unwrap();
@@ -2014,6 +3384,44 @@ mod tests {
             PathBuf::from("/foo2/bar"),
         ];
         assert_iter_eq(sys::parse_paths(":/foo1:/foo2/bar").unwrap(), paths);
+
+        // home is expanded
+        let home = user::home_dir().unwrap();
+        let paths = vec![home.mash("bin"), PathBuf::from("/usr/bin"), home.mash("bin")];
+        assert_iter_eq(sys::parse_paths("~/bin:/usr/bin:~/bin").unwrap(), paths);
+
+        // odd entries are kept as is rather than failing
+        let paths = vec![PathBuf::from("/opt/foo~1/bin"), PathBuf::from("$FOO/bin"), PathBuf::from("~foo~/bin"), PathBuf::from("/usr/bin")];
+        assert!(sys::var("FOO").is_err());
+        assert_iter_eq(sys::parse_paths("/opt/foo~1/bin:$FOO/bin:~foo~/bin:/usr/bin").unwrap(), paths);
+    }
+
+    #[test]
+    fn test_parse_paths_dedup() {
+        let home = user::home_dir().unwrap();
+        let paths = vec![home.mash("bin"), PathBuf::from("/usr/bin")];
+        assert_iter_eq(sys::parse_paths_dedup("~/bin:/usr/bin:~/bin").unwrap(), paths);
+
+        // first occurrence order is preserved
+        let paths = vec![PathBuf::from("/foo2"), PathBuf::from("/foo1"), sys::cwd().unwrap()];
+        assert_iter_eq(sys::parse_paths_dedup("/foo2:/foo1:/foo2::/foo1:").unwrap(), paths);
+    }
+
+    #[test]
+    fn test_join_paths() {
+        // round trip
+        let paths = vec![PathBuf::from("/foo1"), PathBuf::from("/foo2/bar")];
+        let joined = sys::join_paths(paths.clone()).unwrap();
+        assert_eq!(joined, OsString::from("/foo1:/foo2/bar"));
+        assert_iter_eq(sys::parse_paths(joined.to_str().unwrap()).unwrap(), paths);
+
+        // single and empty
+        assert_eq!(sys::join_paths(vec![PathBuf::from("/foo1")]).unwrap(), OsString::from("/foo1"));
+        assert_eq!(sys::join_paths(Vec::<PathBuf>::new()).unwrap(), OsString::new());
+
+        // separator rejection
+        let paths = vec![PathBuf::from("/foo1"), PathBuf::from("/foo2:bar")];
+        assert_eq!(sys::join_paths(paths).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::contains_separator("/foo2:bar")));
     }
 
     #[test]
@@ -2034,6 +3442,41 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_readlink_abs() {
+        let tmpdir = setup().mash("path_readlink_abs");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = tmpdir.mash("file1");
+        let link1 = tmpdir.mash("link1");
+        let link2 = dir1.mash("link2");
+        let link3 = dir1.mash("link3");
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+        assert!(sys::symlink(&link1, "file1").is_ok());
+        assert!(sys::symlink(&link2, "../file1").is_ok());
+        assert!(sys::symlink(&link3, &file1).is_ok());
+
+        // raw vs absolute targets
+        assert_eq!(link1.readlink().unwrap(), PathBuf::from("file1"));
+        assert_eq!(link1.readlink_abs().unwrap(), file1);
+        assert_eq!(link2.readlink().unwrap(), PathBuf::from("../file1"));
+        assert_eq!(sys::readlink_abs(&link2).unwrap(), file1);
+        assert_eq!(link3.readlink().unwrap(), file1);
+        assert_eq!(link3.readlink_abs().unwrap(), file1);
+
+        // links vs regular files
+        assert_eq!(link1.is_symlink(), true);
+        assert_eq!(link2.is_symlink(), true);
+        assert_eq!(file1.is_symlink(), false);
+        assert_eq!(dir1.is_symlink(), false);
+        assert!(file1.readlink_abs().is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     // Path tests
     // ---------------------------------------------------------------------------------------------
 
@@ -2062,7 +3505,64 @@ mod tests {
 
     #[test]
     fn test_pathext_base() {
+        assert!(PathBuf::from("").base().is_err());
         assert_eq!("bar", PathBuf::from("/foo/bar").base().unwrap());
+        assert_eq!("bar.tar.gz", PathBuf::from("/foo/bar.tar.gz").base().unwrap());
+        assert_eq!(".bashrc", PathBuf::from("/foo/.bashrc").base().unwrap());
+    }
+
+    #[test]
+    fn test_pathext_canonicalize_lossy() {
+        let tmpdir = setup().mash("path_pathbuf_canonicalize_lossy");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = dir1.mash("file1");
+        let link1 = tmpdir.mash("link1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+        assert!(sys::symlink(&link1, "dir1").is_ok());
+        let real = tmpdir.canonicalize().unwrap();
+
+        // fully existing paths match canonicalize
+        assert_eq!(file1.canonicalize_lossy().unwrap(), real.mash("dir1/file1"));
+        assert_eq!(link1.mash("file1").canonicalize_lossy().unwrap(), real.mash("dir1/file1"));
+        assert_eq!(link1.canonicalize_lossy().unwrap(), link1.canonicalize().unwrap());
+        assert_eq!(PathBuf::from("/").canonicalize_lossy().unwrap(), PathBuf::from("/"));
+
+        // partially existing paths resolve the existing prefix
+        assert!(link1.mash("not/yet").canonicalize().is_err());
+        assert_eq!(link1.mash("not/yet").canonicalize_lossy().unwrap(), real.mash("dir1/not/yet"));
+        assert_eq!(link1.mash("not/./yet/../file2").canonicalize_lossy().unwrap(), real.mash("dir1/not/file2"));
+        assert_eq!(tmpdir.mash("bogus").canonicalize_lossy().unwrap(), real.mash("bogus"));
+
+        // relative paths are made absolute first
+        assert_eq!(PathBuf::from("tests/temp/path_pathbuf_canonicalize_lossy/link1/foo").canonicalize_lossy().unwrap(), real.mash("dir1/foo"));
+
+        // links resolve before parent directories
+        let other = tmpdir.mash("other/dir");
+        let link2 = tmpdir.mash("link2");
+        assert!(sys::mkdir(&other).is_ok());
+        assert!(sys::symlink(&link2, &other).is_ok());
+        assert_eq!(link2.mash("x").canonicalize_lossy().unwrap(), real.mash("other/dir/x"));
+        let raw = PathBuf::from(format!("{}/../x", link2.display()));
+        assert_eq!(raw.canonicalize_lossy().unwrap(), real.mash("other/x"));
+        assert!(sys::touch(tmpdir.mash("other/x")).is_ok());
+        assert_eq!(raw.canonicalize_lossy().unwrap(), raw.canonicalize().unwrap());
+
+        // dangling links are followed
+        let link3 = tmpdir.mash("link3");
+        assert!(sys::symlink(&link3, "dir1/missing").is_ok());
+        assert_eq!(link3.canonicalize_lossy().unwrap(), real.mash("dir1/missing"));
+        assert_eq!(link3.mash("foo").canonicalize_lossy().unwrap(), real.mash("dir1/missing/foo"));
+
+        // link loops are detected
+        let loop1 = tmpdir.mash("loop1");
+        assert!(sys::symlink(&loop1, "loop1").is_ok());
+        assert!(loop1.canonicalize_lossy().is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
     #[test]
@@ -2124,6 +3624,11 @@ mod tests {
             ("../foo", "../foo/"),
             ("../foo/bar", "../foo/bar"),
             ("..", "../foo/.."),
+            ("../bar", "foo/../../bar"),
+            ("c", "a/b/../../c"),
+            ("/c", "/a/b/../../c"),
+            ("/foo", "/../../foo"),
+            ("../../c", "a/../../../c"),
             ("~/foo", "~/foo"),
         ];
         for test in tests {
@@ -2131,6 +3636,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pathext_components_str() {
+        // absolute
+        assert_eq!(Path::new("/").components_str().unwrap(), vec!["/"]);
+        assert_eq!(Path::new("/foo/bar").components_str().unwrap(), vec!["/", "foo", "bar"]);
+        assert_eq!(Path::new("//foo//bar/").components_str().unwrap(), vec!["/", "foo", "bar"]);
+        assert_eq!(Path::new("/foo/node_modules/bar").components_str().unwrap().contains(&"node_modules".to_string()), true);
+
+        // relative
+        assert_eq!(Path::new("").components_str().unwrap(), Vec::<String>::new());
+        assert_eq!(Path::new(".").components_str().unwrap(), vec!["."]);
+        assert_eq!(Path::new("foo").components_str().unwrap(), vec!["foo"]);
+        assert_eq!(Path::new("./foo/./bar").components_str().unwrap(), vec!["foo", "bar"]);
+        assert_eq!(Path::new("~/foo").components_str().unwrap(), vec!["~", "foo"]);
+
+        // parent references
+        assert_eq!(Path::new("/foo/../bar").components_str().unwrap(), vec!["/", "bar"]);
+        assert_eq!(Path::new("/../foo").components_str().unwrap(), vec!["/", "foo"]);
+        assert_eq!(Path::new("foo/../../bar").components_str().unwrap(), vec!["..", "bar"]);
+        assert_eq!(Path::new("../../foo").components_str().unwrap(), vec!["..", "..", "foo"]);
+        assert_eq!(Path::new("foo/..").components_str().unwrap(), vec!["."]);
+    }
+
     #[test]
     fn test_pathext_concat() {
         assert_eq!(Path::new("").concat(".rs").unwrap(), PathBuf::from(".rs"));
@@ -2189,12 +3717,46 @@ mod tests {
         assert_eq!(PathBuf::from("~/foo/$PATHEXT_EXPAND/blah").expand().unwrap(), home.mash("foo/bar/blah"));
     }
 
+    #[test]
+    fn test_expand_vars() {
+        sys::set_var("PATH_EXPAND_VARS", "bar");
+        sys::set_var("PATH_EXPAND_VARS_EMPTY", "");
+        sys::unset_var("PATH_EXPAND_VARS_UNDEFINED");
+
+        // whole and partial segments
+        assert_eq!(sys::expand("$PATH_EXPAND_VARS/app").unwrap(), PathBuf::from("bar/app"));
+        assert_eq!(sys::expand("/foo/${PATH_EXPAND_VARS}").unwrap(), PathBuf::from("/foo/bar"));
+        assert_eq!(sys::expand("/foo/${PATH_EXPAND_VARS}_$PATH_EXPAND_VARS.txt").unwrap(), PathBuf::from("/foo/bar_bar.txt"));
+        assert_eq!(sys::expand("/foo/pre$PATH_EXPAND_VARS").unwrap(), PathBuf::from("/foo/prebar"));
+        assert_eq!(sys::expand("/foo/$PATH_EXPAND_VARS_EMPTY/bar").unwrap(), PathBuf::from("/foo//bar"));
+
+        // a $ without a variable name is kept
+        assert_eq!(sys::expand("/foo/$/bar$").unwrap(), PathBuf::from("/foo/$/bar$"));
+        assert_eq!(sys::expand("/foo/$1").unwrap(), PathBuf::from("/foo/$1"));
+
+        // undefined and invalid variables
+        assert_eq!(sys::expand("/foo/$PATH_EXPAND_VARS_UNDEFINED").unwrap_err().downcast_ref::<PathError>(), Some(&PathError::undefined_var("PATH_EXPAND_VARS_UNDEFINED")));
+        assert_eq!(sys::expand("/foo/${PATH_EXPAND_VARS_UNDEFINED}").unwrap_err().downcast_ref::<PathError>(), Some(&PathError::undefined_var("PATH_EXPAND_VARS_UNDEFINED")));
+        assert_eq!(sys::expand("/foo/${PATH_EXPAND_VARS").unwrap_err().downcast_ref::<PathError>(), Some(&PathError::invalid_expansion("/foo/${PATH_EXPAND_VARS")));
+    }
+
     #[test]
     fn test_pathext_ext() {
         assert!(PathBuf::from("").ext().is_err());
         assert!(PathBuf::from("foo").ext().is_err());
         assert_eq!(PathBuf::from("foo.exe").ext().unwrap(), "exe");
         assert_eq!(PathBuf::from("/foo/bar.exe").ext().unwrap(), "exe");
+
+        // lowercased
+        assert_eq!(PathBuf::from("foo.EXE").ext().unwrap(), "exe");
+
+        // dot files have no extension
+        assert!(PathBuf::from(".bashrc").ext().is_err());
+        assert!(PathBuf::from("/foo/.bashrc").ext().is_err());
+        assert_eq!(PathBuf::from("/foo/bar").ext().unwrap_err().downcast_ref::<PathError>(), Some(&PathError::extension_not_found("/foo/bar")));
+
+        // multiple dots
+        assert_eq!(PathBuf::from("foo.tar.gz").ext().unwrap(), "gz");
     }
 
     #[test]
@@ -2221,6 +3783,15 @@ mod tests {
         let path = PathBuf::from("/foo/bar");
         assert_eq!(path.has_prefix("/foo"), true);
         assert_eq!(path.has_prefix("foo"), false);
+
+        // substring that is not a prefix
+        assert_eq!(path.has_prefix("bar"), false);
+        assert_eq!(path.has_prefix("/bar"), false);
+
+        // empty prefix
+        assert_eq!(path.has_prefix(""), true);
+        assert_eq!(PathBuf::from("").has_prefix(""), true);
+        assert_eq!(PathBuf::from("").has_prefix("/foo"), false);
     }
 
     #[test]
@@ -2230,6 +3801,27 @@ mod tests {
         assert_eq!(path.has_suffix("/bar"), true);
     }
 
+    #[test]
+    fn test_pathext_is_ancestor_of() {
+        assert_eq!(Path::new("/foo").is_ancestor_of("/foo/bar"), true);
+        assert_eq!(Path::new("/foo/").is_ancestor_of("/foo/bar/blah"), true);
+        assert_eq!(Path::new("/").is_ancestor_of("/foo"), true);
+        assert_eq!(Path::new("foo").is_ancestor_of("foo/bar"), true);
+        assert_eq!(Path::new("/foo/bar/..").is_ancestor_of("/foo/./blah"), true);
+
+        // string prefix false positives
+        assert_eq!(Path::new("/foo").is_ancestor_of("/foobar"), false);
+        assert_eq!(PathBuf::from("/foo").has_prefix("/foobar"), false);
+        assert_eq!(PathBuf::from("/foobar").has_prefix("/foo"), true);
+
+        // not an ancestor
+        assert_eq!(Path::new("/foo").is_ancestor_of("/foo"), false);
+        assert_eq!(Path::new("/foo/bar").is_ancestor_of("/foo"), false);
+        assert_eq!(Path::new("/foo").is_ancestor_of("foo/bar"), false);
+        assert_eq!(Path::new("/foo").is_ancestor_of("/foo/bar/../../foobar"), false);
+        assert_eq!(Path::new("").is_ancestor_of("/foo"), false);
+    }
+
     #[test]
     fn test_pathext_is_dir() {
         let tmpdir = setup().mash("path_pathext_is_dir");
@@ -2300,9 +3892,21 @@ mod tests {
     fn test_pathext_mash() {
         // strips off root on path
         assert_eq!(Path::new("/foo").mash("/bar"), PathBuf::from("/foo/bar"));
+        assert_eq!(Path::new("foo").mash("/bar/blah"), PathBuf::from("foo/bar/blah"));
+        assert_eq!(Path::new("/").mash("/bar"), PathBuf::from("/bar"));
 
         // strips off trailing slashes
         assert_eq!(Path::new("/foo").mash("bar/"), PathBuf::from("/foo/bar"));
+        assert_eq!(Path::new("/foo/").mash("bar//"), PathBuf::from("/foo/bar"));
+        assert_eq!(Path::new("/foo/").mash("/"), PathBuf::from("/foo"));
+
+        // empty operands
+        assert_eq!(Path::new("/foo").mash(""), PathBuf::from("/foo"));
+        assert_eq!(Path::new("").mash("bar"), PathBuf::from("bar"));
+        assert_eq!(Path::new("").mash(""), PathBuf::new());
+
+        // sys version matches
+        assert_eq!(sys::mash("/foo", "/bar"), Path::new("/foo").mash("/bar"));
     }
 
     #[test]
@@ -2324,12 +3928,39 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_pathext_normalize_separators() {
+        // mixed separators
+        assert_eq!(Path::new("foo\\bar").normalize_separators().unwrap(), PathBuf::from("foo/bar"));
+        assert_eq!(Path::new("foo\\bar/blah").normalize_separators().unwrap(), PathBuf::from("foo/bar/blah"));
+        assert_eq!(Path::new("\\foo\\\\bar\\").normalize_separators().unwrap(), PathBuf::from("/foo/bar"));
+        assert_eq!(Path::new("foo\\..\\bar\\.\\blah").normalize_separators().unwrap(), PathBuf::from("bar/blah"));
+
+        // no separators
+        assert_eq!(Path::new("foo").normalize_separators().unwrap(), PathBuf::from("foo"));
+        assert_eq!(Path::new("").normalize_separators().unwrap(), PathBuf::from("."));
+
+        // backslashes are left alone by default
+        assert_eq!(Path::new("foo\\bar").clean().unwrap(), PathBuf::from("foo\\bar"));
+    }
+
     #[test]
     fn test_pathext_name() {
         assert!(PathBuf::from("").name().is_err());
         assert_eq!(PathBuf::from("foo").name().unwrap(), "foo");
         assert_eq!(PathBuf::from("foo.exe").name().unwrap(), "foo");
         assert_eq!(PathBuf::from("/foo/bar.exe").name().unwrap(), "bar");
+
+        // multi-dot names only strip the last extension
+        assert_eq!(PathBuf::from("/foo/bar.tar.gz").name().unwrap(), "bar.tar");
+        assert_eq!(PathBuf::from("bar.tar.gz").name().unwrap(), "bar.tar");
+
+        // extensionless names
+        assert_eq!(PathBuf::from("/foo/bar").name().unwrap(), "bar");
+
+        // dotfiles are unchanged
+        assert_eq!(PathBuf::from(".bashrc").name().unwrap(), ".bashrc");
+        assert_eq!(PathBuf::from("/home/foo/.bashrc").name().unwrap(), ".bashrc");
     }
 
     #[test]
@@ -2345,6 +3976,31 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_pathext_set_ext() {
+        // replace
+        assert_eq!(Path::new("foo.c").set_ext("o").unwrap(), PathBuf::from("foo.o"));
+        assert_eq!(Path::new("/foo/bar.c").set_ext(".o").unwrap(), PathBuf::from("/foo/bar.o"));
+        assert_eq!(Path::new("foo.tar.gz").set_ext("xz").unwrap(), PathBuf::from("foo.tar.xz"));
+        assert_eq!(Path::new("foo.d/bar.c").set_ext("o").unwrap(), PathBuf::from("foo.d/bar.o"));
+
+        // add
+        assert_eq!(Path::new("foo").set_ext("o").unwrap(), PathBuf::from("foo.o"));
+        assert_eq!(Path::new("foo.d/bar").set_ext("o").unwrap(), PathBuf::from("foo.d/bar.o"));
+        assert_eq!(Path::new(".bashrc").set_ext("bak").unwrap(), PathBuf::from(".bashrc.bak"));
+
+        // strip
+        assert_eq!(Path::new("foo.c").set_ext("").unwrap(), PathBuf::from("foo"));
+        assert_eq!(Path::new("foo.tar.gz").set_ext("").unwrap(), PathBuf::from("foo.tar"));
+        assert_eq!(Path::new(".bashrc").set_ext("").unwrap(), PathBuf::from(".bashrc"));
+        assert_eq!(Path::new(".bashrc.bak").set_ext("").unwrap(), PathBuf::from(".bashrc"));
+
+        // no filename
+        assert_eq!(Path::new("").set_ext("o").unwrap_err().downcast_ref::<PathError>(), Some(&PathError::filename_not_found("")));
+        assert_eq!(Path::new("/").set_ext("o").unwrap_err().downcast_ref::<PathError>(), Some(&PathError::filename_not_found("/")));
+        assert_eq!(Path::new("foo/..").set_ext("o").unwrap_err().downcast_ref::<PathError>(), Some(&PathError::filename_not_found("foo/..")));
+    }
+
     #[test]
     fn test_pathext_setperms() {
         let tmpdir = setup().mash("path_pathbuf_setperms");
@@ -2362,6 +4018,48 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_pathext_shared_prefix() {
+        assert_eq!(Path::new("/foo/bar1").shared_prefix("/foo/bar2"), PathBuf::from("/foo"));
+        assert_eq!(Path::new("/foo/bar/blah1").shared_prefix("/foo/bar/blah2/file"), PathBuf::from("/foo/bar"));
+        assert_eq!(Path::new("/foo/bar").shared_prefix("/foo/bar"), PathBuf::from("/foo/bar"));
+        assert_eq!(Path::new("/foo/bar").shared_prefix("/foo/bar/blah"), PathBuf::from("/foo/bar"));
+        assert_eq!(Path::new("/foo//bar/./").shared_prefix("/foo/bar/../bar/blah"), PathBuf::from("/foo/bar"));
+        assert_eq!(Path::new("foo/bar").shared_prefix("foo/blah"), PathBuf::from("foo"));
+
+        // nothing shared beyond the root
+        assert_eq!(Path::new("/foo").shared_prefix("/foobar"), PathBuf::from("/"));
+        assert_eq!(Path::new("/foo").shared_prefix("foo"), PathBuf::new());
+        assert_eq!(Path::new("").shared_prefix("/foo"), PathBuf::new());
+    }
+
+    #[test]
+    fn test_pathext_rel() {
+        // same path
+        assert_eq!(Path::new("/foo/bar").rel("/foo/bar").unwrap(), PathBuf::from("."));
+        assert_eq!(Path::new("foo/./bar").rel("foo/bar/").unwrap(), PathBuf::from("."));
+
+        // sibling directories
+        assert_eq!(Path::new("/foo/bar1").rel("/foo/bar2").unwrap(), PathBuf::from("../bar1"));
+        assert_eq!(Path::new("foo1/bar1").rel("foo2/bar2").unwrap(), PathBuf::from("../../foo1/bar1"));
+        assert_eq!(Path::new("/a/b/c").rel("/a/x/y/z").unwrap(), PathBuf::from("../../../b/c"));
+
+        // nested children
+        assert_eq!(Path::new("/foo/bar/blah").rel("/foo").unwrap(), PathBuf::from("bar/blah"));
+        assert_eq!(Path::new("foo/bar").rel(".").unwrap(), PathBuf::from("foo/bar"));
+        assert_eq!(Path::new("/foo").rel("/foo/bar/blah").unwrap(), PathBuf::from("../.."));
+
+        // parent references
+        assert_eq!(Path::new("../foo").rel("bar").unwrap(), PathBuf::from("../../foo"));
+        assert_eq!(Path::new("/foo/../bar").rel("/foo").unwrap(), PathBuf::from("../bar"));
+
+        // no common root
+        assert!(Path::new("/foo").rel("foo").is_err());
+        assert!(Path::new("foo").rel("/foo").is_err());
+        assert!(Path::new("foo").rel("../bar").is_err());
+        assert_eq!(Path::new("/foo").rel("bar").unwrap_err().downcast_ref::<PathError>(), Some(&PathError::no_common_root("/foo")));
+    }
+
     #[test]
     fn test_pathext_relative_from() {
         let cwd = sys::cwd().unwrap();
@@ -2382,6 +4080,29 @@ mod tests {
         assert_eq!(PathBuf::from("blah1/foo1/bar1").relative_from("blah2/foo2/bar2").unwrap(), PathBuf::from("../../blah1/foo1/bar1"));
     }
 
+    #[test]
+    fn test_pathext_relative_home() {
+        let home = user::home_dir().unwrap();
+
+        // inside home
+        assert_eq!(home.mash("foo").relative_home().unwrap(), PathBuf::from("~/foo"));
+        assert_eq!(home.mash("foo/bar").relative_home().unwrap(), PathBuf::from("~/foo/bar"));
+
+        // home itself
+        assert_eq!(home.relative_home().unwrap(), PathBuf::from("~"));
+
+        // unclean paths
+        let home_str = home.to_string().unwrap();
+        assert_eq!(PathBuf::from(format!("{}/./foo", home_str)).relative_home().unwrap(), PathBuf::from("~/foo"));
+        assert_eq!(PathBuf::from(format!("{}//foo", home_str)).relative_home().unwrap(), PathBuf::from("~/foo"));
+        assert_eq!(PathBuf::from(format!("{}/bar/../foo", home_str)).relative_home().unwrap(), PathBuf::from("~/foo"));
+
+        // outside home
+        assert_eq!(PathBuf::from("/etc/hosts").relative_home().unwrap(), PathBuf::from("/etc/hosts"));
+        assert_eq!(PathBuf::from("foo/bar").relative_home().unwrap(), PathBuf::from("foo/bar"));
+        assert_eq!(PathBuf::from(format!("{}foo", home.to_string().unwrap())).relative_home().unwrap(), PathBuf::from(format!("{}foo", home.to_string().unwrap())));
+    }
+
     #[test]
     fn test_pathext_trim_ext() {
         assert_eq!(PathBuf::from("").trim_ext().unwrap(), PathBuf::new());
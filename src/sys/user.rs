@@ -1,14 +1,22 @@
 use crate::{
+    core::defer,
     errors::*,
-    sys::{self, PathExt},
+    sys::{self, group::Group, PathExt},
+};
+use std::{
+    ffi::CString,
+    io, iter, mem,
+    ops::Deref,
+    path::{Path, PathBuf},
+    ptr,
 };
-use std::{io, iter, mem, path::PathBuf, ptr};
 
 // Implementation in Rust for the XDB Base Directory Specification
 // https://wiki.archlinux.org/index.php/XDG_Base_Directory
 // -------------------------------------------------------------------------------------------------
 
-/// Returns the full path to the current user's home directory.
+/// Returns the full path to the current user's home directory. Falls back on the passwd database
+/// when `$HOME` is unset or empty.
 ///
 /// Alternate implementation as the Rust std::env::home_dir implementation which has be deprecated
 /// https://doc.rust-lang.org/std/env/fn.home_dir.html
@@ -20,9 +28,31 @@ use std::{io, iter, mem, path::PathBuf, ptr};
 /// assert!(user::home_dir().is_ok());
 /// ```
 pub fn home_dir() -> FuResult<PathBuf> {
-    let home = sys::var("HOME")?;
-    let dir = PathBuf::from(home);
-    Ok(dir)
+    match sys::var("HOME") {
+        Ok(home) if !home.is_empty() => Ok(PathBuf::from(home)),
+        _ => home_p(lookup(getuid())?),
+    }
+}
+
+/// Returns the full path to the home directory of the user with the given `name` as recorded in
+/// the passwd database. Useful when a root process sets up files for another account.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(user::home_dir_of("root").unwrap().is_absolute());
+/// ```
+pub fn home_dir_of<T: AsRef<str>>(name: T) -> FuResult<PathBuf> {
+    home_p(lookup_by_name(name)?)
+}
+
+/// Private implementation to return the given `user`'s home directory erroring if it isn't set
+fn home_p(user: User) -> FuResult<PathBuf> {
+    if user.home.empty() {
+        return Err(UserError::home_not_found(user.name).into());
+    }
+    Ok(user.home)
 }
 
 /// Returns the full path to the current user's config directory.
@@ -101,10 +131,11 @@ pub fn runtime_dir() -> PathBuf {
     }
 }
 
-/// Returns the full path to a newly created directory in `/tmp` that can be used for temporary
-/// work. The returned path will be checked for uniqueness and created with a random suffix and
-/// the given `prefix`. It is up to the calling code to ensure the directory returned is
-/// properly cleaned up when done with.
+/// Returns the full path to a newly created directory that can be used for temporary work. The
+/// directory is created under `$TMPDIR` when set else under `user::runtime_dir()` which falls
+/// back on `/tmp`. The returned path will be checked for uniqueness and created with a random
+/// suffix and the given `prefix`. It is up to the calling code to ensure the directory returned
+/// is properly cleaned up when done with.
 ///
 /// ### Examples
 /// ```
@@ -118,15 +149,108 @@ pub fn runtime_dir() -> PathBuf {
 /// assert_eq!(tmpdir.exists(), false);
 /// ```
 pub fn temp_dir<T: AsRef<str>>(prefix: T) -> FuResult<PathBuf> {
+    let root = temp_root()?;
     loop {
         let suffix: String = iter::repeat_with(fastrand::alphanumeric).take(8).collect();
-        let dir = PathBuf::from(format!("/tmp/{}-{}", prefix.as_ref(), suffix));
+        let dir = root.mash(format!("{}-{}", prefix.as_ref(), suffix));
         if !dir.exists() {
             return sys::mkdir(&dir);
         }
     }
 }
 
+/// Returns the root directory to create temporary files and directories under i.e. `$TMPDIR` when
+/// set else `user::runtime_dir()` which falls back on `/tmp`.
+pub(crate) fn temp_root() -> FuResult<PathBuf> {
+    temp_root_p(sys::var("TMPDIR").ok())
+}
+
+/// Private implementation of temp_root taking the `$TMPDIR` value as a parameter
+fn temp_root_p(tmpdir: Option<String>) -> FuResult<PathBuf> {
+    match tmpdir {
+        Some(x) if !x.is_empty() => PathBuf::from(x).abs(),
+        _ => Ok(runtime_dir()),
+    }
+}
+
+/// TempDir provides a temporary directory that is removed recursively when dropped
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf, // path to the temporary directory
+    persist: bool, // skip removal on drop
+}
+
+impl TempDir {
+    /// Returns the path to the temporary directory
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpdir = user::scoped_temp_dir("foo").unwrap();
+    /// assert_eq!(tmpdir.path().exists(), true);
+    /// ```
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Consume the handle without removing the directory and return its path. It is then up to
+    /// the calling code to clean up the directory.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpdir = user::scoped_temp_dir("foo").unwrap().persist();
+    /// assert_eq!(tmpdir.exists(), true);
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// ```
+    pub fn persist(mut self) -> PathBuf {
+        self.persist = true;
+        self.path.clone()
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if !self.persist {
+            let _ = sys::remove_all(&self.path);
+        }
+    }
+}
+
+/// Wraps `temp_dir` to return a `TempDir` handle that removes the directory recursively once
+/// it goes out of scope unless `persist` is called.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let path = {
+///     let tmpdir = user::scoped_temp_dir("foo").unwrap();
+///     assert_eq!(tmpdir.exists(), true);
+///     tmpdir.to_path_buf()
+/// };
+/// assert_eq!(path.exists(), false);
+/// ```
+pub fn scoped_temp_dir<T: AsRef<str>>(prefix: T) -> FuResult<TempDir> {
+    Ok(TempDir { path: temp_dir(prefix)?, persist: false })
+}
+
 /// Returns the current user's data directories.
 /// List of directories seperated by : (analogous to PATH).
 /// Defaults to /usr/local/share:/usr/share.
@@ -162,7 +286,7 @@ pub fn config_dirs() -> FuResult<Vec<PathBuf>> {
 }
 
 /// Returns the current user's path directories.
-/// List of directories seperated by :
+/// List of directories seperated by : with duplicates dropped
 ///
 /// ### Examples
 /// ```
@@ -171,7 +295,7 @@ pub fn config_dirs() -> FuResult<Vec<PathBuf>> {
 /// assert!(user::path_dirs().is_ok());
 /// ```
 pub fn path_dirs() -> FuResult<Vec<PathBuf>> {
-    sys::parse_paths(sys::var("PATH")?)
+    sys::parse_paths_dedup(sys::var("PATH")?)
 }
 
 // User functions
@@ -185,6 +309,7 @@ pub struct User {
     pub name: String,       // user name
     pub home: PathBuf,      // user home
     pub shell: PathBuf,     // user shell
+    pub gecos: String,      // user information e.g. full name, office and phone
     pub ruid: u32,          // real user id behind sudo
     pub rgid: u32,          // real user group id behind sudo
     pub realname: String,   // real user name behind sudo
@@ -193,6 +318,55 @@ pub struct User {
 }
 
 impl User {
+    /// Returns the user's full name i.e. the first comma separated field of the GECOS, which
+    /// will be empty if the GECOS is empty.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let user = user::User { gecos: "John Doe,Room 1,555-1234,,".to_string(), ..Default::default() };
+    /// assert_eq!(user.display_name(), "John Doe");
+    /// ```
+    pub fn display_name(&self) -> &str {
+        self.gecos.split(',').next().unwrap_or_default().trim()
+    }
+
+    /// Returns all the groups the user belongs to including the primary group
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let user = user::current().unwrap();
+    /// assert!(user.groups().unwrap().iter().any(|x| x.gid == user.gid));
+    /// ```
+    pub fn groups(&self) -> FuResult<Vec<Group>> {
+        let name = CString::new(self.name.as_str())?;
+        let mut gids: Vec<libc::gid_t> = vec![0; 32];
+        loop {
+            let mut ngroups = gids.len() as libc::c_int;
+            let ret = unsafe { libc::getgrouplist(name.as_ptr(), self.gid, gids.as_mut_ptr(), &mut ngroups) };
+
+            // Users with many groups may not fit into the buffer, ngroups holds the required size
+            if ret == -1 {
+                gids.resize(std::cmp::max(ngroups as usize, gids.len() * 2), 0);
+                continue;
+            }
+            gids.truncate(ngroups as usize);
+            break;
+        }
+
+        // The primary group may be listed more than once
+        let mut groups: Vec<Group> = vec![];
+        for gid in gids {
+            if !groups.iter().any(|x| x.gid == gid) {
+                groups.push(sys::group::lookup(gid)?);
+            }
+        }
+        Ok(groups)
+    }
+
     /// Returns true if the user is root
     ///
     /// ### Examples
@@ -206,6 +380,35 @@ impl User {
     }
 }
 
+/// Returns all users in the passwd database in the order they are listed. The database is
+/// always closed again even on error.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(user::all().unwrap().iter().any(|x| x.uid == 0));
+/// ```
+pub fn all() -> FuResult<Vec<User>> {
+    let mut users = vec![];
+    let mut buf = vec![0; 2048];
+    unsafe { libc::setpwent() };
+    let _guard = defer(|| unsafe { libc::endpwent() });
+    loop {
+        let mut res = ptr::null_mut::<libc::passwd>();
+        let mut passwd = unsafe { mem::zeroed::<libc::passwd>() };
+        match unsafe { libc::getpwent_r(&mut passwd, buf.as_mut_ptr(), buf.len(), &mut res) } {
+            // Entry didn't fit in the buffer so grow it and retry the same entry
+            libc::ERANGE => buf.resize(buf.len() * 2, 0),
+            libc::ENOENT => break,
+            0 if !res.is_null() => users.push(to_user(&passwd)?),
+            0 => break,
+            err => return Err(io::Error::from_raw_os_error(err).into()),
+        }
+    }
+    Ok(users)
+}
+
 /// Get the current user
 ///
 /// ### Examples
@@ -237,6 +440,30 @@ pub fn drop_sudo() -> FuResult<()> {
     }
 }
 
+/// Returns true if a user with the given user id exists.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(user::exists_by_id(user::getuid()), true);
+/// ```
+pub fn exists_by_id(uid: u32) -> bool {
+    lookup(uid).is_ok()
+}
+
+/// Returns true if a user with the given user name exists.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(user::exists_by_name("root"), true);
+/// ```
+pub fn exists_by_name<T: AsRef<str>>(name: T) -> bool {
+    lookup_by_name(name).is_ok()
+}
+
 /// Returns the user ID for the current user.
 ///
 /// ### Examples
@@ -337,53 +564,41 @@ pub fn lookup(uid: u32) -> FuResult<User> {
     if res.is_null() || res != &mut passwd {
         return Err(UserError::does_not_exist_by_id(uid).into());
     }
+    to_user(&passwd)
+}
 
-    // Convert libc::passwd object into a User object
-    //----------------------------------------------------------------------------------------------
-    let gid = passwd.pw_gid;
-
-    // User name for the lookedup user. We always want this and it should always exist.
-    let username = unsafe { sys::libc::to_string(passwd.pw_name)? };
-
-    // Will almost always be a single 'x' as the passwd is in the shadow database
-    // let userpwd = unsafe { crate::sys::libc::to_string(passwd.pw_passwd)? };
-
-    // User home directory e.g. '/home/<user>'. Might be a null pointer indicating the system default
-    // should be used
-    let userhome = unsafe { sys::libc::to_string(passwd.pw_dir) }.unwrap_or_default();
-
-    // User shell e.g. '/bin/bash'. Might be a null pointer indicating the system default should be used
-    let usershell = unsafe { sys::libc::to_string(passwd.pw_shell) }.unwrap_or_default();
+/// Lookup a user by user name
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(user::lookup_by_name("root").unwrap().uid, 0);
+/// ```
+pub fn lookup_by_name<T: AsRef<str>>(name: T) -> FuResult<User> {
+    let name = name.as_ref();
+    let cname = match CString::new(name) {
+        Ok(x) => x,
+        Err(_) => return Err(UserError::does_not_exist_by_name(name).into()),
+    };
 
-    // A string container user contextual information, possibly real name or phone number.
-    // let usergecos = unsafe { crate::sys::libc::to_string(passwd.pw_gecos)? };
+    // Get the libc::passwd by user name
+    let mut buf = vec![0; 2048];
+    loop {
+        let mut res = ptr::null_mut::<libc::passwd>();
+        let mut passwd = unsafe { mem::zeroed::<libc::passwd>() };
+        let ret = unsafe { libc::getpwnam_r(cname.as_ptr(), &mut passwd, buf.as_mut_ptr(), buf.len(), &mut res) };
 
-    // Get the user's real ids as well if applicable
-    let (ruid, rgid) = getrids(uid, gid);
-    let realuser = if uid != ruid {
-        lookup(ruid)?
-    } else {
-        User {
-            uid,
-            gid,
-            name: username.to_string(),
-            home: PathBuf::from(&userhome),
-            shell: PathBuf::from(&usershell),
-            ..Default::default()
+        // Entry didn't fit in the buffer so grow it and retry
+        if ret == libc::ERANGE {
+            buf.resize(buf.len() * 2, 0);
+            continue;
         }
-    };
-    Ok(User {
-        uid,
-        gid,
-        name: username,
-        home: PathBuf::from(&userhome),
-        shell: PathBuf::from(&usershell),
-        ruid,
-        rgid,
-        realname: realuser.name,
-        realhome: realuser.home,
-        realshell: realuser.shell,
-    })
+        if res.is_null() || res != &mut passwd {
+            return Err(UserError::does_not_exist_by_name(name).into());
+        }
+        return to_user(&passwd);
+    }
 }
 
 /// Returns the current user's name.
@@ -489,7 +704,83 @@ pub fn sudo() -> FuResult<()> {
     switchuser(0, 0, 0, 0, 0, 0)
 }
 
+/// UserGuard restores the prior real, effective and saved user and group ids when dropped
+#[derive(Debug)]
+pub struct UserGuard {
+    uids: (u32, u32, u32), // prior real, effective and saved user ids
+    gids: (u32, u32, u32), // prior real, effective and saved group ids
+    restored: bool,        // skip restoring on drop
+}
+
+impl UserGuard {
+    /// Switch back to the prior real, effective and saved user and group ids captured at creation,
+    /// returning a `UserError::SwitchFailed` if they couldn't be restored.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let guard = user::switch_scoped(user::getuid(), user::getgid()).unwrap();
+    /// assert!(guard.restore().is_ok());
+    /// ```
+    pub fn restore(mut self) -> FuResult<()> {
+        self.restored = true;
+        self.switchback()
+    }
+
+    /// Private implementation to switch back to the prior ids. Unlike `switchuser` the user ids
+    /// are restored first as the privileges to restore the group ids may depend on them.
+    fn switchback(&self) -> FuResult<()> {
+        let (ruid, euid, suid) = self.uids;
+        let (rgid, egid, sgid) = self.gids;
+        match unsafe { libc::setresuid(ruid, euid, suid) } {
+            0 => match unsafe { libc::setresgid(rgid, egid, sgid) } {
+                0 => Ok(()),
+                _ => Err(UserError::switch_failed(ruid, euid).into()),
+            },
+            _ => Err(UserError::switch_failed(ruid, euid).into()),
+        }
+    }
+}
+
+impl Drop for UserGuard {
+    fn drop(&mut self) {
+        if !self.restored {
+            let _ = self.switchback();
+        }
+    }
+}
+
+/// Switches the real and effective ids to the given user `uid` and group `gid` while saving the
+/// current effective ids so that the returned `UserGuard` can switch back to the prior ids when
+/// dropped or when `restore` is called. This is the safe way to perform a single operation as
+/// another user. Returns a `UserError::SwitchFailed` if the switch isn't allowed.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// {
+///     let _guard = user::switch_scoped(user::getuid(), user::getgid()).unwrap();
+///     assert_eq!(user::geteuid(), user::getuid());
+/// }
+/// ```
+pub fn switch_scoped(uid: u32, gid: u32) -> FuResult<UserGuard> {
+    let (mut ruid, mut euid, mut suid) = (0, 0, 0);
+    let (mut rgid, mut egid, mut sgid) = (0, 0, 0);
+    unsafe {
+        libc::getresuid(&mut ruid, &mut euid, &mut suid);
+        libc::getresgid(&mut rgid, &mut egid, &mut sgid);
+    }
+
+    // Partial switches will be reverted when the guard is dropped on failure
+    let guard = UserGuard { uids: (ruid, euid, suid), gids: (rgid, egid, sgid), restored: false };
+    switchuser(uid, uid, euid, gid, gid, egid)?;
+    Ok(guard)
+}
+
 /// Switches to another use by setting the real, effective and saved user and group ids.
+/// Returns a `UserError::SwitchFailed` if either the group or user ids couldn't be set.
 ///
 /// ### Examples
 /// ```ignore
@@ -506,24 +797,120 @@ pub fn switchuser(ruid: u32, euid: u32, suid: u32, rgid: u32, egid: u32, sgid: u
     match unsafe { libc::setresgid(rgid, egid, sgid) } {
         0 => match unsafe { libc::setresuid(ruid, euid, suid) } {
             0 => Ok(()),
-            _ => Err(io::Error::last_os_error().into()),
+            _ => Err(UserError::switch_failed(ruid, euid).into()),
         },
-        _ => Err(io::Error::last_os_error().into()),
+        _ => Err(UserError::switch_failed(ruid, euid).into()),
     }
 }
 
+/// Private implementation to convert a libc::passwd object into a User object
+fn to_user(passwd: &libc::passwd) -> FuResult<User> {
+    let uid = passwd.pw_uid;
+    let gid = passwd.pw_gid;
+
+    // User name for the lookedup user. We always want this and it should always exist.
+    let username = unsafe { sys::libc::to_string(passwd.pw_name)? };
+
+    // Will almost always be a single 'x' as the passwd is in the shadow database
+    // let userpwd = unsafe { crate::sys::libc::to_string(passwd.pw_passwd)? };
+
+    // User home directory e.g. '/home/<user>'. Might be a null pointer indicating the system default
+    // should be used
+    let userhome = unsafe { sys::libc::to_string(passwd.pw_dir) }.unwrap_or_default();
+
+    // User shell e.g. '/bin/bash'. Might be a null pointer indicating the system default should be used
+    let usershell = unsafe { sys::libc::to_string(passwd.pw_shell) }.unwrap_or_default();
+
+    // A string container user contextual information, possibly real name or phone number. Might
+    // be a null pointer or empty.
+    let usergecos = unsafe { sys::libc::to_string(passwd.pw_gecos) }.unwrap_or_default();
+
+    // Get the user's real ids as well if applicable
+    let (ruid, rgid) = getrids(uid, gid);
+    let realuser = if uid != ruid {
+        lookup(ruid)?
+    } else {
+        User {
+            uid,
+            gid,
+            name: username.to_string(),
+            home: PathBuf::from(&userhome),
+            shell: PathBuf::from(&usershell),
+            ..Default::default()
+        }
+    };
+    Ok(User {
+        uid,
+        gid,
+        name: username,
+        home: PathBuf::from(&userhome),
+        shell: PathBuf::from(&usershell),
+        gecos: usergecos,
+        ruid,
+        rgid,
+        realname: realuser.name,
+        realhome: realuser.home,
+        realshell: realuser.shell,
+    })
+}
+
 // Unit tests
 // -------------------------------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
 
+    #[test]
+    fn test_user_all() {
+        let users = user::all().unwrap();
+        assert!(!users.is_empty());
+        assert!(users.iter().any(|x| x.uid == 0 && x.name == "root"));
+        assert!(users.iter().any(|x| x.uid == user::getuid()));
+        assert!(users.iter().all(|x| !x.name.is_empty()));
+
+        // enumeration restarts from the beginning each call
+        assert_eq!(user::all().unwrap().len(), users.len());
+    }
+
+    #[test]
+    fn test_user_display_name() {
+        let user = user::current().unwrap();
+        assert_eq!(user.gecos.contains(user.display_name()), true);
+
+        let mut user = user::User { gecos: "John Doe,Room 1,555-1234,555-4321,other".to_string(), ..Default::default() };
+        assert_eq!(user.display_name(), "John Doe");
+        user.gecos = "John Doe".to_string();
+        assert_eq!(user.display_name(), "John Doe");
+        user.gecos = ",Room 1".to_string();
+        assert_eq!(user.display_name(), "");
+        user.gecos = "".to_string();
+        assert_eq!(user.display_name(), "");
+    }
+
+    #[test]
+    fn test_user_groups() {
+        let user = user::current().unwrap();
+        let groups = user.groups().unwrap();
+        assert!(groups.iter().any(|x| x.gid == user.gid));
+        assert!(groups.iter().all(|x| !x.name.is_empty()));
+
+        let root = user::lookup(0).unwrap();
+        assert_eq!(root.groups().unwrap().first().unwrap().name, "root");
+    }
+
     #[test]
     fn test_user_home() {
         let home_str = sys::var("HOME").unwrap();
         let home_path = PathBuf::from(home_str);
         let home_dir = home_path.parent().unwrap();
         assert_eq!(home_dir.to_path_buf(), user::home_dir().unwrap().dir().unwrap());
+
+        // other users come from the passwd database
+        let root = user::home_dir_of("root").unwrap();
+        assert_eq!(root.is_absolute(), true);
+        assert_eq!(root.empty(), false);
+        assert_eq!(root, user::lookup(0).unwrap().home);
+        assert_eq!(user::home_dir_of("foobar-fungus").unwrap_err().downcast_ref::<UserError>(), Some(&UserError::does_not_exist_by_name("foobar-fungus")));
     }
 
     #[test]
@@ -547,6 +934,23 @@ mod tests {
         // assert!(user::setuid(user::getuid()).is_ok());
     }
 
+    #[test]
+    fn test_user_lookup() {
+        assert_eq!(user::lookup(0).unwrap().name, "root");
+        assert_eq!(user::lookup_by_name("root").unwrap().uid, 0);
+        assert_eq!(user::lookup_by_name("root").unwrap().gid, 0);
+        assert_eq!(user::lookup_by_name(user::name().unwrap()).unwrap().uid, user::getuid());
+        assert_eq!(user::lookup_by_name("fungus-bogus").unwrap_err().downcast_ref::<UserError>(), Some(&UserError::does_not_exist_by_name("fungus-bogus")));
+        assert_eq!(user::lookup_by_name("foo\0bar").unwrap_err().downcast_ref::<UserError>(), Some(&UserError::does_not_exist_by_name("foo\0bar")));
+
+        assert_eq!(user::exists_by_id(0), true);
+        assert_eq!(user::exists_by_id(user::getuid()), true);
+        assert_eq!(user::exists_by_id(u32::MAX - 1), false);
+        assert_eq!(user::exists_by_name("root"), true);
+        assert_eq!(user::exists_by_name("fungus-bogus"), false);
+        assert_eq!(user::exists_by_name(""), false);
+    }
+
     #[test]
     fn test_user_dirs() {
         assert!(user::home_dir().is_ok());
@@ -566,6 +970,59 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_scoped_temp_dir() {
+        // removed on drop
+        let path = {
+            let tmpdir = user::scoped_temp_dir("test_scoped_temp_dir").unwrap();
+            assert_eq!(tmpdir.is_dir(), true);
+            assert!(sys::touch(tmpdir.mash("dir1/file1")).is_ok());
+            assert_eq!(tmpdir.path(), tmpdir.as_ref());
+            tmpdir.to_path_buf()
+        };
+        assert_eq!(path.exists(), false);
+
+        // survives when persisted
+        let path = {
+            let tmpdir = user::scoped_temp_dir("test_scoped_temp_dir").unwrap();
+            tmpdir.persist()
+        };
+        assert_eq!(path.is_dir(), true);
+        assert!(sys::remove_all(&path).is_ok());
+    }
+
+    #[test]
+    fn test_switch_scoped() {
+        let ids = (user::getuid(), user::geteuid(), user::getgid(), user::getegid());
+
+        // Switching to the current user is a no-op that is restored on drop
+        {
+            let _guard = user::switch_scoped(user::getuid(), user::getgid()).unwrap();
+            assert_eq!(user::geteuid(), user::getuid());
+        }
+        assert_eq!((user::getuid(), user::geteuid(), user::getgid(), user::getegid()), ids);
+
+        // Or restored explicitly
+        let guard = user::switch_scoped(user::getuid(), user::getgid()).unwrap();
+        assert!(guard.restore().is_ok());
+        assert_eq!((user::getuid(), user::geteuid(), user::getgid(), user::getegid()), ids);
+
+        // Only root may switch to another user
+        if !user::is_root() {
+            assert_eq!(user::switch_scoped(0, 0).unwrap_err().downcast_ref::<UserError>(), Some(&UserError::switch_failed(0, 0)));
+            assert_eq!((user::getuid(), user::geteuid(), user::getgid(), user::getegid()), ids);
+        }
+    }
+
+    #[test]
+    fn test_switchuser() {
+        // Only root may switch to another user
+        if !user::is_root() {
+            assert_eq!(user::switchuser(0, 0, 0, 0, 0, 0).unwrap_err().downcast_ref::<UserError>(), Some(&UserError::switch_failed(0, 0)));
+            assert_eq!(user::getuid(), user::geteuid());
+        }
+    }
+
     #[test]
     fn test_temp_dir() {
         let tmpdir = user::temp_dir("foo").unwrap();
@@ -573,4 +1030,16 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
         assert_eq!(tmpdir.exists(), false);
     }
+
+    #[test]
+    fn test_temp_root() {
+        // honor TMPDIR override
+        let root = PathBuf::from("tests/temp").abs().unwrap().mash("user_temp_root");
+        assert_eq!(super::temp_root_p(Some(root.to_string().unwrap())).unwrap(), root);
+        assert_eq!(super::temp_root_p(Some("tests/temp".to_string())).unwrap(), PathBuf::from("tests/temp").abs().unwrap());
+
+        // fall back on the runtime dir when unset or empty
+        assert_eq!(super::temp_root_p(Some("".to_string())).unwrap(), user::runtime_dir());
+        assert_eq!(super::temp_root_p(None).unwrap(), user::runtime_dir());
+    }
 }